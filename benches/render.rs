@@ -0,0 +1,276 @@
+//! Compares the original `Paragraph`-based game view with the widgets that
+//! write straight into the ratatui buffer, in each render mode.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use ratatui::{Terminal, backend::TestBackend, layout::Rect};
+use spacey::tui::{Assets, Enemy, GameState, Laser, RenderMode, Theme, game_ui};
+
+/// Terminal sizes to compare, from a classic 80x24 up to a large monitor
+const SIZES: &[(u16, u16)] = &[(80, 24), (200, 60), (400, 120)];
+
+/// A game with enough enemies and lasers in flight to exercise every sprite
+fn busy_game() -> GameState {
+    let mut game = GameState::new();
+    game.frame = 42;
+    game.ship_x = 0.2;
+    game.ship_y = -0.1;
+    game.enemies = (0..20)
+        .map(|i| {
+            let mut enemy = Enemy::new(i * 7919);
+            enemy.z = 5.0 + i as f32 * 4.5;
+            enemy
+        })
+        .collect();
+    game.lasers = (0..10)
+        .map(|i| Laser {
+            x: 0.1 * i as f32 - 0.5,
+            y: 0.0,
+            z: 2.0 + i as f32 * 9.0,
+        })
+        .collect();
+    game
+}
+
+fn bench_game_view(c: &mut Criterion) {
+    let game = busy_game();
+    let assets = Assets::embedded();
+    let theme = Theme::default();
+    let mut group = c.benchmark_group("game_view");
+
+    for &(width, height) in SIZES {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut frame = terminal.get_frame();
+        let area = Rect::new(0, 0, width, height);
+        let label = format!("{width}x{height}");
+
+        group.bench_with_input(BenchmarkId::new("paragraph", &label), &area, |b, &area| {
+            b.iter(|| legacy::render_view(&mut frame, area, &game))
+        });
+        for (name, mode) in [
+            ("buffer", RenderMode::Ascii),
+            ("half_block", RenderMode::HalfBlock),
+            ("braille", RenderMode::Braille),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, &label), &area, |b, &area| {
+                b.iter(|| {
+                    game_ui::render_view(frame.buffer_mut(), area, &game, &assets, &theme, mode)
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_game_view);
+criterion_main!(benches);
+
+/// The game view as it was rendered before the buffer widgets: a
+/// `Vec<Vec<char>>` trench converted to `Line`s and one `Paragraph` per entity.
+///
+/// Kept only as a speed baseline. It draws what the game drew then, so its
+/// cells differ from `game_ui::render_view` and aren't compared with it.
+mod legacy {
+    use ratatui::{prelude::*, widgets::Paragraph};
+    use spacey::tui::GameState;
+
+    const PHOSPHOR_GREEN_DIM: Color = Color::Rgb(0, 100, 0);
+    const PHOSPHOR_GREEN_BRIGHT: Color = Color::Rgb(50, 255, 50);
+
+    pub fn render_view(frame: &mut Frame, area: Rect, game: &GameState) {
+        render_trench(frame, area, game);
+        render_enemies(frame, area, game);
+        render_lasers(frame, area, game);
+        render_crosshair(frame, area);
+    }
+
+    fn render_trench(frame: &mut Frame, area: Rect, game: &GameState) {
+        let width = area.width as usize;
+        let height = area.height as usize;
+        let center_x = width / 2;
+        let center_y = height / 2;
+
+        let phase = (game.frame as f32 * 0.5) % 8.0;
+
+        let vp_x = center_x as i32 - (game.ship_x * (width as f32 / 3.0)) as i32;
+        let vp_y = center_y as i32 - (game.ship_y * (height as f32 / 3.0)) as i32;
+
+        let mut buffer = vec![vec![' '; width]; height];
+
+        for (y, row) in buffer.iter_mut().enumerate().take(height) {
+            let dy = y as i32 - vp_y;
+            if dy == 0 {
+                continue;
+            }
+
+            let is_floor = dy > 0;
+            let dist_factor = (height as f32 / dy.abs() as f32).max(1.0);
+            let trench_width_at_depth = (width as f32 / dist_factor) * 0.8;
+
+            let left_wall_x = (vp_x as f32 - trench_width_at_depth) as i32;
+            let right_wall_x = (vp_x as f32 + trench_width_at_depth) as i32;
+
+            if left_wall_x >= 0 && left_wall_x < width as i32 {
+                row[left_wall_x as usize] = if is_floor { '/' } else { '\\' };
+            }
+            if right_wall_x >= 0 && right_wall_x < width as i32 {
+                row[right_wall_x as usize] = if is_floor { '\\' } else { '/' };
+            }
+
+            let z_depth = 100.0 / dist_factor;
+            let grid_pos = (z_depth + phase) % 10.0;
+
+            if grid_pos < 1.0 {
+                let start = left_wall_x.max(0) as usize;
+                let end = right_wall_x.min(width as i32) as usize;
+                for (x, cell) in row.iter_mut().enumerate().take(end).skip(start) {
+                    if x % 10 != 0 {
+                        *cell = '-';
+                    }
+                }
+            }
+
+            let pillar_interval = (z_depth + phase) % 20.0;
+            if pillar_interval < 2.0 {
+                let outer_left = (left_wall_x - 10).max(0);
+                if left_wall_x > 0 {
+                    for cell in row
+                        .iter_mut()
+                        .take(left_wall_x as usize)
+                        .skip(outer_left as usize)
+                    {
+                        *cell = '|';
+                    }
+                }
+                let outer_right = (right_wall_x + 10).min(width as i32);
+                if right_wall_x < width as i32 {
+                    for cell in row
+                        .iter_mut()
+                        .take(outer_right as usize)
+                        .skip(right_wall_x as usize)
+                    {
+                        *cell = '|';
+                    }
+                }
+            }
+        }
+
+        let lines: Vec<Line> = buffer
+            .into_iter()
+            .map(|row| {
+                let s: String = row.into_iter().collect();
+                Line::from(Span::styled(s, Style::default().fg(PHOSPHOR_GREEN_DIM)))
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+
+    fn render_enemies(frame: &mut Frame, area: Rect, game: &GameState) {
+        let width = area.width as f32;
+        let height = area.height as f32;
+        let vp_x = width / 2.0 - (game.ship_x * (width / 3.0));
+        let vp_y = height / 2.0 - (game.ship_y * (height / 3.0));
+
+        for enemy in &game.enemies {
+            if enemy.z <= 1.0 {
+                continue;
+            }
+            let scale = 100.0 / enemy.z;
+            let proj_x = vp_x + (enemy.x * width * scale * 0.5);
+            let proj_y = vp_y + (enemy.y * height * scale * 0.5);
+
+            let sprite = if scale < 2.0 {
+                "."
+            } else if scale < 5.0 {
+                "-o-"
+            } else {
+                r"/-\"
+            };
+
+            if proj_x < 0.0 || proj_x >= width || proj_y < 0.0 || proj_y >= height {
+                continue;
+            }
+
+            let enemy_area = Rect {
+                x: area.x + proj_x as u16,
+                y: area.y + proj_y as u16,
+                width: sprite.len() as u16,
+                height: 1,
+            };
+            let render_area = area.intersection(enemy_area);
+            if render_area.area() > 0 {
+                frame.render_widget(
+                    Paragraph::new(sprite).style(Style::default().fg(PHOSPHOR_GREEN_BRIGHT)),
+                    render_area,
+                );
+            }
+        }
+    }
+
+    fn render_lasers(frame: &mut Frame, area: Rect, game: &GameState) {
+        let width = area.width as f32;
+        let height = area.height as f32;
+        let vp_x = width / 2.0 - (game.ship_x * (width / 3.0));
+        let vp_y = height / 2.0 - (game.ship_y * (height / 3.0));
+
+        for laser in &game.lasers {
+            if laser.z <= 1.0 {
+                continue;
+            }
+            let scale = 100.0 / laser.z;
+            let proj_x = vp_x + (laser.x * width * scale * 0.5);
+            let proj_y = vp_y + (laser.y * height * scale * 0.5);
+
+            if proj_x < 0.0 || proj_x >= width || proj_y < 0.0 || proj_y >= height {
+                continue;
+            }
+
+            let sprite = match scale {
+                s if s < 2.0 => ".",
+                s if s < 5.0 => "|",
+                _ => "||",
+            };
+
+            let laser_area = Rect {
+                x: area.x + proj_x as u16,
+                y: area.y + proj_y as u16,
+                width: sprite.len() as u16,
+                height: 1,
+            };
+            let render_area = area.intersection(laser_area);
+            if render_area.area() > 0 {
+                frame.render_widget(
+                    Paragraph::new(sprite).style(Style::default().fg(Color::Red)),
+                    render_area,
+                );
+            }
+        }
+    }
+
+    fn render_crosshair(frame: &mut Frame, area: Rect) {
+        let center_x = (area.width / 2) as i16;
+        let center_y = (area.height / 2) as i16;
+        let ch_x = center_x - 2;
+
+        if ch_x >= 0 && ch_x < area.width as i16 && center_y < area.height as i16 {
+            let ch_area = Rect {
+                x: area.x + ch_x as u16,
+                y: area.y + center_y as u16,
+                width: 5,
+                height: 1,
+            };
+            let render_area = area.intersection(ch_area);
+            if render_area.area() > 0 {
+                frame.render_widget(
+                    Paragraph::new("[ + ]").style(
+                        Style::default()
+                            .fg(PHOSPHOR_GREEN_BRIGHT)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    render_area,
+                );
+            }
+        }
+    }
+}
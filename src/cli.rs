@@ -0,0 +1,371 @@
+//! Command-line arguments
+//!
+//! Parsed by hand: the game has a handful of flags, and the `--help` text
+//! is tested against them so the two stay in step.
+
+use std::path::PathBuf;
+
+use color_eyre::{
+    Result,
+    eyre::{bail, eyre},
+};
+use spacey::tui::{
+    DEFAULT_FPS, Difficulty, Gauge, HudLayout, Palette,
+    soak::{DEFAULT_GAMES, DEFAULT_MAX_TICKS, Format},
+};
+
+/// Highest frame rate `--fps` accepts
+const MAX_FPS: u32 = 120;
+
+/// What to do
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Open the start menu
+    Menu,
+    /// Start a game straight away
+    Play,
+    /// Watch a recorded game
+    Replay(PathBuf),
+    /// Print the high score table
+    Scores,
+    /// Fly many games with the autopilot and print statistics
+    Soak,
+    /// Print usage
+    Help,
+    /// Print the version
+    Version,
+}
+
+/// Everything given on the command line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cli {
+    pub command: Command,
+    /// Seed for `play` or the first `soak` game, or taken from the clock
+    pub seed: Option<u64>,
+    pub difficulty: Difficulty,
+    pub theme: Option<Palette>,
+    pub fps: u32,
+    /// Gauges on the HUD, or all of them
+    pub hud: Option<HudLayout>,
+    /// Draw without color
+    pub no_color: bool,
+    /// Run `replay` without a terminal and print the result
+    pub headless: bool,
+    /// Games flown by `soak`
+    pub games: u32,
+    /// Ticks each `soak` game may last
+    pub max_ticks: u64,
+    /// How `soak` prints its report
+    pub format: Format,
+}
+
+impl Default for Cli {
+    fn default() -> Self {
+        Self {
+            command: Command::Menu,
+            seed: None,
+            difficulty: Difficulty::default(),
+            theme: None,
+            fps: DEFAULT_FPS,
+            hud: None,
+            no_color: false,
+            headless: false,
+            games: DEFAULT_GAMES,
+            max_ticks: DEFAULT_MAX_TICKS,
+            format: Format::default(),
+        }
+    }
+}
+
+impl Cli {
+    /// Parse the arguments after the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut cli = Self::default();
+        let mut command: Option<Command> = None;
+        // The last flag given that only `soak` understands
+        let mut soak_only = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Options take their value as the next argument or after `=`
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| -> Result<String> {
+                match inline {
+                    Some(value) => Ok(value.to_string()),
+                    None => args.next().ok_or_else(|| eyre!("{name} needs a value")),
+                }
+            };
+            match flag.as_str() {
+                "-h" | "--help" => command = Some(Command::Help),
+                "-V" | "--version" => command = Some(Command::Version),
+                "--seed" => {
+                    let seed = value("--seed")?;
+                    cli.seed = Some(seed.parse().map_err(|_| eyre!("invalid seed `{seed}`"))?);
+                }
+                "--difficulty" => {
+                    let name = value("--difficulty")?;
+                    cli.difficulty = Difficulty::from_name(&name)
+                        .ok_or_else(|| eyre!("unknown difficulty `{name}`"))?;
+                }
+                "--theme" => {
+                    let name = value("--theme")?;
+                    cli.theme = Some(
+                        Palette::from_name(&name).ok_or_else(|| eyre!("unknown theme `{name}`"))?,
+                    );
+                }
+                "--fps" => {
+                    let fps = value("--fps")?;
+                    cli.fps = fps
+                        .parse()
+                        .ok()
+                        .filter(|fps| (1..=MAX_FPS).contains(fps))
+                        .ok_or_else(|| eyre!("--fps must be from 1 to {MAX_FPS}, not `{fps}`"))?;
+                }
+                "--hud" => {
+                    let list = value("--hud")?;
+                    let gauges = list
+                        .split(',')
+                        .map(|name| {
+                            Gauge::from_name(name).ok_or_else(|| eyre!("unknown gauge `{name}`"))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    cli.hud = Some(HudLayout::new(&gauges));
+                }
+                "--games" => {
+                    let games = value("--games")?;
+                    cli.games = games
+                        .parse()
+                        .ok()
+                        .filter(|&games| games > 0)
+                        .ok_or_else(|| eyre!("--games must be a positive number, not `{games}`"))?;
+                    soak_only = Some("--games");
+                }
+                "--max-ticks" => {
+                    let ticks = value("--max-ticks")?;
+                    cli.max_ticks =
+                        ticks
+                            .parse()
+                            .ok()
+                            .filter(|&ticks| ticks > 0)
+                            .ok_or_else(|| {
+                                eyre!("--max-ticks must be a positive number, not `{ticks}`")
+                            })?;
+                    soak_only = Some("--max-ticks");
+                }
+                "--format" => {
+                    let name = value("--format")?;
+                    cli.format =
+                        Format::from_name(&name).ok_or_else(|| eyre!("unknown format `{name}`"))?;
+                    soak_only = Some("--format");
+                }
+                "--no-color" => cli.no_color = true,
+                "--headless" => cli.headless = true,
+                flag if flag.starts_with('-') => return Err(eyre!("unknown option `{flag}`")),
+                word => {
+                    // Help and version win over any command
+                    if matches!(command, Some(Command::Help | Command::Version)) {
+                        continue;
+                    }
+                    // The file may come after flags: `replay --headless run.txt`
+                    if let Some(Command::Replay(file)) = &mut command
+                        && file.as_os_str().is_empty()
+                    {
+                        *file = word.into();
+                        continue;
+                    }
+                    if command.is_some() {
+                        bail!("unexpected argument `{word}`");
+                    }
+                    command = Some(match word {
+                        "play" => Command::Play,
+                        // The file is the next word that isn't a flag
+                        "replay" => Command::Replay(PathBuf::new()),
+                        "scores" => Command::Scores,
+                        "soak" => Command::Soak,
+                        "help" => Command::Help,
+                        other => return Err(eyre!("unknown command `{other}`")),
+                    });
+                }
+            }
+        }
+        cli.command = command.unwrap_or(Command::Menu);
+        if matches!(cli.command, Command::Help | Command::Version) {
+            return Ok(cli);
+        }
+
+        if let Command::Replay(file) = &cli.command
+            && file.as_os_str().is_empty()
+        {
+            bail!("replay needs a file");
+        }
+        if cli.seed.is_some() && !matches!(cli.command, Command::Play | Command::Soak) {
+            bail!("--seed only applies to `play` and `soak`");
+        }
+        if let Some(flag) = soak_only
+            && cli.command != Command::Soak
+        {
+            bail!("{flag} only applies to `soak`");
+        }
+        if cli.headless && !matches!(cli.command, Command::Replay(_)) {
+            bail!("--headless only applies to `replay`");
+        }
+        Ok(cli)
+    }
+}
+
+/// Usage printed by `--help`
+pub fn help() -> String {
+    format!(
+        "\
+Spacey - a first-person ASCII space shooter
+
+Usage: spacey [OPTIONS] [COMMAND]
+
+Commands:
+  play           Start a game straight away
+  replay <FILE>  Watch a recorded game
+  scores         Print the high score table
+  soak           Fly many games with the autopilot and print statistics
+  help           Print this help
+
+Options:
+      --seed <N>            Seed for `play` or `soak` [default: from the clock]
+      --difficulty <LEVEL>  easy, normal or hard [default: normal]
+      --theme <NAME>        Color palette, e.g. amber or high-contrast
+      --fps <N>             Game frames per second, 1 to {MAX_FPS} [default: {DEFAULT_FPS}]
+      --hud <GAUGES>        HUD gauges in order, e.g. shield,wave,score
+      --no-color            Draw without color, like NO_COLOR=1
+      --headless            With `replay`, print the result without drawing
+      --games <N>           Games `soak` flies [default: {DEFAULT_GAMES}]
+      --max-ticks <N>       Frames each `soak` game may last [default: {DEFAULT_MAX_TICKS}]
+      --format <FORMAT>     `soak` report as json or csv [default: json]
+  -h, --help                Print this help
+  -V, --version             Print the version
+"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Cli> {
+        Cli::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_no_arguments_opens_the_menu() {
+        assert_eq!(parse("").unwrap(), Cli::default());
+    }
+
+    #[test]
+    fn test_subcommands_and_flags() {
+        let cli = parse("play --seed 42 --difficulty hard").unwrap();
+        assert_eq!(cli.command, Command::Play);
+        assert_eq!(cli.seed, Some(42));
+        assert_eq!(cli.difficulty, Difficulty::Hard);
+
+        let cli = parse("--theme amber --fps=30 --no-color").unwrap();
+        assert_eq!(cli.command, Command::Menu);
+        assert_eq!(cli.theme, Some(Palette::Amber));
+        assert_eq!(cli.fps, 30);
+        assert!(cli.no_color);
+
+        let cli = parse("--hud shield,Combo,score").unwrap();
+        assert_eq!(
+            cli.hud.unwrap().gauges(),
+            [Gauge::Shield, Gauge::Combo, Gauge::Score]
+        );
+
+        let cli = parse("replay last.replay --headless").unwrap();
+        assert_eq!(cli.command, Command::Replay("last.replay".into()));
+        assert!(cli.headless);
+
+        // Flags may sit between the command and its file
+        let cli = parse("replay --headless run.txt").unwrap();
+        assert_eq!(cli.command, Command::Replay("run.txt".into()));
+        assert!(cli.headless);
+
+        assert_eq!(parse("scores").unwrap().command, Command::Scores);
+
+        let cli = parse("soak --games 50 --seed 3 --max-ticks=900 --format csv").unwrap();
+        assert_eq!(cli.command, Command::Soak);
+        assert_eq!(cli.games, 50);
+        assert_eq!(cli.seed, Some(3));
+        assert_eq!(cli.max_ticks, 900);
+        assert_eq!(cli.format, Format::Csv);
+        assert_eq!(parse("-V").unwrap().command, Command::Version);
+    }
+
+    #[test]
+    fn test_bad_arguments_are_rejected() {
+        for args in [
+            "fly",
+            "play scores",
+            "replay",
+            "replay --headless",
+            "replay one.txt two.txt",
+            "--seed",
+            "--seed ten play",
+            "--seed 1",
+            "--difficulty insane",
+            "--theme plaid",
+            "--fps 0",
+            "--fps 500",
+            "--hud shield,fuel",
+            "--hud shield,",
+            "--warp",
+            "play --headless",
+            "soak --games 0",
+            "soak --max-ticks none",
+            "soak --format xml",
+            "play --games 5",
+            "--format csv",
+        ] {
+            assert!(parse(args).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_help() {
+        for args in [
+            "--help",
+            "-h",
+            "help",
+            "play --help",
+            "--help play extra",
+            "--seed 1 --help",
+            "replay --help",
+        ] {
+            assert_eq!(parse(args).unwrap().command, Command::Help, "{args:?}");
+        }
+
+        let help = help();
+        assert!(help.starts_with("Spacey - a first-person ASCII space shooter\n"));
+        assert!(help.contains("\nUsage: spacey [OPTIONS] [COMMAND]\n"));
+        assert!(help.contains(
+            "      --fps <N>             Game frames per second, 1 to 120 [default: 20]\n"
+        ));
+        // Every flag the parser knows is documented
+        for flag in [
+            "--seed",
+            "--difficulty",
+            "--theme",
+            "--fps",
+            "--hud",
+            "--no-color",
+            "--headless",
+            "--games",
+            "--max-ticks",
+            "--format",
+            "--help",
+            "--version",
+        ] {
+            assert!(help.contains(flag), "{flag}");
+        }
+        // Fits an 80-column terminal
+        assert!(help.lines().all(|line| line.len() <= 80));
+    }
+}
@@ -0,0 +1,6 @@
+//! Spacey - a first-person ASCII space shooter
+//!
+//! The library exposes the TUI so the binary, benchmarks and tests can
+//! drive the same game code.
+
+pub mod tui;
@@ -1,30 +1,124 @@
-mod tui;
+mod cli;
 
+use std::{env, process};
+
+use cli::{Cli, Command};
 use color_eyre::Result;
+use spacey::tui::{
+    self, ColorDepth, GameState, Settings, Start,
+    replay::Replay,
+    soak::{Format, Soak},
+};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
-    // Run the TUI and get the selected menu action
-    let selected = tui::run()?;
+    let cli = match Cli::parse(env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("error: {err}\n\nRun `spacey --help` for usage.");
+            process::exit(2);
+        }
+    };
+    let mut settings = Settings {
+        difficulty: cli.difficulty,
+        fps: cli.fps,
+        ..Settings::default()
+    };
+    if let Some(palette) = cli.theme {
+        settings.palette = palette;
+    }
+    if let Some(hud) = cli.hud.clone() {
+        settings.hud = hud;
+    }
+    let depth = if cli.no_color {
+        ColorDepth::NoColor
+    } else {
+        ColorDepth::detect()
+    };
 
-    // Handle the selected action
-    if let Some(action) = selected {
-        match action {
-            tui::MenuItem::StartGame => {
-                println!("Starting game... (not yet implemented)");
-            }
-            tui::MenuItem::Options => {
-                println!("Options... (not yet implemented)");
+    let start = match cli.command {
+        Command::Menu => Start::Menu,
+        Command::Play => Start::Play { seed: cli.seed },
+        Command::Replay(path) => {
+            let replay = Replay::load(&path)?;
+            if cli.headless {
+                print_result(&replay.play());
+                return Ok(());
             }
-            tui::MenuItem::HighScores => {
-                println!("High Scores... (not yet implemented)");
+            Start::Replay(replay)
+        }
+        Command::Scores => return print_high_scores(),
+        Command::Soak => {
+            let report = Soak {
+                games: cli.games,
+                seed: cli.seed.unwrap_or_else(tui::time_seed),
+                difficulty: cli.difficulty,
+                max_ticks: cli.max_ticks,
+                fps: cli.fps,
             }
-            tui::MenuItem::Exit => {
-                println!("Goodbye!");
+            .run();
+            match cli.format {
+                Format::Json => print!("{}", report.to_json()),
+                Format::Csv => print!("{}", report.to_csv()),
             }
+            return Ok(());
+        }
+        Command::Help => {
+            print!("{}", cli::help());
+            return Ok(());
+        }
+        Command::Version => {
+            println!("spacey {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+    };
+
+    // Run the TUI and finish whatever the player left it for
+    if let Some(exit) = tui::run(settings, depth, start)? {
+        match exit {
+            tui::Exit::HighScores => print_high_scores()?,
+            tui::Exit::Quit => println!("Goodbye!"),
         }
     }
 
     Ok(())
 }
+
+/// Print how a game ended
+fn print_result(game: &GameState) {
+    println!(
+        "score {}  kills {}  waves {}  shield {}  frames {}",
+        game.score, game.kills, game.waves, game.shield, game.frame
+    );
+}
+
+/// Print the saved high score table
+fn print_high_scores() -> Result<()> {
+    let table = match tui::scores::path() {
+        Some(path) => tui::scores::HighScores::load(&path)?,
+        None => Default::default(),
+    };
+    if table.entries().is_empty() {
+        println!("No high scores yet.");
+        return Ok(());
+    }
+
+    println!("RANK   SCORE  KILLS  ACCURACY  COMBO  WAVES");
+    for (rank, entry) in table.entries().iter().enumerate() {
+        let accuracy = entry
+            .accuracy()
+            .map(|a| format!("{:>7}%", (a * 100.0).round() as u32))
+            .unwrap_or_else(|| "      --".to_string());
+        println!(
+            "{:>4}  {:>6}  {:>5}  {}  {:>5}  {:>5}",
+            rank + 1,
+            entry.score,
+            entry.kills,
+            accuracy,
+            entry.best_combo,
+            entry.waves
+        );
+    }
+    Ok(())
+}
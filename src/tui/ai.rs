@@ -0,0 +1,307 @@
+//! Enemy flight behaviors
+//!
+//! Each behavior is a small steering rule. An enemy's pattern lists
+//! behaviors in priority order and the first one that applies this frame
+//! steers it, like a selector node in a behavior tree. Enemies whose
+//! pattern is empty (or where nothing applies) fly straight at the camera.
+
+use std::f32::consts::TAU;
+
+use super::enemy::Enemy;
+
+/// What an enemy can see when choosing how to fly
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Situation {
+    /// Where the player is aiming
+    pub ship_x: f32,
+    pub ship_y: f32,
+}
+
+/// Velocity asked for by a behavior for one frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Steer {
+    pub dx: f32,
+    pub dy: f32,
+    /// Multiple of the normal closing speed; negative flies away
+    pub throttle: f32,
+}
+
+impl Steer {
+    /// Sideways drift at the normal closing speed
+    pub fn drift(dx: f32, dy: f32) -> Self {
+        Self {
+            dx,
+            dy,
+            throttle: 1.0,
+        }
+    }
+}
+
+impl Default for Steer {
+    fn default() -> Self {
+        Self::drift(0.0, 0.0)
+    }
+}
+
+/// A steering rule
+pub trait Behavior {
+    /// Steering for `enemy` this frame, or `None` to let the next behavior
+    /// in the pattern decide
+    fn steer(&self, enemy: &Enemy, situation: &Situation) -> Option<Steer>;
+}
+
+/// Weave from side to side
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Strafe {
+    /// Furthest the weave reaches either side, in view units
+    pub amplitude: f32,
+    /// Frames for one full weave
+    pub period: f32,
+}
+
+impl Behavior for Strafe {
+    fn steer(&self, enemy: &Enemy, _: &Situation) -> Option<Steer> {
+        let rate = TAU / self.period;
+        let dx = self.amplitude * rate * (enemy.age as f32 * rate).cos();
+        Some(Steer::drift(dx, 0.0))
+    }
+}
+
+/// Speed up and swing toward the ship once close enough
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dive {
+    /// Depth the dive starts at
+    pub below: f32,
+    /// Fraction of the gap to the ship closed each frame
+    pub pull: f32,
+    pub throttle: f32,
+}
+
+impl Behavior for Dive {
+    fn steer(&self, enemy: &Enemy, situation: &Situation) -> Option<Steer> {
+        (enemy.z < self.below).then_some(Steer {
+            dx: (situation.ship_x - enemy.x) * self.pull,
+            dy: (situation.ship_y - enemy.y) * self.pull,
+            throttle: self.throttle,
+        })
+    }
+}
+
+/// Fly in a loop while closing in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    /// Radius of the loop, in view units
+    pub radius: f32,
+    /// Frames for one full loop
+    pub period: f32,
+}
+
+impl Behavior for Circle {
+    fn steer(&self, enemy: &Enemy, _: &Situation) -> Option<Steer> {
+        let rate = TAU / self.period;
+        let angle = enemy.age as f32 * rate;
+        let speed = self.radius * rate;
+        Some(Steer::drift(-speed * angle.sin(), speed * angle.cos()))
+    }
+}
+
+/// Jink out of the crosshair while the player is aiming at it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Evade {
+    /// How close to the crosshair counts as targeted, in view units
+    pub radius: f32,
+    pub speed: f32,
+}
+
+impl Behavior for Evade {
+    fn steer(&self, enemy: &Enemy, situation: &Situation) -> Option<Steer> {
+        let (off_x, off_y) = (enemy.x - situation.ship_x, enemy.y - situation.ship_y);
+        if off_x.abs() >= self.radius || off_y.abs() >= self.radius {
+            return None;
+        }
+        // Dead centre breaks to the right
+        let away = |offset: f32| if offset < 0.0 { -1.0 } else { 1.0 };
+        Some(Steer::drift(
+            away(off_x) * self.speed,
+            away(off_y) * self.speed * 0.5,
+        ))
+    }
+}
+
+/// Turn around and leave after a while
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Retreat {
+    /// Frames before turning back
+    pub after: u32,
+    pub throttle: f32,
+}
+
+impl Behavior for Retreat {
+    fn steer(&self, enemy: &Enemy, _: &Situation) -> Option<Steer> {
+        (enemy.age >= self.after).then_some(Steer {
+            dx: 0.0,
+            dy: 0.0,
+            throttle: self.throttle,
+        })
+    }
+}
+
+/// One behavior in a pattern
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Maneuver {
+    Strafe(Strafe),
+    Dive(Dive),
+    Circle(Circle),
+    Evade(Evade),
+    Retreat(Retreat),
+}
+
+impl Maneuver {
+    pub const STRAFE: Self = Maneuver::Strafe(Strafe {
+        amplitude: 0.3,
+        period: 40.0,
+    });
+    pub const DIVE: Self = Maneuver::Dive(Dive {
+        below: 40.0,
+        pull: 0.08,
+        throttle: 1.5,
+    });
+    pub const CIRCLE: Self = Maneuver::Circle(Circle {
+        radius: 0.3,
+        period: 50.0,
+    });
+    pub const EVADE: Self = Maneuver::Evade(Evade {
+        radius: 0.2,
+        speed: 0.06,
+    });
+    pub const RETREAT: Self = Maneuver::Retreat(Retreat {
+        after: 45,
+        throttle: -1.5,
+    });
+
+    /// Get the standard maneuvers
+    pub fn all() -> &'static [Maneuver] {
+        &[
+            Maneuver::STRAFE,
+            Maneuver::DIVE,
+            Maneuver::CIRCLE,
+            Maneuver::EVADE,
+            Maneuver::RETREAT,
+        ]
+    }
+
+    /// Name of a standard maneuver, or `None` for a custom one
+    pub fn name(&self) -> Option<&'static str> {
+        let names = ["strafe", "dive", "circle", "evade", "retreat"];
+        let index = Maneuver::all().iter().position(|m| m == self)?;
+        Some(names[index])
+    }
+
+    /// The steering rule behind this maneuver
+    pub fn behavior(&self) -> &dyn Behavior {
+        match self {
+            Maneuver::Strafe(b) => b,
+            Maneuver::Dive(b) => b,
+            Maneuver::Circle(b) => b,
+            Maneuver::Evade(b) => b,
+            Maneuver::Retreat(b) => b,
+        }
+    }
+}
+
+/// Steering from the first behavior in `pattern` that applies
+pub fn steer(pattern: &[Maneuver], enemy: &Enemy, situation: &Situation) -> Steer {
+    pattern
+        .iter()
+        .find_map(|m| m.behavior().steer(enemy, situation))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enemy_at(x: f32, y: f32, z: f32, age: u32) -> Enemy {
+        let mut enemy = Enemy::new(0);
+        (enemy.x, enemy.y, enemy.z, enemy.age) = (x, y, z, age);
+        enemy
+    }
+
+    #[test]
+    fn test_strafe_weaves_both_ways() {
+        let aim = Situation::default();
+        let start = Maneuver::STRAFE
+            .behavior()
+            .steer(&enemy_at(0.0, 0.0, 80.0, 0), &aim);
+        let half = Maneuver::STRAFE
+            .behavior()
+            .steer(&enemy_at(0.0, 0.0, 80.0, 20), &aim);
+        assert!(start.unwrap().dx > 0.0);
+        assert!(half.unwrap().dx < 0.0);
+    }
+
+    #[test]
+    fn test_dive_waits_then_homes_in() {
+        let aim = Situation {
+            ship_x: 0.5,
+            ship_y: 0.0,
+        };
+        let dive = Maneuver::DIVE.behavior();
+        assert_eq!(dive.steer(&enemy_at(0.0, 0.0, 60.0, 0), &aim), None);
+
+        let steer = dive.steer(&enemy_at(0.0, 0.0, 30.0, 0), &aim).unwrap();
+        assert!(steer.dx > 0.0);
+        assert!(steer.throttle > 1.0);
+    }
+
+    #[test]
+    fn test_circle_returns_to_start() {
+        let Maneuver::Circle(circle) = Maneuver::CIRCLE else {
+            unreachable!();
+        };
+        let mut enemy = enemy_at(0.0, 0.0, 80.0, 0);
+        let (mut x, mut y) = (0.0, 0.0);
+        for age in 0..circle.period as u32 {
+            enemy.age = age;
+            let steer = circle.steer(&enemy, &Situation::default()).unwrap();
+            x += steer.dx;
+            y += steer.dy;
+            assert!(x.abs() <= circle.radius * 2.1 && y.abs() <= circle.radius * 2.1);
+        }
+        assert!(x.abs() < 0.05 && y.abs() < 0.05);
+    }
+
+    #[test]
+    fn test_evade_only_when_targeted() {
+        let aim = Situation {
+            ship_x: 0.1,
+            ship_y: 0.0,
+        };
+        let evade = Maneuver::EVADE.behavior();
+        assert_eq!(evade.steer(&enemy_at(0.6, 0.0, 50.0, 0), &aim), None);
+
+        let steer = evade.steer(&enemy_at(0.0, 0.05, 50.0, 0), &aim).unwrap();
+        assert!(steer.dx < 0.0);
+        assert!(steer.dy > 0.0);
+    }
+
+    #[test]
+    fn test_pattern_picks_first_that_applies() {
+        let pattern = [Maneuver::RETREAT, Maneuver::EVADE, Maneuver::STRAFE];
+        let aim = Situation::default();
+
+        // Young and not targeted: strafing
+        let chosen = steer(&pattern, &enemy_at(0.5, 0.5, 80.0, 0), &aim);
+        assert_eq!(chosen.throttle, 1.0);
+        assert!(chosen.dx > 0.0);
+
+        // Old enough to retreat, even while targeted
+        let chosen = steer(&pattern, &enemy_at(0.0, 0.0, 30.0, 100), &aim);
+        assert!(chosen.throttle < 0.0);
+
+        assert_eq!(
+            steer(&[], &enemy_at(0.0, 0.0, 30.0, 0), &aim),
+            Steer::default()
+        );
+    }
+}
@@ -1,27 +1,77 @@
 //! Application state management
 
-use super::menu::MenuItem;
-
-/// Application state
-pub struct App {
+use super::{
+    menu::{MenuItem, PauseItem},
+    options::OptionItem,
+};
+
+/// Application state: a list of items to choose one from
+///
+/// The start menu is an `App<MenuItem>`, the pause menu an
+/// `App<PauseItem>` and the options screen an `App<OptionItem>`; all three
+/// navigate the same way.
+pub struct App<T: 'static = MenuItem> {
+    /// Items in display order
+    items: &'static [T],
     /// Current menu selection index
     selected_index: usize,
     /// Whether the app is still running
     running: bool,
     /// The action selected by the user (if any)
-    selected_action: Option<MenuItem>,
+    selected_action: Option<T>,
+    /// A message shown in place of the footer prompt
+    notice: Option<String>,
 }
 
 impl App {
     /// Create a new App instance
     pub fn new() -> Self {
+        Self::with_items(MenuItem::all())
+    }
+}
+
+impl App<PauseItem> {
+    /// Create the pause menu with Resume selected
+    pub fn pause() -> Self {
+        Self::with_items(PauseItem::all())
+    }
+}
+
+impl App<OptionItem> {
+    /// Create the options screen with the first row selected
+    pub fn options() -> Self {
+        Self::with_items(OptionItem::all())
+    }
+}
+
+impl<T: Copy> App<T> {
+    /// Create a menu of `items` with the first selected
+    pub fn with_items(items: &'static [T]) -> Self {
         Self {
+            items,
             selected_index: 0,
             running: true,
             selected_action: None,
+            notice: None,
         }
     }
 
+    /// Show `notice` in place of the footer prompt
+    pub fn with_notice(mut self, notice: Option<String>) -> Self {
+        self.notice = notice;
+        self
+    }
+
+    /// Get the message to show, if any
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// Get the items in display order
+    pub fn items(&self) -> &'static [T] {
+        self.items
+    }
+
     /// Check if the app is still running
     pub fn is_running(&self) -> bool {
         self.running
@@ -33,13 +83,13 @@ impl App {
     }
 
     /// Get the selected action after app exits
-    pub fn selected_action(&self) -> Option<MenuItem> {
+    pub fn selected_action(&self) -> Option<T> {
         self.selected_action
     }
 
     /// Move selection to previous menu item
     pub fn previous(&mut self) {
-        let menu_len = MenuItem::all().len();
+        let menu_len = self.items.len();
         if self.selected_index > 0 {
             self.selected_index -= 1;
         } else {
@@ -49,19 +99,16 @@ impl App {
 
     /// Move selection to next menu item
     pub fn next(&mut self) {
-        let menu_len = MenuItem::all().len();
+        let menu_len = self.items.len();
         self.selected_index = (self.selected_index + 1) % menu_len;
     }
 
     /// Select the current menu item
     pub fn select(&mut self) {
-        let items = MenuItem::all();
-        if let Some(item) = items.get(self.selected_index) {
+        if let Some(item) = self.items.get(self.selected_index) {
             self.selected_action = Some(*item);
-            match item {
-                MenuItem::Exit => self.quit(),
-                _ => self.quit(), // For now, all selections exit
-            }
+            // Every selection closes the menu for the caller to act on
+            self.quit();
         }
     }
 
@@ -69,6 +116,13 @@ impl App {
     pub fn quit(&mut self) {
         self.running = false;
     }
+
+    /// Open the menu again after a selection, keeping the same item
+    /// highlighted
+    pub fn reopen(&mut self) {
+        self.running = true;
+        self.selected_action = None;
+    }
 }
 
 impl Default for App {
@@ -117,4 +171,21 @@ mod tests {
         assert!(!app.is_running());
         assert!(app.selected_action().is_some());
     }
+
+    #[test]
+    fn test_pause_menu_selects_and_reopens() {
+        let mut menu = App::pause();
+        assert_eq!(menu.items(), PauseItem::all());
+
+        // Up from Resume wraps to the last item
+        menu.previous();
+        menu.select();
+        assert_eq!(menu.selected_action(), Some(PauseItem::QuitGame));
+        assert!(!menu.is_running());
+
+        menu.reopen();
+        assert!(menu.is_running());
+        assert!(menu.selected_action().is_none());
+        assert_eq!(menu.selected_index(), PauseItem::all().len() - 1);
+    }
 }
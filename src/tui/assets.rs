@@ -0,0 +1,376 @@
+//! Sprite asset loading
+//!
+//! Art lives in text files under `assets/` (the format is documented in
+//! `assets/README.md`). The files are embedded at build time and can be
+//! overridden one by one from a user directory.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, eyre},
+};
+
+use super::sprite::{ColorHint, Sprite, SpriteSet};
+
+/// Asset files and their embedded defaults
+const EMBEDDED: &[(&str, &str)] = &[
+    ("title.txt", include_str!("../../assets/title.txt")),
+    ("cockpit.txt", include_str!("../../assets/cockpit.txt")),
+    ("cracks.txt", include_str!("../../assets/cracks.txt")),
+    ("fighter.txt", include_str!("../../assets/fighter.txt")),
+    ("laser.txt", include_str!("../../assets/laser.txt")),
+    ("missile.txt", include_str!("../../assets/missile.txt")),
+    ("asteroid.txt", include_str!("../../assets/asteroid.txt")),
+    ("debris.txt", include_str!("../../assets/debris.txt")),
+];
+
+/// All art used by the menu and game
+#[derive(Clone, Debug)]
+pub struct Assets {
+    pub title: SpriteSet,
+    pub cockpit: SpriteSet,
+    /// Cockpit variants shown as the shield drops
+    pub cockpit_damaged: SpriteSet,
+    pub cockpit_critical: SpriteSet,
+    /// Cracked glass over the view at low shield
+    pub cracks: SpriteSet,
+    pub fighter: SpriteSet,
+    pub laser: SpriteSet,
+    pub missile: SpriteSet,
+    pub asteroid: SpriteSet,
+    /// Wreckage floating in trenches and canyons
+    pub debris: SpriteSet,
+}
+
+impl Assets {
+    /// Load the embedded art only
+    pub fn embedded() -> Self {
+        Self::load(None).expect("embedded assets are valid")
+    }
+
+    /// Load assets, preferring files found in `user_dir` over embedded ones
+    pub fn load(user_dir: Option<&Path>) -> Result<Self> {
+        let mut sprites = HashMap::new();
+
+        for (file, embedded) in EMBEDDED {
+            let override_path = user_dir.map(|dir| dir.join(file)).filter(|p| p.is_file());
+            let parsed = match override_path {
+                Some(path) => {
+                    let source = fs::read_to_string(&path)
+                        .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+                    parse(&source, &path.display().to_string())?
+                }
+                None => parse(embedded, &format!("embedded {file}"))?,
+            };
+            for (name, set) in parsed {
+                if sprites.insert(name.clone(), set).is_some() {
+                    bail!("sprite `{name}` is defined more than once");
+                }
+            }
+        }
+
+        let mut take = |name: &str| {
+            sprites
+                .remove(name)
+                .ok_or_else(|| eyre!("missing sprite `{name}`"))
+        };
+        Ok(Self {
+            title: take("title")?,
+            cockpit: take("cockpit")?,
+            cockpit_damaged: take("cockpit_damaged")?,
+            cockpit_critical: take("cockpit_critical")?,
+            cracks: take("cracks")?,
+            fighter: take("fighter")?,
+            laser: take("laser")?,
+            missile: take("missile")?,
+            asteroid: take("asteroid")?,
+            debris: take("debris")?,
+        })
+    }
+}
+
+/// Directory searched for user overrides of the embedded assets
+pub fn user_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("SPACEY_ASSETS_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("spacey").join("assets"))
+}
+
+/// A sprite set while its file is being read
+struct PendingSprite {
+    name: String,
+    color: ColorHint,
+    frames: Vec<(f32, Sprite)>,
+    /// Rows and anchor of the frame currently being read
+    limit: Option<f32>,
+    rows: Vec<String>,
+    anchor: Option<(u16, u16)>,
+}
+
+impl PendingSprite {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            color: ColorHint::default(),
+            frames: Vec::new(),
+            limit: None,
+            rows: Vec::new(),
+            anchor: None,
+        }
+    }
+
+    /// Close the current frame, if any
+    fn end_frame(&mut self) -> Result<()> {
+        let Some(limit) = self.limit.take() else {
+            return Ok(());
+        };
+        if self.rows.is_empty() {
+            bail!("frame has no art rows");
+        }
+
+        let mut sprite = Sprite::new(self.rows.drain(..));
+        if let Some((x, y)) = self.anchor.take() {
+            if x >= sprite.width() || y >= sprite.height() {
+                bail!(
+                    "anchor {x} {y} is outside the {}x{} frame",
+                    sprite.width(),
+                    sprite.height()
+                );
+            }
+            sprite = sprite.with_anchor(x, y);
+        }
+        self.frames.push((limit, sprite));
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(String, SpriteSet)> {
+        self.end_frame()?;
+        match self.frames.last() {
+            None => Err(eyre!("sprite `{}` has no frames", self.name)),
+            Some((limit, _)) if limit.is_finite() => {
+                Err(eyre!("sprite `{}` must end with `frame *`", self.name))
+            }
+            Some(_) => Ok((self.name, SpriteSet::new(self.color, self.frames))),
+        }
+    }
+}
+
+/// Parse one asset file into named sprite sets
+///
+/// `origin` names the file in error messages.
+pub fn parse(source: &str, origin: &str) -> Result<Vec<(String, SpriteSet)>> {
+    let mut sprites = Vec::new();
+    let mut current: Option<PendingSprite> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let at = || format!("{origin}:{}", index + 1);
+        parse_line(line, &mut current, &mut sprites).wrap_err_with(at)?;
+    }
+
+    if let Some(sprite) = current {
+        sprites.push(sprite.finish().wrap_err_with(|| origin.to_string())?);
+    }
+    Ok(sprites)
+}
+
+fn parse_line(
+    line: &str,
+    current: &mut Option<PendingSprite>,
+    sprites: &mut Vec<(String, SpriteSet)>,
+) -> Result<()> {
+    if let Some(row) = line.strip_prefix(':') {
+        return match current {
+            Some(sprite) if sprite.limit.is_some() => {
+                sprite.rows.push(row.trim_end().to_string());
+                Ok(())
+            }
+            _ => Err(eyre!("art row outside a frame")),
+        };
+    }
+
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(());
+    }
+
+    let mut words = line.split_whitespace();
+    let directive = words.next().unwrap_or_default();
+    let args: Vec<&str> = words.collect();
+
+    if directive == "sprite" {
+        let [name] = args[..] else {
+            bail!("expected `sprite <name>`");
+        };
+        if let Some(sprite) = current.take() {
+            sprites.push(sprite.finish()?);
+        }
+        *current = Some(PendingSprite::new(name));
+        return Ok(());
+    }
+
+    let Some(sprite) = current else {
+        bail!("`{directive}` before any `sprite`");
+    };
+    match (directive, &args[..]) {
+        ("color", [hint]) => {
+            sprite.color =
+                ColorHint::from_name(hint).ok_or_else(|| eyre!("unknown color hint `{hint}`"))?;
+        }
+        ("frame", [limit]) => {
+            sprite.end_frame()?;
+            let limit = if *limit == "*" {
+                f32::INFINITY
+            } else {
+                limit
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|l| l.is_finite())
+                    .ok_or_else(|| eyre!("invalid frame limit `{limit}`"))?
+            };
+            if let Some((previous, _)) = sprite.frames.last()
+                && limit <= *previous
+            {
+                bail!("frame limits must increase");
+            }
+            sprite.limit = Some(limit);
+        }
+        ("anchor", [x, y]) => {
+            if sprite.limit.is_none() {
+                bail!("`anchor` outside a frame");
+            }
+            let x = x.parse().wrap_err("invalid anchor column")?;
+            let y = y.parse().wrap_err("invalid anchor row")?;
+            sprite.anchor = Some((x, y));
+        }
+        ("color" | "frame" | "anchor", _) => {
+            bail!("wrong number of arguments to `{directive}`");
+        }
+        _ => {
+            bail!("unknown directive `{directive}`");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_text(result: Result<Vec<(String, SpriteSet)>>) -> String {
+        format!("{:#}", result.unwrap_err())
+    }
+
+    #[test]
+    fn test_embedded_assets_are_valid() {
+        let assets = Assets::embedded();
+        assert_eq!(assets.fighter.color(), ColorHint::Bright);
+        assert_eq!(assets.fighter.frame(1.0).rows(), ["."]);
+        assert_eq!(assets.cockpit.frame(20.0).height(), 8);
+        assert_eq!(assets.cockpit.frame(40.0).height(), 14);
+        assert_eq!(assets.cockpit.frame(40.0).anchor(), (22, 0));
+        assert_eq!(assets.asteroid.frame(5.0).rows(), ["@"]);
+        assert_eq!(assets.debris.color(), ColorHint::Dim);
+        assert_eq!(assets.missile.frame(8.0).rows(), ["(o)"]);
+
+        // Damaged variants line up with the intact cockpit
+        for variant in [&assets.cockpit_damaged, &assets.cockpit_critical] {
+            for rows in [20.0, 40.0] {
+                let intact = assets.cockpit.frame(rows);
+                assert_eq!(variant.frame(rows).height(), intact.height());
+                assert_eq!(variant.frame(rows).anchor(), intact.anchor());
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_frames_and_anchor() {
+        let source = "\
+# a comment
+sprite blip
+color dim
+frame 3
+anchor 0 0
+:ab
+:c
+frame *
+: x
+";
+        let sprites = parse(source, "test").unwrap();
+        assert_eq!(sprites.len(), 1);
+
+        let (name, set) = &sprites[0];
+        assert_eq!(name, "blip");
+        assert_eq!(set.color(), ColorHint::Dim);
+        assert_eq!(set.frame(1.0).rows(), ["ab", "c"]);
+        assert_eq!(set.frame(1.0).anchor(), (0, 0));
+        assert_eq!(set.frame(9.0).rows(), [" x"]);
+    }
+
+    #[test]
+    fn test_parse_errors_report_line() {
+        let err = error_text(parse("sprite a\n:row\n", "a.txt"));
+        assert!(err.contains("a.txt:2"), "{err}");
+        assert!(err.contains("art row outside a frame"), "{err}");
+
+        let err = error_text(parse("sprite a\nframe *\n:x\nglow on\n", "a.txt"));
+        assert!(err.contains("a.txt:4"), "{err}");
+        assert!(err.contains("unknown directive `glow`"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_validates_frames() {
+        let err = error_text(parse("sprite a\nframe 5\n:x\n", "t"));
+        assert!(err.contains("must end with `frame *`"), "{err}");
+
+        let err = error_text(parse("sprite a\nframe 5\n:x\nframe 2\n", "t"));
+        assert!(err.contains("frame limits must increase"), "{err}");
+
+        let err = error_text(parse("sprite a\nframe *\nanchor 3 0\n:x\n", "t"));
+        assert!(err.contains("outside the 1x1 frame"), "{err}");
+
+        let err = error_text(parse("sprite a\nframe 2\nframe *\n:x\n", "t"));
+        assert!(err.contains("frame has no art rows"), "{err}");
+    }
+
+    #[test]
+    fn test_user_dir_overrides_embedded_file() {
+        let dir = env::temp_dir().join(format!("spacey-assets-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("laser.txt"),
+            "sprite laser\ncolor bright\nframe *\n:*\n",
+        )
+        .unwrap();
+
+        let assets = Assets::load(Some(&dir)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(assets.laser.color(), ColorHint::Bright);
+        assert_eq!(assets.laser.frame(50.0).rows(), ["*"]);
+        // Files without an override keep the embedded art
+        assert_eq!(assets.fighter, Assets::embedded().fighter);
+    }
+
+    #[test]
+    fn test_invalid_override_is_an_error() {
+        let dir = env::temp_dir().join(format!("spacey-bad-assets-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fighter.txt"), "sprite fighter\nframe 2\n:.\n").unwrap();
+
+        let err = Assets::load(Some(&dir)).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let err = format!("{err:#}");
+        assert!(err.contains("fighter.txt"), "{err}");
+        assert!(err.contains("frame *"), "{err}");
+    }
+}
@@ -0,0 +1,78 @@
+//! Attract mode
+//!
+//! When the start menu sits untouched for a while, the `Autopilot` flies a
+//! demo game behind it, the way arcade cabinets show off between players.
+//! The demo is silent and records nothing; it ends when the ship is lost,
+//! after the game over screen has had a moment, or when a key is pressed.
+
+use std::time::Duration;
+
+use super::{
+    audio::NullSink,
+    autopilot::Autopilot,
+    game::{Difficulty, GameState},
+};
+
+/// How long the menu waits for a key before the demo starts
+pub const IDLE_TIME: Duration = Duration::from_secs(20);
+/// Ticks the game over screen stays up before the demo ends
+const GAME_OVER_TICKS: u32 = 60;
+
+/// A demo game flown by the autopilot
+pub struct Attract {
+    pub game: GameState,
+    pilot: Autopilot,
+    /// Ticks since the ship was lost
+    over_ticks: u32,
+}
+
+impl Attract {
+    /// A demo flown from `seed` at `difficulty`
+    pub fn new(seed: u64, difficulty: Difficulty) -> Self {
+        Self {
+            game: GameState::with_seed(seed).with_difficulty(difficulty),
+            pilot: Autopilot,
+            over_ticks: 0,
+        }
+    }
+
+    /// Advance the demo one tick
+    pub fn tick(&mut self) {
+        if self.game.is_over() {
+            self.over_ticks += 1;
+            return;
+        }
+        self.game.update();
+        for action in self.pilot.actions(&self.game) {
+            action.apply(&mut self.game);
+        }
+        // Nobody is listening, but the queue shouldn't grow all demo long
+        self.game.drain_sounds(&mut NullSink::default());
+    }
+
+    /// Whether the demo has run its course
+    pub fn is_finished(&self) -> bool {
+        self.over_ticks >= GAME_OVER_TICKS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_plays_until_the_ship_is_lost() {
+        let mut demo = Attract::new(3, Difficulty::Hard);
+        let mut ticks = 0;
+        while !demo.is_finished() {
+            demo.tick();
+            ticks += 1;
+            assert!(ticks < 20_000, "demo never ended");
+        }
+        assert!(demo.game.is_over());
+        assert!(demo.game.kills > 0);
+        assert!(demo.game.shots_fired > 0);
+        // The game over screen was held, not skipped
+        assert_eq!(u64::from(GAME_OVER_TICKS), ticks - demo.game.frame);
+    }
+}
@@ -0,0 +1,152 @@
+//! Sound effects
+//!
+//! Game logic queues a `Sound` for each event worth hearing and the main
+//! loop hands them to an `AudioSink`. The default sink rings the terminal
+//! bell for the events that matter most; building with the `synth` feature
+//! plays real sounds through the system audio device when one is available.
+
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+/// Shortest gap between two rings of the bell
+const BELL_GAP: Duration = Duration::from_millis(150);
+
+/// Something that makes a noise
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sound {
+    LaserFire,
+    MissileLaunch,
+    Explosion,
+    ShieldHit,
+    MenuMove,
+    GameOver,
+}
+
+impl Sound {
+    /// Get all sounds
+    pub fn all() -> &'static [Sound] {
+        &[
+            Sound::LaserFire,
+            Sound::MissileLaunch,
+            Sound::Explosion,
+            Sound::ShieldHit,
+            Sound::MenuMove,
+            Sound::GameOver,
+        ]
+    }
+}
+
+/// Where sounds are sent to be played
+pub trait AudioSink {
+    /// Start playing `sound`; it mixes with anything already playing
+    fn play(&mut self, sound: Sound);
+}
+
+/// Rings the terminal bell for hits, explosions and game over
+///
+/// Shots and menu moves stay silent, and rings closer together than
+/// `BELL_GAP` are dropped, so holding fire doesn't turn into one long beep.
+pub struct Bell<W: Write> {
+    out: W,
+    last_ring: Option<Instant>,
+}
+
+impl Bell<io::Stdout> {
+    /// Ring the bell of the terminal on standard output
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl<W: Write> Bell<W> {
+    /// Ring the bell by writing to `out`
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            last_ring: None,
+        }
+    }
+}
+
+impl<W: Write> AudioSink for Bell<W> {
+    fn play(&mut self, sound: Sound) {
+        if !matches!(sound, Sound::Explosion | Sound::ShieldHit | Sound::GameOver) {
+            return;
+        }
+        let now = Instant::now();
+        if self.last_ring.is_some_and(|last| now - last < BELL_GAP) {
+            return;
+        }
+        self.last_ring = Some(now);
+        // A bell that can't be written is just a missed beep
+        let _ = self.out.write_all(b"\x07").and_then(|_| self.out.flush());
+    }
+}
+
+/// Plays nothing but remembers what it was asked to play
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NullSink {
+    events: Vec<Sound>,
+}
+
+impl NullSink {
+    /// Sounds played so far, oldest first
+    pub fn events(&self) -> &[Sound] {
+        &self.events
+    }
+}
+
+impl AudioSink for NullSink {
+    fn play(&mut self, sound: Sound) {
+        self.events.push(sound);
+    }
+}
+
+/// The best sink available: the audio device with the `synth` feature,
+/// otherwise the terminal bell
+pub fn default_sink() -> Box<dyn AudioSink> {
+    #[cfg(feature = "synth")]
+    if let Ok(speaker) = super::speaker::Speaker::open() {
+        return Box::new(speaker);
+    }
+    Box::new(Bell::stdout())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bell_rings_for_important_sounds_only() {
+        let mut bell = Bell::new(Vec::new());
+        bell.play(Sound::LaserFire);
+        bell.play(Sound::MenuMove);
+        assert!(bell.out.is_empty());
+
+        bell.play(Sound::ShieldHit);
+        assert_eq!(bell.out, b"\x07");
+    }
+
+    #[test]
+    fn test_bell_drops_rapid_rings() {
+        let mut bell = Bell::new(Vec::new());
+        bell.play(Sound::Explosion);
+        bell.play(Sound::Explosion);
+        assert_eq!(bell.out.len(), 1);
+
+        bell.last_ring = Some(Instant::now() - BELL_GAP);
+        bell.play(Sound::GameOver);
+        assert_eq!(bell.out.len(), 2);
+    }
+
+    #[test]
+    fn test_null_sink_records_in_order() {
+        let mut sink = NullSink::default();
+        for &sound in Sound::all() {
+            sink.play(sound);
+        }
+        assert_eq!(sink.events(), Sound::all());
+    }
+}
@@ -0,0 +1,198 @@
+//! A simple built-in pilot
+//!
+//! The autopilot reads the game state each tick and answers with the same
+//! `Action`s a player would press, so anything it flies can be recorded and
+//! replayed. It lines up on the nearest enemy ahead and fires, launches a
+//! missile whenever a lock is ready, and sidesteps scenery, walls and
+//! enemies about to ram it. It is meant to be predictable, not good.
+
+use super::{game::GameState, replay::Action};
+
+/// Depth within which an enemy is about to reach the ship
+const DANGER_Z: f32 = 12.0;
+/// Depth within which scenery is worth steering around
+const LOOKAHEAD_Z: f32 = 10.0;
+/// Lateral distance at which the pilot counts itself lined up
+const AIM_TOLERANCE: f32 = 0.08;
+/// Margin the pilot keeps from enemies and scenery, a little more than
+/// the hull
+const CLEARANCE: f32 = 0.35;
+/// Margin the pilot keeps from walls: the hull plus one step
+const WALL_CLEARANCE: f32 = 0.2;
+/// How far one steering action moves the ship
+const STEP: f32 = 0.1;
+
+/// Flies the ship by choosing each tick's actions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Autopilot;
+
+impl Autopilot {
+    /// Actions to take this tick, in the order to apply them
+    pub fn actions(&self, game: &GameState) -> Vec<Action> {
+        let mut actions = Vec::new();
+        if game.paused || game.is_over() {
+            return actions;
+        }
+
+        let (target_x, target_y) = self.aim_point(game);
+        let steer = self.steer(game, target_x, target_y);
+        actions.extend(steer);
+
+        // Fire when lined up with anything ahead
+        let lined_up = game.enemies.iter().any(|e| {
+            e.z > 1.0
+                && (e.x - game.ship_x).abs() < AIM_TOLERANCE * 1.5
+                && (e.y - game.ship_y).abs() < AIM_TOLERANCE * 1.5
+        });
+        if lined_up && game.laser_ready() {
+            actions.push(Action::Fire);
+        }
+        if game.locked_enemy().is_some() {
+            actions.push(Action::Missile);
+        }
+        actions
+    }
+
+    /// Where the ship wants to be: in line with the nearest enemy that is
+    /// still far enough off to shoot, or the middle of the view
+    fn aim_point(&self, game: &GameState) -> (f32, f32) {
+        game.enemies
+            .iter()
+            .filter(|e| e.z > DANGER_Z)
+            .min_by(|a, b| a.z.total_cmp(&b.z))
+            .map_or((0.0, 0.0), |e| (e.x, e.y))
+    }
+
+    /// One steering action toward (`x`, `y`), or away from danger
+    fn steer(&self, game: &GameState, x: f32, y: f32) -> Option<Action> {
+        let here = (game.ship_x, game.ship_y);
+        let moves = [
+            (Action::Left, (here.0 - STEP, here.1)),
+            (Action::Right, (here.0 + STEP, here.1)),
+            (Action::Up, (here.0, here.1 - STEP)),
+            (Action::Down, (here.0, here.1 + STEP)),
+        ];
+        let risk = self.danger(game, here);
+        if risk > 0 {
+            // Take the first move that gets clear, else the least bad, and
+            // stay put only if every move makes things worse
+            return moves
+                .iter()
+                .map(|&(action, spot)| (action, self.danger(game, spot)))
+                .filter(|&(_, moved)| moved <= risk)
+                .min_by_key(|&(_, moved)| moved)
+                .map(|(action, _)| action);
+        }
+
+        let dx = x - here.0;
+        let dy = y - here.1;
+        let wanted = if dx.abs() >= dy.abs() && dx.abs() > AIM_TOLERANCE {
+            if dx < 0.0 {
+                Action::Left
+            } else {
+                Action::Right
+            }
+        } else if dy.abs() > AIM_TOLERANCE {
+            if dy < 0.0 { Action::Up } else { Action::Down }
+        } else {
+            return None;
+        };
+        // Hold position rather than steer into trouble
+        let (_, spot) = moves.iter().find(|(action, _)| *action == wanted)?;
+        (self.danger(game, *spot) == 0).then_some(wanted)
+    }
+
+    /// How many things the ship at `spot` is in the way of, counting a
+    /// wall double since scraping one is certain
+    fn danger(&self, game: &GameState, (x, y): (f32, f32)) -> usize {
+        let enemies = game
+            .enemies
+            .iter()
+            .filter(|e| {
+                e.z < DANGER_Z && (e.x - x).abs() < CLEARANCE && (e.y - y).abs() < CLEARANCE
+            })
+            .count();
+        let obstacles = game
+            .obstacles()
+            .iter()
+            .filter(|o| o.z() < LOOKAHEAD_Z && o.hits(x, y, CLEARANCE - STEP))
+            .count();
+        let walls = game
+            .environment
+            .walls(game.distance)
+            .is_some_and(|(left, right)| x - WALL_CLEARANCE < left || x + WALL_CLEARANCE > right);
+        enemies + obstacles + 2 * usize::from(walls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::{enemy::Enemy, environment::Environment};
+
+    fn game_with_enemy(x: f32, y: f32, z: f32) -> GameState {
+        let mut game = GameState::with_seed(1);
+        game.environment = Environment::OpenSpace;
+        game.enemies.clear();
+        let mut enemy = Enemy::new(0);
+        (enemy.x, enemy.y, enemy.z) = (x, y, z);
+        game.enemies.push(enemy);
+        game.frame = 100;
+        game
+    }
+
+    #[test]
+    fn test_lines_up_and_fires() {
+        let pilot = Autopilot;
+        let game = game_with_enemy(0.5, 0.0, 60.0);
+        assert_eq!(pilot.actions(&game), [Action::Right]);
+
+        let game = game_with_enemy(0.0, -0.3, 60.0);
+        assert_eq!(pilot.actions(&game), [Action::Up]);
+
+        let game = game_with_enemy(0.02, 0.0, 60.0);
+        assert_eq!(pilot.actions(&game), [Action::Fire]);
+    }
+
+    #[test]
+    fn test_dodges_an_enemy_about_to_ram() {
+        let game = game_with_enemy(0.0, 0.0, 5.0);
+        let actions = Autopilot.actions(&game);
+        assert!(
+            matches!(
+                actions.first(),
+                Some(Action::Left | Action::Right | Action::Up | Action::Down)
+            ),
+            "{actions:?}"
+        );
+    }
+
+    #[test]
+    fn test_does_nothing_while_paused() {
+        let mut game = game_with_enemy(0.5, 0.0, 60.0);
+        game.toggle_pause();
+        assert!(Autopilot.actions(&game).is_empty());
+    }
+
+    #[test]
+    fn test_survives_longer_than_sitting_still() {
+        let survived = |pilot: Option<Autopilot>| {
+            let mut game = GameState::with_seed(12);
+            for _ in 0..3000 {
+                game.update();
+                for action in pilot.map(|p| p.actions(&game)).unwrap_or_default() {
+                    action.apply(&mut game);
+                }
+            }
+            (game.frame, game.kills)
+        };
+        let (idle_frames, idle_kills) = survived(None);
+        let (flown_frames, flown_kills) = survived(Some(Autopilot));
+        assert_eq!(idle_kills, 0);
+        assert!(flown_kills > 0);
+        assert!(
+            flown_frames >= idle_frames,
+            "{flown_frames} < {idle_frames}"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Key bindings for gameplay
+//!
+//! The game loop and the controls page both read `BINDINGS`, so the help
+//! the player sees always matches the keys that work.
+
+use crossterm::event::KeyCode;
+
+use super::replay::Action;
+
+/// Keys for each action during play, in the order the controls page lists
+/// them
+pub const BINDINGS: &[(Action, &[KeyCode])] = &[
+    (Action::Left, &[KeyCode::Left, KeyCode::Char('a')]),
+    (Action::Right, &[KeyCode::Right, KeyCode::Char('d')]),
+    (Action::Up, &[KeyCode::Up, KeyCode::Char('w')]),
+    (Action::Down, &[KeyCode::Down, KeyCode::Char('s')]),
+    (Action::Fire, &[KeyCode::Char(' ')]),
+    (Action::Missile, &[KeyCode::Char('f')]),
+    (Action::Pause, &[KeyCode::Enter]),
+    (Action::Quit, &[KeyCode::Char('q')]),
+];
+
+/// Action bound to `key`, if any
+pub fn action_for(key: KeyCode) -> Option<Action> {
+    BINDINGS
+        .iter()
+        .find(|(_, keys)| keys.contains(&key))
+        .map(|&(action, _)| action)
+}
+
+/// Name of a key as printed on the keyboard
+pub fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Left => "LEFT".to_string(),
+        KeyCode::Right => "RIGHT".to_string(),
+        KeyCode::Up => "UP".to_string(),
+        KeyCode::Down => "DOWN".to_string(),
+        KeyCode::Enter => "ENTER".to_string(),
+        KeyCode::Esc => "ESC".to_string(),
+        KeyCode::Char(' ') => "SPACE".to_string(),
+        KeyCode::Char(c) => c.to_ascii_uppercase().to_string(),
+        other => format!("{other:?}").to_uppercase(),
+    }
+}
+
+/// One row per action: its label and the keys bound to it
+pub fn help() -> Vec<(&'static str, String)> {
+    BINDINGS
+        .iter()
+        .map(|(action, keys)| {
+            let names: Vec<String> = keys.iter().map(|&key| key_name(key)).collect();
+            (action.label(), names.join(" / "))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_action_is_bound_once() {
+        for &action in Action::all() {
+            let bound = BINDINGS.iter().filter(|(a, _)| *a == action).count();
+            assert_eq!(bound, 1, "{action:?}");
+        }
+        assert_eq!(action_for(KeyCode::Char('a')), Some(Action::Left));
+        assert_eq!(action_for(KeyCode::Char(' ')), Some(Action::Fire));
+        assert_eq!(action_for(KeyCode::Char('x')), None);
+    }
+
+    #[test]
+    fn test_help_lists_the_bound_keys() {
+        let help = help();
+        assert_eq!(help.len(), BINDINGS.len());
+        assert_eq!(help[0], ("STEER LEFT", "LEFT / A".to_string()));
+        assert!(help.contains(&("FIRE LASERS", "SPACE".to_string())));
+    }
+}
@@ -0,0 +1,224 @@
+//! CRT post-processing
+//!
+//! An optional pass over a finished frame that imitates an old phosphor
+//! monitor: lit cells leave a fading afterglow, every other row is dimmed
+//! like a scanline, and now and then the whole screen flickers.
+
+use ratatui::{buffer::Cell, prelude::*};
+
+use super::theme::Theme;
+
+/// Frames a cell keeps glowing after it stops being drawn
+const AFTERGLOW_FRAMES: u8 = 3;
+/// Brightness of the dimmed scanline rows
+const SCANLINE_LEVEL: f32 = 0.7;
+/// Brightness of the whole screen during a flicker
+const FLICKER_LEVEL: f32 = 0.85;
+/// On average one frame in this many flickers
+const FLICKER_ODDS: u64 = 40;
+
+/// What a cell looked like when it was last lit
+#[derive(Clone, Debug)]
+struct Glow {
+    symbol: String,
+    fg: Color,
+    /// Frames of afterglow left
+    frames: u8,
+}
+
+/// CRT effect state carried from frame to frame
+#[derive(Debug, Default)]
+pub struct Crt {
+    enabled: bool,
+    area: Rect,
+    glow: Vec<Option<Glow>>,
+    frame: u64,
+}
+
+impl Crt {
+    /// Create the effect, switched on or off
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Switch the effect on or off, forgetting any afterglow
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled != self.enabled {
+            self.enabled = enabled;
+            self.glow.clear();
+        }
+    }
+
+    /// Post-process a finished frame
+    ///
+    /// Does nothing when disabled or when the theme is monochrome, where
+    /// dimmed shades would blur the shape cues.
+    pub fn apply(&mut self, buf: &mut Buffer, theme: &Theme) {
+        if !self.enabled || theme.is_monochrome() {
+            self.glow.clear();
+            return;
+        }
+        if self.area != buf.area || self.glow.len() != buf.content.len() {
+            self.area = buf.area;
+            self.glow = vec![None; buf.content.len()];
+        }
+
+        let flicker = flickers(self.frame);
+        self.frame += 1;
+        let width = buf.area.width.max(1) as usize;
+
+        for (i, cell) in buf.content.iter_mut().enumerate() {
+            self.afterglow(i, cell, theme);
+
+            let mut level = 1.0;
+            if (i / width) % 2 == 1 {
+                level *= SCANLINE_LEVEL;
+            }
+            if flicker {
+                level *= FLICKER_LEVEL;
+            }
+            if level < 1.0 {
+                cell.fg = scale(cell.fg, level);
+                cell.bg = scale(cell.bg, level);
+            }
+        }
+    }
+
+    /// Remember lit cells and redraw recently unlit ones, fading out
+    fn afterglow(&mut self, i: usize, cell: &mut Cell, theme: &Theme) {
+        if cell.symbol() != " " {
+            self.glow[i] = Some(Glow {
+                symbol: cell.symbol().to_string(),
+                fg: cell.fg,
+                frames: AFTERGLOW_FRAMES,
+            });
+            return;
+        }
+        let Some(glow) = &mut self.glow[i] else {
+            return;
+        };
+        glow.frames -= 1;
+        if glow.frames == 0 {
+            self.glow[i] = None;
+            return;
+        }
+        let level = f32::from(glow.frames) / f32::from(AFTERGLOW_FRAMES);
+        let fg = match glow.fg {
+            Color::Rgb(..) => scale(glow.fg, level),
+            // Named and indexed colors can't be shaded, so glow in the
+            // theme's dimmest color instead
+            _ => theme.dim,
+        };
+        cell.set_symbol(&glow.symbol).set_fg(fg);
+    }
+}
+
+/// Whether a frame flickers, picked by hashing the frame number so the
+/// pattern looks random but is repeatable
+fn flickers(frame: u64) -> bool {
+    let mut x = frame.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    x ^= x >> 31;
+    x.is_multiple_of(FLICKER_ODDS)
+}
+
+/// Darken a truecolor color; other colors are left alone
+fn scale(color: Color, level: f32) -> Color {
+    match color {
+        Color::Rgb(r, g, b) => {
+            let s = |v: u8| (f32::from(v) * level).round() as u8;
+            Color::Rgb(s(r), s(g), s(b))
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::theme::{ColorDepth, Palette};
+
+    const GREEN: Color = Color::Rgb(0, 200, 0);
+
+    fn frame_with(lit: bool) -> Buffer {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 2));
+        if lit {
+            buf[(1, 0)].set_char('#').set_fg(GREEN);
+        }
+        buf
+    }
+
+    /// Apply the effect to a frame, skipping any flickering frames so
+    /// results are exact
+    fn apply(crt: &mut Crt, mut buf: Buffer, theme: &Theme) -> Buffer {
+        while flickers(crt.frame) {
+            crt.frame += 1;
+        }
+        crt.apply(&mut buf, theme);
+        buf
+    }
+
+    #[test]
+    fn test_afterglow_fades_out() {
+        let theme = Theme::default();
+        let mut crt = Crt::new(true);
+        apply(&mut crt, frame_with(true), &theme);
+
+        let mut levels = Vec::new();
+        for _ in 0..AFTERGLOW_FRAMES {
+            let buf = apply(&mut crt, frame_with(false), &theme);
+            let cell = &buf[(1, 0)];
+            if cell.symbol() == "#" {
+                let Color::Rgb(_, g, _) = cell.fg else {
+                    panic!("afterglow should stay truecolor");
+                };
+                levels.push(g);
+            } else {
+                levels.push(0);
+            }
+        }
+        assert_eq!(levels, [133, 67, 0]);
+    }
+
+    #[test]
+    fn test_afterglow_uses_dim_without_truecolor() {
+        let theme = Theme::new(Palette::Phosphor, ColorDepth::Ansi16);
+        let mut crt = Crt::new(true);
+        let mut buf = frame_with(true);
+        buf[(1, 0)].set_fg(theme.bright);
+        apply(&mut crt, buf, &theme);
+
+        let buf = apply(&mut crt, frame_with(false), &theme);
+        assert_eq!(buf[(1, 0)].symbol(), "#");
+        assert_eq!(buf[(1, 0)].fg, theme.dim);
+    }
+
+    #[test]
+    fn test_scanlines_dim_odd_rows() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 2));
+        for y in 0..2 {
+            buf[(0, y)].set_char('-').set_fg(Color::Rgb(100, 100, 100));
+        }
+        let buf = apply(&mut Crt::new(true), buf, &Theme::default());
+        assert_eq!(buf[(0, 0)].fg, Color::Rgb(100, 100, 100));
+        assert_eq!(buf[(0, 1)].fg, Color::Rgb(70, 70, 70));
+    }
+
+    #[test]
+    fn test_flicker_is_occasional() {
+        let count = (0..4000).filter(|&frame| flickers(frame)).count();
+        assert!((50..200).contains(&count), "{count} flickers");
+    }
+
+    #[test]
+    fn test_disabled_or_monochrome_leaves_frame_alone() {
+        let mono = Theme::new(Palette::Monochrome, ColorDepth::TrueColor);
+        for (mut crt, theme) in [(Crt::new(false), Theme::default()), (Crt::new(true), mono)] {
+            apply(&mut crt, frame_with(true), &theme);
+            let buf = apply(&mut crt, frame_with(false), &theme);
+            assert_eq!(buf, frame_with(false));
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Stepping through a fixed list of choices
+//!
+//! Options like the palette, render mode and difficulty are changed with
+//! left and right, moving to the next or previous value and wrapping
+//! around at either end.
+
+/// A type with a fixed list of values to step through
+pub trait Cycle: Copy + PartialEq + 'static {
+    /// Every value, in display order
+    const ALL: &'static [Self];
+
+    /// The value after this one, wrapping around
+    fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// The value before this one, wrapping around
+    fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// The next value if `forward`, otherwise the previous one
+    fn step(self, forward: bool) -> Self {
+        if forward {
+            self.next()
+        } else {
+            self.previous()
+        }
+    }
+
+    /// Position of this value in `ALL`
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&v| v == self).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Size {
+        Small,
+        Medium,
+        Large,
+    }
+
+    impl Cycle for Size {
+        const ALL: &'static [Self] = &[Size::Small, Size::Medium, Size::Large];
+    }
+
+    #[test]
+    fn test_cycles_both_ways() {
+        assert_eq!(Size::Small.next(), Size::Medium);
+        assert_eq!(Size::Large.next(), Size::Small);
+        assert_eq!(Size::Small.previous(), Size::Large);
+        assert_eq!(Size::Medium.previous(), Size::Small);
+        assert_eq!(Size::Large.step(true), Size::Small);
+        assert_eq!(Size::Small.step(false), Size::Large);
+    }
+}
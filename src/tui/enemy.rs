@@ -1,5 +1,18 @@
 //! Enemy logic and state
 
+use super::ai::{Maneuver, Steer};
+use super::formation::Formation;
+
+/// Depth enemies are spawned at
+pub const SPAWN_Z: f32 = 100.0;
+/// Enemies flying away are gone once past this depth
+const ESCAPE_Z: f32 = 130.0;
+/// Furthest enemies stray from the middle of the view
+const MAX_OFFSET: f32 = 1.2;
+
+/// How fighters fly when their wave doesn't say otherwise
+const FIGHTER_PATTERN: &[Maneuver] = &[Maneuver::EVADE, Maneuver::STRAFE];
+
 /// Enemy types
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum EnemyType {
@@ -7,9 +20,50 @@ pub enum EnemyType {
     // Future types: Scout, Bomber, Boss
 }
 
+impl EnemyType {
+    /// Get all enemy types
+    pub fn all() -> &'static [EnemyType] {
+        &[EnemyType::Fighter]
+    }
+
+    /// Name of the type, as written in save files
+    pub fn label(&self) -> &'static str {
+        match self {
+            EnemyType::Fighter => "fighter",
+        }
+    }
+
+    /// Look up an enemy type by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        EnemyType::all().iter().copied().find(|k| k.label() == name)
+    }
+
+    /// Default flight pattern for this type
+    pub fn pattern(&self) -> &'static [Maneuver] {
+        match self {
+            EnemyType::Fighter => FIGHTER_PATTERN,
+        }
+    }
+}
+
+/// An enemy's place in its formation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    /// Flies its own pattern
+    Leader,
+    /// Holds a slot relative to the leader with the given id
+    Wingman {
+        leader: u32,
+        slot: u8,
+        formation: Formation,
+    },
+}
+
 /// A visual enemy entity
 #[derive(Clone, Debug)]
 pub struct Enemy {
+    /// Identifies the enemy to its wingmen
+    pub id: u32,
     /// X position normalized (-1.0 to 1.0)
     pub x: f32,
     /// Y position normalized (-1.0 to 1.0)
@@ -17,7 +71,14 @@ pub struct Enemy {
     /// Z depth (0.0 to 100.0, where 0 is close and 100 is far)
     pub z: f32,
     /// Enemy type
-    pub _kind: EnemyType,
+    pub kind: EnemyType,
+    /// Behaviors in priority order; empty flies straight
+    pub pattern: &'static [Maneuver],
+    pub role: Role,
+    /// Frames since spawning
+    pub age: u32,
+    /// Depth closed last frame
+    pub closing: f32,
 }
 
 impl Enemy {
@@ -29,22 +90,41 @@ impl Enemy {
         let y = (((seed / 200) % 100) as f32 / 100.0) * 0.5 - 0.25;
 
         Self {
+            id: 0,
             x,
             y,
-            z: 100.0, // Start far away
-            _kind: EnemyType::Fighter,
+            z: SPAWN_Z, // Start far away
+            kind: EnemyType::Fighter,
+            pattern: &[],
+            role: Role::Leader,
+            age: 0,
+            closing: 0.0,
         }
     }
 
     /// Update enemy position (move closer)
     pub fn update(&mut self, speed: f32) {
-        self.z -= speed;
+        self.fly(Steer::default(), speed);
+    }
+
+    /// Move by one frame of `steer`, closing at `speed` times its throttle
+    pub fn fly(&mut self, steer: Steer, speed: f32) {
+        self.x = (self.x + steer.dx).clamp(-MAX_OFFSET, MAX_OFFSET);
+        self.y = (self.y + steer.dy).clamp(-MAX_OFFSET, MAX_OFFSET);
+        self.closing = speed * steer.throttle;
+        self.z -= self.closing;
+        self.age = self.age.saturating_add(1);
     }
 
     /// Check if enemy has passed the player
     pub fn is_visible(&self) -> bool {
         self.z > 0.0
     }
+
+    /// Check if enemy has flown back out of range
+    pub fn has_escaped(&self) -> bool {
+        self.z > ESCAPE_Z
+    }
 }
 
 #[cfg(test)]
@@ -76,4 +156,26 @@ mod tests {
         enemy.update(0.2); // z becomes -0.1
         assert!(!enemy.is_visible());
     }
+
+    #[test]
+    fn test_fly_steers_and_ages() {
+        let mut enemy = Enemy::new(100);
+        enemy.fly(
+            Steer {
+                dx: 0.1,
+                dy: -0.1,
+                throttle: -1.0,
+            },
+            2.0,
+        );
+        assert_eq!((enemy.x, enemy.y), (0.1, -0.35));
+        assert_eq!(enemy.z, SPAWN_Z + 2.0);
+        assert_eq!(enemy.age, 1);
+
+        enemy.z = ESCAPE_Z + 1.0;
+        assert!(enemy.has_escaped());
+
+        enemy.fly(Steer::drift(5.0, 0.0), 1.0);
+        assert_eq!(enemy.x, MAX_OFFSET);
+    }
 }
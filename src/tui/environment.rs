@@ -0,0 +1,411 @@
+//! Level environments
+//!
+//! An environment is the scenery the ship flies through. Each kind has its
+//! own parameters so level data can vary the same scenery, e.g. a narrow
+//! trench with dense pillars or a wide, gently curving canyon.
+
+/// Depth the scenery scrolls toward the camera per frame
+pub const SCROLL_SPEED: f32 = 1.0;
+/// Farthest depth scenery is placed at
+const FAR_Z: f32 = 100.0;
+/// Half the thickness of a beam, in world units
+pub const BEAM_THICKNESS: f32 = 0.1;
+
+/// A straight corridor with a floor grid and pillars on the walls
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrenchShape {
+    /// Distance from the middle to each wall, in world units
+    pub width: f32,
+    /// Depth between floor and ceiling grid lines
+    pub grid_spacing: f32,
+    /// Depth between pillars on the walls
+    pub pillar_spacing: f32,
+    /// Depth between beams hanging from the top of the walls
+    pub beam_spacing: Option<f32>,
+    /// Depth between pieces of floating debris
+    pub debris_spacing: Option<f32>,
+}
+
+/// Open-topped rock walls that sway from side to side
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CanyonShape {
+    /// Distance from the canyon's middle to each wall, in world units
+    pub width: f32,
+    /// How far the middle swings either way, in world units
+    pub curvature: f32,
+    /// Depth between rock strata on the walls
+    pub spacing: f32,
+    /// Depth between pieces of floating debris
+    pub debris_spacing: Option<f32>,
+}
+
+/// Rocks floating in open space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AsteroidShape {
+    /// Depth between one asteroid and the next
+    pub spacing: f32,
+    /// Asteroid radius, in world units
+    pub size: f32,
+    /// Asteroids are scattered this far either side of the middle
+    pub spread: f32,
+}
+
+/// The scenery for a level
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Environment {
+    Trench(TrenchShape),
+    /// Nothing but stars
+    OpenSpace,
+    AsteroidField(AsteroidShape),
+    Canyon(CanyonShape),
+}
+
+/// Environments in level order; later levels repeat the list
+pub const LEVELS: &[Environment] = &[
+    Environment::TRENCH,
+    Environment::Canyon(CanyonShape {
+        width: 0.9,
+        curvature: 0.4,
+        spacing: 12.0,
+        debris_spacing: Some(30.0),
+    }),
+    Environment::AsteroidField(AsteroidShape {
+        spacing: 8.0,
+        size: 0.15,
+        spread: 1.2,
+    }),
+    Environment::OpenSpace,
+    Environment::Trench(TrenchShape {
+        width: 0.6,
+        grid_spacing: 6.0,
+        pillar_spacing: 12.0,
+        beam_spacing: Some(35.0),
+        debris_spacing: Some(15.0),
+    }),
+];
+
+/// A lump of rock or wreckage in world space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rock {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub radius: f32,
+}
+
+/// Solid scenery the ship has to steer around
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Obstacle {
+    /// A girder spanning the trench from wall to wall at height `y`
+    Beam {
+        y: f32,
+        z: f32,
+        span: f32,
+    },
+    Debris(Rock),
+    Asteroid(Rock),
+}
+
+impl Obstacle {
+    /// Depth of the obstacle
+    pub fn z(&self) -> f32 {
+        match self {
+            Obstacle::Beam { z, .. } => *z,
+            Obstacle::Debris(rock) | Obstacle::Asteroid(rock) => rock.z,
+        }
+    }
+
+    /// Whether a ship at view position (`x`, `y`) flies into this obstacle,
+    /// given the ship's own radius
+    pub fn hits(&self, x: f32, y: f32, ship_radius: f32) -> bool {
+        match self {
+            Obstacle::Beam {
+                y: beam_y, span, ..
+            } => x.abs() < *span && (beam_y - y).abs() < BEAM_THICKNESS + ship_radius,
+            Obstacle::Debris(rock) | Obstacle::Asteroid(rock) => {
+                let reach = rock.radius + ship_radius;
+                (rock.x - x).abs() < reach && (rock.y - y).abs() < reach
+            }
+        }
+    }
+}
+
+impl Environment {
+    /// The original trench run
+    pub const TRENCH: Self = Environment::Trench(TrenchShape {
+        width: 0.8,
+        grid_spacing: 10.0,
+        pillar_spacing: 20.0,
+        beam_spacing: Some(60.0),
+        debris_spacing: Some(25.0),
+    });
+
+    /// Environment for a level, counting from 0
+    pub fn for_level(level: usize) -> Self {
+        LEVELS[level % LEVELS.len()]
+    }
+
+    /// Get the display text for this environment
+    pub fn label(&self) -> &'static str {
+        match self {
+            Environment::Trench(_) => "TRENCH",
+            Environment::OpenSpace => "OPEN SPACE",
+            Environment::AsteroidField(_) => "ASTEROID FIELD",
+            Environment::Canyon(_) => "CANYON",
+        }
+    }
+
+    /// Parse an environment name such as `canyon` or `open-space`, with
+    /// default parameters
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase().replace(['_', ' '], "-");
+        let kind = |pick: fn(&Environment) -> bool| LEVELS.iter().copied().find(pick);
+        match name.as_str() {
+            "trench" => Some(Environment::TRENCH),
+            "open-space" | "space" => Some(Environment::OpenSpace),
+            "asteroid-field" | "asteroids" => kind(|e| matches!(e, Environment::AsteroidField(_))),
+            "canyon" => kind(|e| matches!(e, Environment::Canyon(_))),
+            _ => None,
+        }
+    }
+
+    /// Obstacles in view after the scenery has scrolled `distance`
+    ///
+    /// Obstacles sit at fixed points along the route, so the same distance
+    /// always gives the same obstacles.
+    pub fn obstacles(&self, distance: f32) -> Vec<Obstacle> {
+        let mut obstacles = Vec::new();
+        match self {
+            Environment::Trench(shape) => {
+                if let Some(spacing) = shape.beam_spacing {
+                    obstacles.extend(slots(spacing, distance, BEAM_SALT).map(|(z, (a, _, _))| {
+                        Obstacle::Beam {
+                            y: (a * 2.0 - 1.0) * 0.6,
+                            z,
+                            span: shape.width,
+                        }
+                    }));
+                }
+                if let Some(spacing) = shape.debris_spacing {
+                    obstacles.extend(slots(spacing, distance, DEBRIS_SALT).map(|(z, noise)| {
+                        Obstacle::Debris(scatter(noise, 0.0, z, shape.width * 0.8, 0.8, 0.08))
+                    }));
+                }
+            }
+            Environment::Canyon(shape) => {
+                if let Some(spacing) = shape.debris_spacing {
+                    obstacles.extend(slots(spacing, distance, DEBRIS_SALT).map(|(z, noise)| {
+                        let middle = shape.bend(z, distance);
+                        Obstacle::Debris(scatter(noise, middle, z, shape.width * 0.8, 0.8, 0.08))
+                    }));
+                }
+            }
+            Environment::AsteroidField(shape) => {
+                obstacles.extend(slots(shape.spacing, distance, ASTEROID_SALT).map(
+                    |(z, noise)| {
+                        let spread = shape.spread;
+                        Obstacle::Asteroid(scatter(noise, 0.0, z, spread, spread * 0.6, shape.size))
+                    },
+                ));
+            }
+            Environment::OpenSpace => {}
+        }
+        obstacles
+    }
+
+    /// Left and right wall positions where the ship is, if there are walls
+    pub fn walls(&self, distance: f32) -> Option<(f32, f32)> {
+        match self {
+            Environment::Trench(shape) => Some((-shape.width, shape.width)),
+            Environment::Canyon(shape) => {
+                let middle = shape.bend(0.0, distance);
+                Some((middle - shape.width, middle + shape.width))
+            }
+            Environment::OpenSpace | Environment::AsteroidField(_) => None,
+        }
+    }
+}
+
+impl CanyonShape {
+    /// Sideways offset of the canyon's middle at depth `z`, in world units
+    pub fn bend(&self, z: f32, distance: f32) -> f32 {
+        // One full swing every 80 units of depth
+        let phase = (z + distance) * std::f32::consts::TAU / 80.0;
+        self.curvature * phase.sin()
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment::TRENCH
+    }
+}
+
+/// Keeps beams, debris and asteroids from lining up with each other
+const BEAM_SALT: u64 = 0xBEA3;
+const DEBRIS_SALT: u64 = 0xDEB2;
+const ASTEROID_SALT: u64 = 0xA57E_501D;
+
+/// Depths of the slots `spacing` apart that are in view after scrolling
+/// `distance`, each with its repeatable noise
+fn slots(spacing: f32, distance: f32, salt: u64) -> impl Iterator<Item = (f32, (f32, f32, f32))> {
+    let first = (distance / spacing).floor() as i64 + 1;
+    (first..)
+        .map(move |slot| (slot, slot as f32 * spacing - distance))
+        .take_while(|&(_, z)| z <= FAR_Z)
+        .map(move |(slot, z)| (z, slot_noise(slot, salt)))
+}
+
+/// A rock placed by slot noise within `spread_x` and `spread_y` of
+/// (`middle`, 0), between 0.6 and 1.4 times `size`
+fn scatter(
+    (a, b, c): (f32, f32, f32),
+    middle: f32,
+    z: f32,
+    spread_x: f32,
+    spread_y: f32,
+    size: f32,
+) -> Rock {
+    Rock {
+        x: middle + (a * 2.0 - 1.0) * spread_x,
+        y: (b * 2.0 - 1.0) * spread_y,
+        z,
+        radius: size * (0.6 + c * 0.8),
+    }
+}
+
+/// Three repeatable values in `0.0..1.0` for a slot
+fn slot_noise(slot: i64, salt: u64) -> (f32, f32, f32) {
+    let mut x = (slot as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ salt;
+    let mut next = || {
+        x ^= x >> 33;
+        x = x.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+        x ^= x >> 29;
+        (x >> 40) as f32 / (1u64 << 24) as f32
+    };
+    (next(), next(), next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_cycle() {
+        assert_eq!(Environment::for_level(0), Environment::TRENCH);
+        assert_eq!(Environment::for_level(LEVELS.len()), Environment::TRENCH);
+        assert_eq!(Environment::for_level(3), Environment::OpenSpace);
+    }
+
+    #[test]
+    fn test_names_round_trip() {
+        for env in LEVELS {
+            let parsed = Environment::from_name(env.label()).unwrap();
+            assert_eq!(parsed.label(), env.label());
+        }
+        assert_eq!(Environment::from_name("nebula"), None);
+    }
+
+    #[test]
+    fn test_obstacles_scroll_toward_camera() {
+        let field = Environment::from_name("asteroids").unwrap();
+        let now = field.obstacles(50.0);
+        let later = field.obstacles(50.0 + SCROLL_SPEED);
+
+        assert!(!now.is_empty());
+        assert!(now.iter().all(|o| o.z() > 0.0 && o.z() <= FAR_Z));
+        // The nearest asteroid keeps its place and moves closer
+        let (Obstacle::Asteroid(first), Obstacle::Asteroid(first_later)) = (now[0], later[0])
+        else {
+            panic!("asteroid fields hold only asteroids");
+        };
+        assert_eq!(first_later.x, first.x);
+        assert_eq!(first_later.z, first.z - SCROLL_SPEED);
+        assert_eq!(field.obstacles(50.0), now);
+    }
+
+    #[test]
+    fn test_obstacles_per_environment() {
+        let beams = |env: Environment| {
+            env.obstacles(10.0)
+                .iter()
+                .filter(|o| matches!(o, Obstacle::Beam { .. }))
+                .count()
+        };
+        assert!(beams(Environment::TRENCH) > 0);
+        assert_eq!(beams(Environment::from_name("canyon").unwrap()), 0);
+        assert!(Environment::OpenSpace.obstacles(10.0).is_empty());
+
+        let Environment::Trench(shape) = Environment::TRENCH else {
+            unreachable!();
+        };
+        let bare = Environment::Trench(TrenchShape {
+            beam_spacing: None,
+            debris_spacing: None,
+            ..shape
+        });
+        assert!(bare.obstacles(10.0).is_empty());
+    }
+
+    #[test]
+    fn test_canyon_debris_stays_between_walls() {
+        let canyon = Environment::from_name("canyon").unwrap();
+        for distance in (0..400).step_by(7) {
+            let distance = distance as f32;
+            for obstacle in canyon.obstacles(distance) {
+                let Obstacle::Debris(rock) = obstacle else {
+                    panic!("canyons hold only debris");
+                };
+                let Environment::Canyon(shape) = canyon else {
+                    unreachable!();
+                };
+                let middle = shape.bend(rock.z, distance);
+                assert!((rock.x - middle).abs() < shape.width);
+            }
+        }
+    }
+
+    #[test]
+    fn test_obstacle_hits() {
+        let beam = Obstacle::Beam {
+            y: 0.0,
+            z: 1.0,
+            span: 0.8,
+        };
+        assert!(beam.hits(0.5, 0.15, 0.1));
+        assert!(!beam.hits(0.5, 0.5, 0.1));
+        assert!(!beam.hits(0.9, 0.0, 0.1));
+        let rock = Obstacle::Debris(Rock {
+            x: 0.5,
+            y: 0.5,
+            z: 1.0,
+            radius: 0.1,
+        });
+        assert!(rock.hits(0.6, 0.4, 0.1));
+        assert!(!rock.hits(0.0, 0.5, 0.1));
+    }
+
+    #[test]
+    fn test_walls_follow_the_canyon() {
+        let Environment::Canyon(shape) = Environment::from_name("canyon").unwrap() else {
+            panic!("canyon should parse");
+        };
+        let canyon = Environment::Canyon(shape);
+        let (left, right) = canyon.walls(20.0).unwrap();
+        assert_eq!(right - left, shape.width * 2.0);
+        assert_eq!(left + shape.width, shape.bend(0.0, 20.0));
+        assert_eq!(Environment::TRENCH.walls(20.0), Some((-0.8, 0.8)));
+        assert_eq!(Environment::OpenSpace.walls(20.0), None);
+    }
+
+    #[test]
+    fn test_canyon_bends_within_curvature() {
+        let Environment::Canyon(shape) = Environment::from_name("canyon").unwrap() else {
+            panic!("canyon should parse");
+        };
+        let bends: Vec<f32> = (0..100).map(|z| shape.bend(z as f32, 0.0)).collect();
+        assert!(bends.iter().all(|b| b.abs() <= shape.curvature));
+        assert!(bends.iter().any(|b| *b > shape.curvature * 0.9));
+        assert!(bends.iter().any(|b| *b < -shape.curvature * 0.9));
+    }
+}
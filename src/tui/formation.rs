@@ -0,0 +1,340 @@
+//! Enemy waves and formation flying
+//!
+//! A wave is a leader flying its pattern with wingmen holding slots around
+//! it. Wingmen ease toward their slot each frame, so they swing wide on
+//! turns and bunch up when the leader brakes. If the leader is destroyed the
+//! lowest surviving slot takes over and flies the wave's pattern.
+
+use std::f32::consts::TAU;
+
+use super::{
+    ai::Maneuver,
+    enemy::{Enemy, EnemyType, Role, SPAWN_Z},
+};
+
+/// Fraction of the gap to its slot a wingman closes each frame
+const FOLLOW: f32 = 0.3;
+/// Sideways gap between neighbouring slots, in view units
+const SLOT_GAP: f32 = 0.18;
+/// Depth between ranks of a V
+const RANK_DEPTH: f32 = 3.0;
+/// Radius of a swirl, in view units
+const SWIRL_RADIUS: f32 = 0.25;
+/// Frames for a swirl to turn once around its leader
+const SWIRL_PERIOD: f32 = 60.0;
+
+/// How wingmen are arranged around their leader
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Formation {
+    /// No wingmen
+    Single,
+    /// Ranks trailing back either side of the leader
+    V,
+    /// Abreast of the leader
+    Line,
+    /// Circling the leader
+    Swirl,
+}
+
+impl Formation {
+    /// Get all formations
+    pub fn all() -> &'static [Formation] {
+        &[
+            Formation::Single,
+            Formation::V,
+            Formation::Line,
+            Formation::Swirl,
+        ]
+    }
+
+    /// Name of the formation, as written in save files
+    pub fn label(&self) -> &'static str {
+        match self {
+            Formation::Single => "single",
+            Formation::V => "v",
+            Formation::Line => "line",
+            Formation::Swirl => "swirl",
+        }
+    }
+
+    /// Look up a formation by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Formation::all().iter().copied().find(|f| f.label() == name)
+    }
+
+    /// Offset of wingman `slot` (counting from 1) from its leader, `age`
+    /// frames after the leader spawned
+    pub fn offset(&self, slot: u8, age: u32) -> (f32, f32, f32) {
+        let side = if slot % 2 == 1 { -1.0 } else { 1.0 };
+        let rank = slot.div_ceil(2) as f32;
+        match self {
+            Formation::Single => (0.0, 0.0, 0.0),
+            Formation::V => (side * rank * SLOT_GAP, 0.0, rank * RANK_DEPTH),
+            Formation::Line => (side * rank * SLOT_GAP, 0.0, 0.0),
+            Formation::Swirl => {
+                // Slots spread evenly round a circle of six
+                let angle = age as f32 * TAU / SWIRL_PERIOD + slot as f32 * TAU / 6.0;
+                (SWIRL_RADIUS * angle.cos(), SWIRL_RADIUS * angle.sin(), 0.0)
+            }
+        }
+    }
+}
+
+/// A group of enemies spawned together
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wave {
+    pub kind: EnemyType,
+    pub formation: Formation,
+    /// Leader plus wingmen
+    pub size: u8,
+    /// Overrides the enemy type's own pattern
+    pub pattern: Option<&'static [Maneuver]>,
+}
+
+/// Waves in spawn order; later waves repeat the list
+pub const WAVES: &[Wave] = &[
+    Wave {
+        kind: EnemyType::Fighter,
+        formation: Formation::Single,
+        size: 1,
+        pattern: None,
+    },
+    Wave {
+        kind: EnemyType::Fighter,
+        formation: Formation::V,
+        size: 3,
+        pattern: Some(&[Maneuver::DIVE, Maneuver::STRAFE]),
+    },
+    Wave {
+        kind: EnemyType::Fighter,
+        formation: Formation::Line,
+        size: 4,
+        pattern: None,
+    },
+    Wave {
+        kind: EnemyType::Fighter,
+        formation: Formation::Swirl,
+        size: 5,
+        pattern: Some(&[Maneuver::EVADE, Maneuver::CIRCLE]),
+    },
+    Wave {
+        kind: EnemyType::Fighter,
+        formation: Formation::V,
+        size: 5,
+        pattern: Some(&[Maneuver::RETREAT, Maneuver::EVADE, Maneuver::STRAFE]),
+    },
+];
+
+impl Wave {
+    /// Wave to spawn, counting from 0
+    pub fn for_number(number: usize) -> Self {
+        WAVES[number % WAVES.len()]
+    }
+
+    /// Pattern the leader flies
+    pub fn pattern(&self) -> &'static [Maneuver] {
+        self.pattern.unwrap_or_else(|| self.kind.pattern())
+    }
+
+    /// Enemies for this wave with the leader at (`x`, `y`), numbered from
+    /// `first_id`
+    pub fn spawn(&self, x: f32, y: f32, first_id: u32) -> Vec<Enemy> {
+        (0..self.size.max(1))
+            .map(|slot| {
+                let (dx, dy, dz) = self.formation.offset(slot, 0);
+                let mut enemy = Enemy::new(0);
+                enemy.id = first_id + slot as u32;
+                (enemy.x, enemy.y, enemy.z) = (x + dx, y + dy, SPAWN_Z + dz);
+                enemy.kind = self.kind;
+                enemy.pattern = self.pattern();
+                if slot > 0 {
+                    enemy.role = Role::Wingman {
+                        leader: first_id,
+                        slot,
+                        formation: self.formation,
+                    };
+                }
+                enemy
+            })
+            .collect()
+    }
+}
+
+/// Every pattern an enemy can be spawned with
+pub fn patterns() -> impl Iterator<Item = &'static [Maneuver]> {
+    let none: &'static [Maneuver] = &[];
+    [none, EnemyType::Fighter.pattern()]
+        .into_iter()
+        .chain(WAVES.iter().filter_map(|wave| wave.pattern))
+}
+
+/// Ease wingmen toward their slots, promoting a new leader for any wave
+/// whose leader is gone
+pub fn hold_formation(enemies: &mut [Enemy]) {
+    promote_orphans(enemies);
+
+    for i in 0..enemies.len() {
+        let Role::Wingman {
+            leader,
+            slot,
+            formation,
+        } = enemies[i].role
+        else {
+            continue;
+        };
+        let Some(lead) = enemies.iter().find(|e| e.id == leader) else {
+            continue;
+        };
+        let (dx, dy, dz) = formation.offset(slot, lead.age);
+        let target = (lead.x + dx, lead.y + dy, lead.z + dz);
+        let closing = lead.closing;
+
+        // Keep pace with the leader, then drift toward the slot
+        let wingman = &mut enemies[i];
+        wingman.z -= closing;
+        wingman.x += (target.0 - wingman.x) * FOLLOW;
+        wingman.y += (target.1 - wingman.y) * FOLLOW;
+        wingman.z += (target.2 - wingman.z) * FOLLOW;
+        wingman.closing = closing;
+        wingman.age = wingman.age.saturating_add(1);
+    }
+}
+
+/// Give each leaderless group the wingman in its lowest slot as leader
+fn promote_orphans(enemies: &mut [Enemy]) {
+    for i in 0..enemies.len() {
+        let Role::Wingman { leader, .. } = enemies[i].role else {
+            continue;
+        };
+        if enemies.iter().any(|e| e.id == leader) {
+            continue;
+        }
+
+        // Lowest slot still flying with this leader
+        let heir = enemies
+            .iter()
+            .filter_map(|e| match e.role {
+                Role::Wingman {
+                    leader: l, slot, ..
+                } if l == leader => Some((slot, e.id)),
+                _ => None,
+            })
+            .min()
+            .map(|(_, id)| id);
+        let Some(heir) = heir else {
+            continue;
+        };
+
+        for enemy in enemies.iter_mut() {
+            match &mut enemy.role {
+                Role::Wingman { leader: l, .. } if *l == leader && enemy.id == heir => {
+                    enemy.role = Role::Leader;
+                }
+                Role::Wingman {
+                    leader: l, slot, ..
+                } if *l == leader => {
+                    *l = heir;
+                    // Close the gap left by the heir
+                    *slot -= 1;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(formation: Formation, size: u8) -> Vec<Enemy> {
+        Wave {
+            kind: EnemyType::Fighter,
+            formation,
+            size,
+            pattern: None,
+        }
+        .spawn(0.0, 0.0, 10)
+    }
+
+    #[test]
+    fn test_waves_cycle() {
+        assert_eq!(Wave::for_number(0), WAVES[0]);
+        assert_eq!(Wave::for_number(WAVES.len() + 1), WAVES[1]);
+        assert_eq!(WAVES[0].pattern(), EnemyType::Fighter.pattern());
+        assert_ne!(WAVES[1].pattern(), EnemyType::Fighter.pattern());
+    }
+
+    #[test]
+    fn test_spawn_assigns_leader_and_slots() {
+        let enemies = wave(Formation::V, 5);
+        assert_eq!(enemies.len(), 5);
+        assert_eq!(enemies[0].role, Role::Leader);
+        assert_eq!(
+            enemies.iter().map(|e| e.id).collect::<Vec<_>>(),
+            [10, 11, 12, 13, 14]
+        );
+        // The V trails back in ranks either side of the leader
+        assert!(enemies[1].x < 0.0 && enemies[2].x > 0.0);
+        assert_eq!(enemies[1].z, enemies[2].z);
+        assert!(enemies[3].z > enemies[1].z);
+        assert!(matches!(
+            enemies[4].role,
+            Role::Wingman {
+                leader: 10,
+                slot: 4,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_wingmen_follow_the_leader() {
+        let mut enemies = wave(Formation::Line, 3);
+        enemies[0].x = 0.5;
+        enemies[0].z -= 10.0;
+        for _ in 0..30 {
+            hold_formation(&mut enemies);
+        }
+        let (dx, _, _) = Formation::Line.offset(1, 0);
+        assert!((enemies[1].x - (0.5 + dx)).abs() < 0.01);
+        assert!((enemies[1].z - enemies[0].z).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_swirl_turns_around_the_leader() {
+        let start = Formation::Swirl.offset(1, 0);
+        let later = Formation::Swirl.offset(1, 15);
+        assert_ne!(start, later);
+        for (x, y, _) in [start, later] {
+            assert!(((x * x + y * y).sqrt() - SWIRL_RADIUS).abs() < 1e-4);
+        }
+        let (x, y, _) = Formation::Swirl.offset(1, 60);
+        assert!((x - start.0).abs() < 1e-4 && (y - start.1).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_lowest_slot_takes_over() {
+        let mut enemies = wave(Formation::V, 4);
+        enemies.remove(0);
+        hold_formation(&mut enemies);
+
+        assert_eq!(enemies[0].role, Role::Leader);
+        assert!(matches!(
+            enemies[1].role,
+            Role::Wingman {
+                leader: 11,
+                slot: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            enemies[2].role,
+            Role::Wingman {
+                leader: 11,
+                slot: 2,
+                ..
+            }
+        ));
+    }
+}
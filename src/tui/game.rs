@@ -1,7 +1,129 @@
 //! Game state and logic
 #![allow(clippy::manual_is_multiple_of)]
 
-use super::enemy::Enemy;
+use super::{
+    ai::{self, Situation},
+    audio::{AudioSink, Sound},
+    cycle::Cycle,
+    enemy::{Enemy, Role},
+    environment::{Environment, Obstacle, SCROLL_SPEED},
+    formation::{self, Wave},
+    particle::Particles,
+    starfield::Starfield,
+    targeting::{LockOn, MISSILE_SPEED, Missile},
+};
+
+/// Depth enemies close in per frame
+const ENEMY_SPEED: f32 = 1.5;
+/// Depth lasers travel per frame
+const LASER_SPEED: f32 = 2.0;
+/// Frames between enemy waves
+const WAVE_INTERVAL: u64 = 150;
+/// Waves in each level; clearing the last one moves on to the next
+/// level's scenery
+pub const WAVES_PER_LEVEL: usize = 5;
+/// Depth of clear space ahead of the ship when new scenery begins
+const LEVEL_LEAD: f32 = 30.0;
+/// Frames between laser shots
+pub const LASER_COOLDOWN: u64 = 8;
+/// Altitude in metres for each view unit above the floor
+const ALTITUDE_SCALE: f32 = 500.0;
+/// Frames between missile launches
+const MISSILE_COOLDOWN: u64 = 30;
+/// Lateral distance within which a laser hits an enemy
+const HIT_RADIUS: f32 = 0.15;
+/// Lateral distance within which a passing enemy strikes the ship
+const SHIP_RADIUS: f32 = 0.3;
+/// The ship's own size when steering around scenery
+const HULL_RADIUS: f32 = 0.1;
+/// How far the ship is knocked back from a wall it scrapes
+const WALL_BOUNCE: f32 = 0.15;
+/// Points for each enemy destroyed, before the combo multiplier
+pub const KILL_POINTS: u32 = 100;
+/// Bonus for clearing a wave without taking damage
+pub const NO_DAMAGE_BONUS: u32 = 500;
+/// Bonus for clearing a wave with every shot on target, scaled by accuracy
+pub const ACCURACY_BONUS: u32 = 500;
+/// Percent of the starting shield at or below which the ship counts as
+/// damaged
+const DAMAGED_PERCENT: u16 = 60;
+/// Percent of the starting shield at or below which the shield is critical
+const CRITICAL_PERCENT: u16 = 30;
+/// Share of each frame's movement in the ship's smoothed speed
+const SPEED_SMOOTHING: f32 = 0.2;
+/// Frames of shake and flash after the shield is hit
+pub const HIT_FEEDBACK_FRAMES: u8 = 8;
+
+/// Screen shake offsets in cells, indexed by remaining feedback frames
+const SHAKE_PATTERN: [(i16, i16); HIT_FEEDBACK_FRAMES as usize + 1] = [
+    (0, 0),
+    (1, 0),
+    (-1, 0),
+    (1, 1),
+    (-1, -1),
+    (2, 1),
+    (-2, -1),
+    (3, 1),
+    (-3, -1),
+];
+
+/// How hard the enemy pushes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Cycle for Difficulty {
+    const ALL: &'static [Self] = &[Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+}
+
+impl Difficulty {
+    /// Get the display text for this difficulty
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+        }
+    }
+
+    /// Parse a difficulty name such as `hard`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Difficulty::ALL
+            .iter()
+            .copied()
+            .find(|d| d.label().eq_ignore_ascii_case(name))
+    }
+
+    /// Shield level a game starts with
+    pub fn shield(&self) -> u8 {
+        match self {
+            Difficulty::Hard => 6,
+            _ => 10,
+        }
+    }
+
+    /// Frames between enemy waves
+    fn wave_interval(&self) -> u64 {
+        match self {
+            Difficulty::Easy => WAVE_INTERVAL + 30,
+            Difficulty::Normal => WAVE_INTERVAL,
+            Difficulty::Hard => WAVE_INTERVAL - 30,
+        }
+    }
+
+    /// Depth enemies close in per frame
+    fn enemy_speed(&self) -> f32 {
+        match self {
+            Difficulty::Easy => ENEMY_SPEED * 0.8,
+            Difficulty::Normal => ENEMY_SPEED,
+            Difficulty::Hard => ENEMY_SPEED * 1.2,
+        }
+    }
+}
 
 /// Laser projectile
 #[derive(Debug, Clone)]
@@ -14,6 +136,79 @@ pub struct Laser {
     pub z: f32,
 }
 
+/// Points awarded when a wave is over
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WaveBonus {
+    /// Wave number, counting from 1
+    pub wave: usize,
+    /// Awarded if the shield wasn't hit while the wave was in the air
+    pub no_damage: u32,
+    /// Share of `ACCURACY_BONUS` matching the wave's hit rate
+    pub accuracy: u32,
+    /// Frame the bonus was awarded
+    pub frame: u64,
+}
+
+impl WaveBonus {
+    /// Both bonuses together
+    pub fn total(&self) -> u32 {
+        self.no_damage + self.accuracy
+    }
+}
+
+/// Shots and damage counted while a wave is in the air
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct WaveTally {
+    /// Wave number, counting from 1
+    pub(super) wave: usize,
+    /// Id of its first enemy
+    pub(super) first_id: u32,
+    pub(super) size: u32,
+    /// Shot counters when the wave arrived
+    pub(super) shots_fired: u32,
+    pub(super) shots_hit: u32,
+    pub(super) damaged: bool,
+}
+
+impl WaveTally {
+    /// Whether the enemy with `id` flies in this wave
+    fn includes(&self, id: u32) -> bool {
+        id.wrapping_sub(self.first_id) < self.size
+    }
+}
+
+/// What knocked a point off the shield
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HitCause {
+    /// An enemy flew into the ship
+    Enemy,
+    /// Debris, an asteroid or a beam
+    Obstacle,
+    /// Scraping a trench or canyon wall
+    Wall,
+}
+
+impl HitCause {
+    /// Get all causes
+    pub fn all() -> &'static [HitCause] {
+        &[HitCause::Enemy, HitCause::Obstacle, HitCause::Wall]
+    }
+
+    /// Name of the cause, as written in save files and reports
+    pub fn label(&self) -> &'static str {
+        match self {
+            HitCause::Enemy => "enemy",
+            HitCause::Obstacle => "obstacle",
+            HitCause::Wall => "wall",
+        }
+    }
+
+    /// Look up a cause by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        HitCause::all().iter().copied().find(|c| c.label() == name)
+    }
+}
+
 /// Game state during active gameplay
 pub struct GameState {
     /// Ship view X offset (-1.0 to 1.0)
@@ -26,101 +221,516 @@ pub struct GameState {
     pub last_fire_frame: u64,
     /// Active lasers
     pub lasers: Vec<Laser>,
+    /// Homing missiles in flight
+    pub missiles: Vec<Missile>,
+    /// Last frame a missile was launched
+    pub last_missile_frame: u64,
+    /// Enemy the crosshair is locking or locked on to
+    pub lock: LockOn,
     /// Whether the game is paused
     pub paused: bool,
     /// Flag to return to main menu
     pub should_exit: bool,
     /// Current score
     pub score: u32,
-    /// Current altitude
-    pub altitude: u32,
-    /// Shield level (0-10)
+    /// Enemies destroyed
+    pub kills: u32,
+    /// Lasers and missiles fired
+    pub shots_fired: u32,
+    /// Shots that destroyed an enemy
+    pub shots_hit: u32,
+    /// Hits in a row since the last miss or shield hit
+    pub combo: u32,
+    /// Longest combo this game
+    pub best_combo: u32,
+    /// Most recent end-of-wave bonus
+    pub last_bonus: Option<WaveBonus>,
+    /// Shield level, from the difficulty's starting shield down to 0
     pub shield: u8,
     /// Active enemies
     pub enemies: Vec<Enemy>,
+    /// Explosion and impact effects
+    pub particles: Particles,
+    /// Background stars
+    pub stars: Starfield,
+    /// Level being flown, counting from 0
+    pub level: usize,
+    /// Scenery for the current level
+    pub environment: Environment,
+    /// Distance at which the current level's obstacles begin
+    pub scenery_start: f32,
+    /// How far the scenery has scrolled
+    pub distance: f32,
+    /// Frames of hit feedback (shake and flash) remaining
+    pub hit_frames: u8,
+    /// Waves spawned so far
+    pub waves: usize,
+    /// Enemies destroyed from each wave, first wave first
+    pub wave_kills: Vec<u32>,
+    /// What caused each shield hit, in order
+    pub hits: Vec<HitCause>,
+    /// Wave rate, enemy speed and starting shield
+    pub difficulty: Difficulty,
+    /// Sounds waiting to be played
+    sounds: Vec<Sound>,
+    /// Where the ship was at the end of the last frame
+    last_ship: Option<(f32, f32)>,
+    /// Distance the ship moves per frame, smoothed
+    ship_speed: f32,
+    /// Id for the next enemy spawned
+    pub(super) next_enemy_id: u32,
+    /// Waves with enemies still flying
+    pub(super) tallies: Vec<WaveTally>,
+    /// Random state for spawning, so a seed replays the same game
+    pub(super) seed: u64,
 }
 
 impl GameState {
     /// Create a new game state
     pub fn new() -> Self {
+        Self::with_seed(time_seed())
+    }
+
+    /// Create a game state whose enemies always spawn the same way for the
+    /// same seed
+    pub fn with_seed(seed: u64) -> Self {
         let mut state = Self {
             ship_x: 0.0,
             ship_y: 0.0,
             frame: 0,
             last_fire_frame: 0,
             lasers: Vec::new(),
+            missiles: Vec::new(),
+            last_missile_frame: 0,
+            lock: LockOn::default(),
             paused: false,
             should_exit: false,
             score: 0,
-            altitude: 1500,
+            kills: 0,
+            shots_fired: 0,
+            shots_hit: 0,
+            combo: 0,
+            best_combo: 0,
+            last_bonus: None,
             shield: 10,
             enemies: Vec::new(),
+            particles: Particles::new(),
+            stars: Starfield::default(),
+            level: 0,
+            environment: Environment::for_level(0),
+            scenery_start: 0.0,
+            distance: 0.0,
+            hit_frames: 0,
+            waves: 0,
+            wave_kills: Vec::new(),
+            hits: Vec::new(),
+            difficulty: Difficulty::Normal,
+            sounds: Vec::new(),
+            last_ship: None,
+            ship_speed: 0.0,
+            next_enemy_id: 0,
+            tallies: Vec::new(),
+            seed,
         };
         // Add some initial visual enemies
-        state.spawn_enemy();
+        state.spawn_wave();
         state
     }
 
-    fn spawn_enemy(&mut self) {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        // Use a simple seed for now
-        let seed = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+    /// Play at `difficulty` from the start
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self.shield = difficulty.shield();
+        self
+    }
+
+    /// Spawn the next wave with its leader somewhere near the middle
+    fn spawn_wave(&mut self) {
+        let wave = Wave::for_number(self.waves);
+        let x = self.next_unit() * 1.2 - 0.6;
+        let y = self.next_unit() * 0.5 - 0.25;
+
+        let size = wave.size.max(1) as u32;
+        self.enemies.extend(wave.spawn(x, y, self.next_enemy_id));
+        self.waves += 1;
+        self.tallies.push(WaveTally {
+            wave: self.waves,
+            first_id: self.next_enemy_id,
+            size,
+            shots_fired: self.shots_fired,
+            shots_hit: self.shots_hit,
+            damaged: false,
+        });
+        self.next_enemy_id = self.next_enemy_id.wrapping_add(size);
+    }
 
-        self.enemies.push(Enemy::new(seed));
+    /// Next pseudo-random value in `0.0..1.0` (xorshift64)
+    fn next_unit(&mut self) -> f32 {
+        let mut x = self.seed.max(1);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed = x;
+        (x >> 40) as f32 / (1u64 << 24) as f32
     }
 
     /// Update game state each frame
     pub fn update(&mut self) {
-        if !self.paused {
+        if !self.paused && !self.is_over() {
             self.frame = self.frame.wrapping_add(1);
 
-            // Slowly increase altitude and score
-            if self.frame % 10 == 0 {
-                self.altitude = self.altitude.wrapping_add(1);
-                self.score = self.score.wrapping_add(5);
-            }
-
             // Spawn enemies occasionally
-            if self.frame % 150 == 0 {
-                self.spawn_enemy();
+            if self.frame % self.difficulty.wave_interval() == 0 {
+                self.spawn_wave();
             }
 
-            // Move enemies closer
-            let speed = 1.5;
+            // Leaders fly their patterns and wingmen keep station on them
+            let situation = Situation {
+                ship_x: self.ship_x,
+                ship_y: self.ship_y,
+            };
             for enemy in &mut self.enemies {
-                enemy.update(speed);
+                if enemy.role == Role::Leader {
+                    let steer = ai::steer(enemy.pattern, enemy, &situation);
+                    enemy.fly(steer, self.difficulty.enemy_speed());
+                }
             }
+            formation::hold_formation(&mut self.enemies);
 
             // Update lasers
-            let laser_speed = 2.0;
             for laser in &mut self.lasers {
-                laser.z += laser_speed;
+                laser.z += LASER_SPEED;
             }
+            for missile in &mut self.missiles {
+                let target = self.enemies.iter().find(|e| e.id == missile.target);
+                missile.update(target.map(|e| (e.x, e.y)));
+            }
+
+            self.hit_frames = self.hit_frames.saturating_sub(1);
+            self.particles.update();
+            self.stars.update();
+            self.resolve_obstacle_hits();
+            self.distance += SCROLL_SPEED;
+            self.resolve_laser_hits();
+            self.resolve_missile_hits();
+            self.resolve_ship_hits();
+            self.resolve_wall_hits();
 
             // Remove distant objects
-            self.enemies.retain(|e| e.is_visible());
+            self.enemies.retain(|e| e.is_visible() && !e.has_escaped());
+            let fired = self.lasers.len() + self.missiles.len();
             self.lasers.retain(|l| l.z < 100.0);
+            self.missiles.retain(|m| m.z < 100.0);
+            if self.lasers.len() + self.missiles.len() < fired {
+                // A shot flew off without hitting anything
+                self.combo = 0;
+            }
+            self.settle_waves();
+
+            let in_view: Vec<(u32, (f32, f32))> = self
+                .enemies
+                .iter()
+                .filter_map(|e| Some((e.id, self.view_offset(e.x, e.y, e.z)?)))
+                .collect();
+            self.lock.update(in_view);
+            self.track_speed();
+        }
+    }
+
+    /// Fold the ship's movement since the last frame into its speed
+    fn track_speed(&mut self) {
+        let (x, y) = self.last_ship.unwrap_or((self.ship_x, self.ship_y));
+        let moved = (self.ship_x - x).hypot(self.ship_y - y);
+        self.ship_speed += (moved - self.ship_speed) * SPEED_SMOOTHING;
+        self.last_ship = Some((self.ship_x, self.ship_y));
+    }
+
+    /// Where a point appears relative to the crosshair, as fractions of the
+    /// view's width and height, or `None` if it's at or behind the camera
+    pub fn view_offset(&self, x: f32, y: f32, z: f32) -> Option<(f32, f32)> {
+        if z <= 1.0 {
+            return None;
+        }
+        // Matches the game view's projection, with its vanishing point
+        // pushed a third of the view away from the ship
+        let scale = 100.0 / z;
+        Some((
+            x * scale * 0.5 - self.ship_x / 3.0,
+            y * scale * 0.5 - self.ship_y / 3.0,
+        ))
+    }
+
+    /// Height above the floor in metres, from the ship's vertical position
+    ///
+    /// The floor is at `ship_y` 1.0 (the bottom of the view), so the ship
+    /// flies between 0 and 1000 m.
+    pub fn altitude(&self) -> u32 {
+        ((1.0 - self.ship_y) * ALTITUDE_SCALE).round().max(0.0) as u32
+    }
+
+    /// How far the ship moves across the view per frame, smoothed over the
+    /// last few frames, or 0 while paused
+    pub fn speed(&self) -> f32 {
+        if self.paused { 0.0 } else { self.ship_speed }
+    }
+
+    /// Fraction of shots that hit, or `None` before the first shot
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots_fired > 0).then(|| self.shots_hit as f32 / self.shots_fired as f32)
+    }
+
+    /// Score multiplier earned by the current combo: one more for every five
+    /// hits in a row, up to eight
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.combo / 5).min(8)
+    }
+
+    /// Count the enemy `id` destroyed by a shot and score it at the combo's
+    /// multiplier
+    fn record_kill(&mut self, id: u32) {
+        if let Some(tally) = self.tallies.iter().find(|t| t.includes(id)) {
+            if self.wave_kills.len() < tally.wave {
+                self.wave_kills.resize(tally.wave, 0);
+            }
+            self.wave_kills[tally.wave - 1] += 1;
+        }
+        self.kills += 1;
+        self.shots_hit += 1;
+        self.sounds.push(Sound::Explosion);
+        self.combo += 1;
+        self.best_combo = self.best_combo.max(self.combo);
+        self.score = self.score.saturating_add(KILL_POINTS * self.multiplier());
+    }
+
+    /// Knock a point off the shield, breaking the combo
+    fn take_hit(&mut self, cause: HitCause) {
+        if self.is_over() {
+            return;
+        }
+        self.shield -= 1;
+        self.hits.push(cause);
+        self.sounds.push(if self.is_over() {
+            Sound::GameOver
+        } else {
+            Sound::ShieldHit
+        });
+        self.hit_frames = HIT_FEEDBACK_FRAMES;
+        self.particles.spawn_shield_hit(0.0, 0.0);
+        self.combo = 0;
+        for tally in &mut self.tallies {
+            tally.damaged = true;
+        }
+    }
+
+    /// Award bonuses for waves whose enemies are all destroyed or gone
+    fn settle_waves(&mut self) {
+        let (over, flying): (Vec<_>, Vec<_>) = self
+            .tallies
+            .iter()
+            .partition(|t| !self.enemies.iter().any(|e| t.includes(e.id)));
+        self.tallies = flying;
+
+        for tally in over {
+            if tally.wave % WAVES_PER_LEVEL == 0 {
+                self.enter_level(self.level.max(tally.wave / WAVES_PER_LEVEL));
+            }
+            let fired = self.shots_fired - tally.shots_fired;
+            let hit = self.shots_hit - tally.shots_hit;
+            // A missile can land after the wave it was fired at is over
+            let accuracy = (ACCURACY_BONUS * hit.min(fired) + fired / 2)
+                .checked_div(fired)
+                .unwrap_or(0);
+            let bonus = WaveBonus {
+                wave: tally.wave,
+                no_damage: if tally.damaged { 0 } else { NO_DAMAGE_BONUS },
+                accuracy,
+                frame: self.frame,
+            };
+            self.score = self.score.saturating_add(bonus.total());
+            self.last_bonus = Some(bonus);
+        }
+    }
+
+    /// Switch to `level`'s scenery, starting a little way ahead with the
+    /// ship moved inside any new walls
+    fn enter_level(&mut self, level: usize) {
+        if level == self.level {
+            return;
+        }
+        self.level = level;
+        self.environment = Environment::for_level(level);
+        self.scenery_start = self.distance + LEVEL_LEAD;
+        if let Some((left, right)) = self.environment.walls(self.distance) {
+            let margin = HULL_RADIUS + WALL_BOUNCE;
+            self.ship_x = self.ship_x.clamp(left + margin, right - margin);
+        }
+    }
+
+    /// Obstacles in view, leaving out the stretch before this level's
+    /// scenery begins
+    pub fn obstacles(&self) -> Vec<Obstacle> {
+        let mut obstacles = self.environment.obstacles(self.distance);
+        obstacles.retain(|o| self.distance + o.z() >= self.scenery_start);
+        obstacles
+    }
+
+    /// Locked enemy, if it's still flying
+    pub fn locked_enemy(&self) -> Option<&Enemy> {
+        let target = self.lock.target()?;
+        self.enemies.iter().find(|e| e.id == target)
+    }
+
+    /// Destroy enemies struck by lasers, consuming the lasers
+    fn resolve_laser_hits(&mut self) {
+        let mut destroyed = vec![false; self.enemies.len()];
+        let enemies = &self.enemies;
+        let particles = &mut self.particles;
+
+        self.lasers.retain(|laser| {
+            let target = enemies.iter().enumerate().position(|(i, enemy)| {
+                // Each pair closes by this much depth per frame, so checking
+                // half of it either side catches every crossing exactly once
+                let window = (enemy.closing.max(0.0) + LASER_SPEED) / 2.0;
+                !destroyed[i]
+                    && (laser.x - enemy.x).abs() < HIT_RADIUS
+                    && (laser.y - enemy.y).abs() < HIT_RADIUS
+                    && (laser.z - enemy.z).abs() <= window
+            });
+            match target {
+                Some(i) => {
+                    let enemy = &enemies[i];
+                    destroyed[i] = true;
+                    particles.spawn_impact(laser.x, laser.y, enemy.z);
+                    particles.spawn_explosion(enemy.x, enemy.y, enemy.z);
+                    false
+                }
+                None => true,
+            }
+        });
+
+        let killed: Vec<u32> = self
+            .enemies
+            .iter()
+            .zip(&destroyed)
+            .filter(|&(_, &d)| d)
+            .map(|(e, _)| e.id)
+            .collect();
+        let mut destroyed = destroyed.into_iter();
+        self.enemies.retain(|_| !destroyed.next().unwrap_or(false));
+        for id in killed {
+            self.record_kill(id);
+        }
+    }
+
+    /// Destroy enemies that missiles catch up with, consuming the missiles
+    fn resolve_missile_hits(&mut self) {
+        let enemies = &mut self.enemies;
+        let particles = &mut self.particles;
+        let mut killed = Vec::new();
+
+        self.missiles.retain(|missile| {
+            let hit = enemies.iter().position(|enemy| {
+                let window = (enemy.closing.max(0.0) + MISSILE_SPEED) / 2.0;
+                enemy.id == missile.target
+                    && (missile.x - enemy.x).abs() < HIT_RADIUS
+                    && (missile.y - enemy.y).abs() < HIT_RADIUS
+                    && (missile.z - enemy.z).abs() <= window
+            });
+            match hit {
+                Some(i) => {
+                    let enemy = enemies.remove(i);
+                    particles.spawn_explosion(enemy.x, enemy.y, enemy.z);
+                    killed.push(enemy.id);
+                    false
+                }
+                None => true,
+            }
+        });
+        for id in killed {
+            self.record_kill(id);
+        }
+    }
+
+    /// Drain shield for enemies that pass the ship close enough to hit it
+    fn resolve_ship_hits(&mut self) {
+        let mut hits = 0;
+        for enemy in self.enemies.iter().filter(|e| !e.is_visible()) {
+            if (enemy.x - self.ship_x).abs() < SHIP_RADIUS
+                && (enemy.y - self.ship_y).abs() < SHIP_RADIUS
+            {
+                hits += 1;
+            }
+        }
+        for _ in 0..hits {
+            self.take_hit(HitCause::Enemy);
+        }
+    }
+
+    /// Drain shield for obstacles that reach the ship this frame
+    fn resolve_obstacle_hits(&mut self) {
+        let hits = self
+            .obstacles()
+            .into_iter()
+            .filter(|o| o.z() <= SCROLL_SPEED && o.hits(self.ship_x, self.ship_y, HULL_RADIUS))
+            .count();
+        for _ in 0..hits {
+            self.take_hit(HitCause::Obstacle);
         }
     }
 
+    /// Drain shield and knock the ship back when it scrapes a wall
+    fn resolve_wall_hits(&mut self) {
+        let Some((left, right)) = self.environment.walls(self.distance) else {
+            return;
+        };
+        let x = if self.ship_x - HULL_RADIUS < left {
+            left + HULL_RADIUS + WALL_BOUNCE
+        } else if self.ship_x + HULL_RADIUS > right {
+            right - HULL_RADIUS - WALL_BOUNCE
+        } else {
+            return;
+        };
+        self.ship_x = x.clamp(-1.0, 1.0);
+        self.take_hit(HitCause::Wall);
+    }
+
     /// Fire a laser
     pub fn fire_laser(&mut self) {
-        if !self.paused {
+        if !self.paused && !self.is_over() {
             // Cooldown check (e.g., every 8 frames)
-            if self.frame > self.last_fire_frame + 8 {
+            if self.laser_ready() {
                 self.lasers.push(Laser {
                     x: self.ship_x,
                     y: self.ship_y,
                     z: 0.0,
                 });
                 self.last_fire_frame = self.frame;
+                self.shots_fired += 1;
+                self.sounds.push(Sound::LaserFire);
             }
         }
     }
 
+    /// Whether the laser has cooled down since the last shot
+    pub fn laser_ready(&self) -> bool {
+        self.frame > self.last_fire_frame + LASER_COOLDOWN
+    }
+
+    /// Launch a homing missile at the locked enemy
+    pub fn fire_missile(&mut self) {
+        if self.paused || self.is_over() || self.frame <= self.last_missile_frame + MISSILE_COOLDOWN
+        {
+            return;
+        }
+        if let Some(target) = self.locked_enemy().map(|e| e.id) {
+            self.missiles
+                .push(Missile::launch(self.ship_x, self.ship_y, target));
+            self.last_missile_frame = self.frame;
+            self.shots_fired += 1;
+            self.sounds.push(Sound::MissileLaunch);
+        }
+    }
+
     /// Move ship view left
     pub fn move_left(&mut self) {
         if !self.paused {
@@ -149,24 +759,73 @@ impl GameState {
         }
     }
 
+    /// Offset in cells to shake the view by after a shield hit
+    pub fn shake_offset(&self) -> (i16, i16) {
+        if self.paused {
+            return (0, 0);
+        }
+        SHAKE_PATTERN[self.hit_frames.min(HIT_FEEDBACK_FRAMES) as usize]
+    }
+
     /// Toggle pause state
     pub fn toggle_pause(&mut self) {
         self.paused = !self.paused;
     }
 
-    /// Exit to menu (only works when paused)
+    /// Exit to menu (only works when paused or once the game is over)
     pub fn exit_to_menu(&mut self) {
-        if self.paused {
+        if self.paused || self.is_over() {
             self.should_exit = true;
         }
     }
 
+    /// Shield the game started with
+    pub fn max_shield(&self) -> u8 {
+        self.difficulty.shield()
+    }
+
+    /// Whether the shield is down to `percent` of its starting level
+    fn shield_at_most(&self, percent: u16) -> bool {
+        u16::from(self.shield) * 100 <= u16::from(self.max_shield()) * percent
+    }
+
+    /// Whether enough shield is gone for the cockpit to show damage
+    pub fn shield_damaged(&self) -> bool {
+        self.shield_at_most(DAMAGED_PERCENT)
+    }
+
+    /// Whether the shield is low enough for cracks and warnings
+    pub fn shield_critical(&self) -> bool {
+        self.shield_at_most(CRITICAL_PERCENT)
+    }
+
+    /// Whether the shield is gone and play has stopped
+    pub fn is_over(&self) -> bool {
+        self.shield == 0
+    }
+
+    /// Send sounds queued since the last call to `sink`
+    pub fn drain_sounds(&mut self, sink: &mut dyn AudioSink) {
+        for sound in self.sounds.drain(..) {
+            sink.play(sound);
+        }
+    }
+
     /// Check if game should continue running
     pub fn is_running(&self) -> bool {
         !self.should_exit
     }
 }
 
+/// A seed that differs from game to game, taken from the clock
+pub fn time_seed() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_micros() as u64
+}
+
 impl Default for GameState {
     fn default() -> Self {
         Self::new()
@@ -176,6 +835,33 @@ impl Default for GameState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tui::{
+        ai::Maneuver,
+        audio::NullSink,
+        environment::{CanyonShape, Obstacle, TrenchShape},
+        targeting::LOCK_FRAMES,
+    };
+
+    #[test]
+    fn test_difficulty_sets_the_pace() {
+        let hard = GameState::with_seed(4).with_difficulty(Difficulty::Hard);
+        assert_eq!(hard.shield, 6);
+        assert_eq!(Difficulty::from_name("hard"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::from_name("insane"), None);
+
+        let waves_after = |difficulty: Difficulty| {
+            let mut game = GameState::with_seed(4).with_difficulty(difficulty);
+            for _ in 0..WAVE_INTERVAL {
+                game.update();
+            }
+            game.waves
+        };
+        // The first wave is there from the start
+        assert_eq!(waves_after(Difficulty::Easy), 1);
+        assert_eq!(waves_after(Difficulty::Normal), 2);
+        assert_eq!(waves_after(Difficulty::Hard), 2);
+        assert!(Difficulty::Hard.wave_interval() < WAVE_INTERVAL);
+    }
 
     #[test]
     fn test_initial_state() {
@@ -223,6 +909,18 @@ mod tests {
         assert!(game.lasers.is_empty());
     }
 
+    #[test]
+    fn test_scenery_scrolls() {
+        let mut game = GameState::new();
+        game.update();
+        game.update();
+        assert_eq!(game.distance, 2.0 * SCROLL_SPEED);
+
+        game.paused = true;
+        game.update();
+        assert_eq!(game.distance, 2.0 * SCROLL_SPEED);
+    }
+
     #[test]
     fn test_fire_laser() {
         let mut game = GameState::new();
@@ -243,6 +941,506 @@ mod tests {
         assert_eq!(game.lasers.len(), prev_count + 1); // Should not increase yet
     }
 
+    #[test]
+    fn test_laser_destroys_enemy() {
+        let mut game = GameState::new();
+        game.enemies.clear();
+        let mut enemy = Enemy::new(0);
+        enemy.x = 0.0;
+        enemy.y = 0.0;
+        enemy.z = 20.0;
+        game.enemies.push(enemy);
+        game.lasers.push(Laser {
+            x: 0.05,
+            y: 0.0,
+            z: 15.0,
+        });
+
+        for _ in 0..5 {
+            game.update();
+        }
+
+        assert!(game.enemies.is_empty());
+        assert!(game.lasers.is_empty());
+        assert!(!game.particles.is_empty());
+    }
+
+    #[test]
+    fn test_laser_misses_distant_enemy() {
+        let mut game = GameState::new();
+        game.enemies.clear();
+        let mut enemy = Enemy::new(0);
+        enemy.x = 0.8;
+        enemy.y = 0.0;
+        enemy.z = 20.0;
+        game.enemies.push(enemy);
+        game.lasers.push(Laser {
+            x: 0.0,
+            y: 0.0,
+            z: 15.0,
+        });
+
+        for _ in 0..5 {
+            game.update();
+        }
+
+        assert_eq!(game.enemies.len(), 1);
+        assert!(game.particles.is_empty());
+    }
+
+    #[test]
+    fn test_hits_build_a_combo_and_misses_break_it() {
+        let mut game = GameState::with_seed(1);
+        game.environment = Environment::OpenSpace;
+        assert_eq!(game.accuracy(), None);
+
+        game.enemies.clear();
+        game.combo = 9;
+        let mut enemy = Enemy::new(0);
+        (enemy.x, enemy.y, enemy.z) = (0.0, 0.0, 20.0);
+        game.enemies.push(enemy);
+        game.frame = LASER_COOLDOWN + 1;
+        game.fire_laser();
+        for _ in 0..10 {
+            game.update();
+        }
+        assert_eq!(game.kills, 1);
+        assert_eq!(game.combo, 10);
+        assert_eq!(game.multiplier(), 3);
+
+        game.enemies.clear();
+        game.fire_laser();
+        for _ in 0..60 {
+            game.update();
+        }
+        assert_eq!(game.combo, 0);
+        assert_eq!(game.multiplier(), 1);
+        assert_eq!(game.accuracy(), Some(0.5));
+    }
+
+    #[test]
+    fn test_kills_score_at_the_combo_multiplier() {
+        let mut game = GameState::with_seed(1);
+        game.record_kill(0);
+        assert_eq!(game.score, KILL_POINTS);
+
+        game.combo = 4;
+        game.record_kill(0);
+        assert_eq!(game.score, KILL_POINTS * 3);
+        assert_eq!(game.best_combo, 5);
+        // Both kills were from the opening wave
+        assert_eq!(game.wave_kills, [2]);
+
+        // Taking damage breaks the combo but the best is kept
+        game.take_hit(HitCause::Enemy);
+        assert_eq!(game.combo, 0);
+        assert_eq!(game.multiplier(), 1);
+        assert_eq!(game.best_combo, 5);
+    }
+
+    #[test]
+    fn test_cleared_wave_earns_bonuses() {
+        let mut game = GameState::with_seed(1);
+        game.environment = Environment::OpenSpace;
+        game.enemies.clear();
+        (game.shots_fired, game.shots_hit) = (4, 3);
+        game.update();
+
+        let bonus = game.last_bonus.unwrap();
+        assert_eq!(bonus.wave, 1);
+        assert_eq!(bonus.no_damage, NO_DAMAGE_BONUS);
+        assert_eq!(bonus.accuracy, ACCURACY_BONUS * 3 / 4);
+        assert_eq!(game.score, bonus.total());
+    }
+
+    #[test]
+    fn test_damage_forfeits_the_wave_bonus() {
+        let mut game = GameState::with_seed(1);
+        game.environment = Environment::OpenSpace;
+        game.take_hit(HitCause::Enemy);
+        game.enemies.clear();
+        game.update();
+
+        assert_eq!(game.last_bonus.unwrap().total(), 0);
+        assert_eq!(game.score, 0);
+    }
+
+    #[test]
+    fn test_events_make_sounds() {
+        let mut game = GameState::with_seed(1);
+        game.environment = Environment::OpenSpace;
+        game.enemies.clear();
+        let mut enemy = Enemy::new(0);
+        (enemy.x, enemy.y, enemy.z) = (0.0, 0.0, 20.0);
+        game.enemies.push(enemy);
+        game.frame = LASER_COOLDOWN + 1;
+        game.fire_laser();
+        for _ in 0..10 {
+            game.update();
+        }
+        game.take_hit(HitCause::Enemy);
+
+        let mut sink = NullSink::default();
+        game.drain_sounds(&mut sink);
+        assert_eq!(
+            sink.events(),
+            [Sound::LaserFire, Sound::Explosion, Sound::ShieldHit]
+        );
+
+        // Draining empties the queue
+        game.drain_sounds(&mut sink);
+        assert_eq!(sink.events().len(), 3);
+    }
+
+    #[test]
+    fn test_losing_the_last_shield_ends_the_game() {
+        let mut game = GameState::with_seed(1);
+        game.shield = 1;
+        game.take_hit(HitCause::Enemy);
+        assert!(game.is_over());
+        let frame = game.frame;
+        game.update();
+        game.fire_laser();
+        game.take_hit(HitCause::Enemy);
+        assert_eq!(game.frame, frame);
+        assert!(game.lasers.is_empty());
+
+        let mut sink = NullSink::default();
+        game.drain_sounds(&mut sink);
+        assert_eq!(sink.events(), [Sound::GameOver]);
+
+        game.exit_to_menu();
+        assert!(!game.is_running());
+    }
+
+    #[test]
+    fn test_altitude_follows_the_ship() {
+        let mut game = GameState::with_seed(1);
+        game.ship_y = 1.0;
+        assert_eq!(game.altitude(), 0);
+        game.ship_y = 0.0;
+        assert_eq!(game.altitude(), 500);
+        game.move_up();
+        assert!(game.altitude() > 500);
+    }
+
+    #[test]
+    fn test_speed_follows_the_ship() {
+        let mut game = GameState::with_seed(1);
+        game.update();
+        assert_eq!(game.speed(), 0.0);
+
+        game.move_right();
+        game.update();
+        let moving = game.speed();
+        assert!(moving > 0.0);
+
+        // It fades once the ship holds still
+        for _ in 0..30 {
+            game.update();
+        }
+        assert!(game.speed() < moving / 10.0);
+
+        game.toggle_pause();
+        assert_eq!(game.speed(), 0.0);
+    }
+
+    #[test]
+    fn test_passing_enemy_hits_shield() {
+        let mut game = GameState::new();
+        game.enemies.clear();
+        let mut enemy = Enemy::new(0);
+        enemy.x = 0.1;
+        enemy.y = 0.0;
+        enemy.z = 1.0;
+        game.enemies.push(enemy);
+
+        game.update();
+
+        assert_eq!(game.shield, 9);
+        assert!(game.enemies.is_empty());
+        assert!(!game.particles.is_empty());
+        assert_eq!(game.hit_frames, HIT_FEEDBACK_FRAMES);
+    }
+
+    /// A trench with a single kind of obstacle
+    fn trench(beam_spacing: Option<f32>, debris_spacing: Option<f32>) -> Environment {
+        Environment::Trench(TrenchShape {
+            width: 0.8,
+            grid_spacing: 10.0,
+            pillar_spacing: 20.0,
+            beam_spacing,
+            debris_spacing,
+        })
+    }
+
+    #[test]
+    fn test_scraping_a_wall_costs_shield() {
+        let mut game = GameState::new();
+        game.enemies.clear();
+        game.environment = trench(None, None);
+        game.ship_x = 0.6;
+        game.update();
+        assert_eq!(game.shield, 10);
+
+        game.ship_x = 1.0;
+        game.update();
+        assert_eq!(game.shield, 9);
+        assert_eq!(game.hits, [HitCause::Wall]);
+        assert_eq!(game.hit_frames, HIT_FEEDBACK_FRAMES);
+        // Knocked back clear of the wall
+        assert!(game.ship_x + HULL_RADIUS < 0.8);
+        game.update();
+        assert_eq!(game.shield, 9);
+    }
+
+    #[test]
+    fn test_scenery_changes_each_level() {
+        let mut game = GameState::with_seed(1);
+        game.ship_x = -0.7;
+        // Clear every wave as it arrives, keeping the ship alive
+        let fly_to_level = |game: &mut GameState, level: usize| {
+            while game.level < level {
+                game.update();
+                game.enemies.clear();
+                game.shield = 10;
+                assert!(game.frame < 10_000, "stuck at level {}", game.level);
+            }
+        };
+
+        fly_to_level(&mut game, 1);
+        assert_eq!(game.waves, WAVES_PER_LEVEL);
+        assert_eq!(game.environment, Environment::for_level(1));
+        assert_ne!(game.environment, Environment::for_level(0));
+        // The new scenery starts ahead of the ship, with the ship between
+        // its walls
+        assert!(game.scenery_start > game.distance);
+        assert!(game.obstacles().iter().all(|o| o.z() > SCROLL_SPEED));
+        let (left, right) = game.environment.walls(game.distance).unwrap();
+        assert!(game.ship_x - HULL_RADIUS > left && game.ship_x + HULL_RADIUS < right);
+
+        fly_to_level(&mut game, 2);
+        assert_eq!(game.environment, Environment::for_level(2));
+    }
+
+    #[test]
+    fn test_open_space_has_no_walls() {
+        let mut game = GameState::new();
+        game.enemies.clear();
+        game.environment = Environment::OpenSpace;
+        game.ship_x = 1.0;
+        game.update();
+        assert_eq!(game.shield, 10);
+        assert_eq!(game.ship_x, 1.0);
+    }
+
+    #[test]
+    fn test_canyon_walls_move_with_the_bend() {
+        let shape = CanyonShape {
+            width: 0.6,
+            curvature: 0.4,
+            spacing: 12.0,
+            debris_spacing: None,
+        };
+        let mut game = GameState::new();
+        game.enemies.clear();
+        game.environment = Environment::Canyon(shape);
+        // A quarter swing in, the canyon's middle is at its right extreme
+        game.distance = 20.0 - SCROLL_SPEED;
+        game.ship_x = -0.3;
+        game.update();
+        assert_eq!(game.shield, 9);
+
+        game.ship_x = 0.4;
+        game.update();
+        assert_eq!(game.shield, 9);
+    }
+
+    #[test]
+    fn test_dodging_a_beam() {
+        let run = |ship_y: f32| {
+            let mut game = GameState::new();
+            game.enemies.clear();
+            game.environment = trench(Some(50.0), None);
+            game.distance = 49.5;
+            game.ship_y = ship_y;
+            let Obstacle::Beam { y, .. } = game.environment.obstacles(game.distance)[0] else {
+                panic!("expected a beam");
+            };
+            game.ship_y += y;
+            game.update();
+            game.shield
+        };
+        assert_eq!(run(0.0), 9);
+        assert_eq!(run(0.5), 10);
+        assert_eq!(run(-0.5), 10);
+    }
+
+    #[test]
+    fn test_flying_into_debris() {
+        let mut game = GameState::new();
+        game.enemies.clear();
+        game.environment = trench(None, Some(30.0));
+        game.distance = 29.5;
+        let Obstacle::Debris(rock) = game.environment.obstacles(game.distance)[0] else {
+            panic!("expected debris");
+        };
+        game.ship_x = rock.x;
+        game.ship_y = rock.y;
+        game.update();
+        assert_eq!(game.shield, 9);
+
+        // The debris has passed
+        game.update();
+        assert_eq!(game.shield, 9);
+    }
+
+    /// Positions of every enemy after `frames` of a seeded game where the
+    /// player sweeps the crosshair back and forth
+    fn simulate(seed: u64, frames: u64) -> Vec<(u32, f32, f32, f32)> {
+        let mut game = GameState::with_seed(seed);
+        game.environment = Environment::OpenSpace;
+        for frame in 0..frames {
+            if (frame / 20) % 2 == 0 {
+                game.move_left();
+            } else {
+                game.move_right();
+            }
+            game.update();
+        }
+        game.enemies.iter().map(|e| (e.id, e.x, e.y, e.z)).collect()
+    }
+
+    #[test]
+    fn test_seeded_games_repeat() {
+        let flight = simulate(7, 330);
+        assert!(!flight.is_empty());
+        assert_eq!(simulate(7, 330), flight);
+        assert_ne!(simulate(8, 330), flight);
+    }
+
+    #[test]
+    fn test_waves_spawn_in_formation() {
+        let mut game = GameState::with_seed(3);
+        game.environment = Environment::OpenSpace;
+        assert_eq!(game.waves, 1);
+        for _ in 0..WAVE_INTERVAL + 10 {
+            game.update();
+        }
+        assert_eq!(game.waves, 2);
+
+        // The second wave is a V of three, with wingmen holding their slots
+        let wave = Wave::for_number(1);
+        // The first wave was a single enemy with id 0
+        let members: Vec<&Enemy> = game.enemies.iter().filter(|e| e.id > 0).collect();
+        assert_eq!(members.len(), wave.size as usize);
+        let leader = members[0];
+        assert_eq!(leader.role, Role::Leader);
+        for wingman in &members[1..] {
+            let Role::Wingman {
+                leader: id, slot, ..
+            } = wingman.role
+            else {
+                panic!("expected a wingman");
+            };
+            assert_eq!(id, leader.id);
+            let (dx, _, dz) = wave.formation.offset(slot, leader.age);
+            assert!((wingman.x - (leader.x + dx)).abs() < 0.05);
+            assert!((wingman.z - (leader.z + dz)).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_targeted_enemy_evades() {
+        let mut game = GameState::with_seed(1);
+        game.enemies.clear();
+        let mut enemy = Enemy::new(0);
+        (enemy.x, enemy.y, enemy.z) = (0.05, 0.0, 60.0);
+        enemy.pattern = &[Maneuver::EVADE];
+        game.enemies.push(enemy);
+
+        for _ in 0..10 {
+            game.update();
+        }
+        assert!(game.enemies[0].x > 0.2);
+    }
+
+    /// A game with one enemy that flies straight at the middle of the view
+    fn lone_enemy(z: f32) -> GameState {
+        let mut game = GameState::with_seed(1);
+        game.environment = Environment::OpenSpace;
+        game.enemies.clear();
+        let mut enemy = Enemy::new(0);
+        (enemy.id, enemy.x, enemy.y, enemy.z) = (42, 0.0, 0.0, z);
+        game.enemies.push(enemy);
+        game
+    }
+
+    #[test]
+    fn test_holding_aim_locks_on() {
+        let mut game = lone_enemy(90.0);
+        game.frame = 1000;
+        game.fire_missile();
+        assert!(game.missiles.is_empty());
+
+        for _ in 0..LOCK_FRAMES {
+            game.update();
+        }
+        assert_eq!(game.locked_enemy().map(|e| e.id), Some(42));
+
+        game.fire_missile();
+        assert_eq!(game.missiles.len(), 1);
+        // Cooling down
+        game.fire_missile();
+        assert_eq!(game.missiles.len(), 1);
+    }
+
+    #[test]
+    fn test_missile_chases_down_its_target() {
+        let mut game = lone_enemy(90.0);
+        for _ in 0..LOCK_FRAMES {
+            game.update();
+        }
+        game.frame = 1000;
+        game.fire_missile();
+
+        // The target swerves after launch but can't shake the missile
+        game.enemies[0].x = 0.4;
+        for _ in 0..30 {
+            game.update();
+        }
+        assert!(game.enemies.is_empty());
+        assert!(game.missiles.is_empty());
+        assert!(!game.particles.is_empty());
+        assert_eq!(game.locked_enemy().map(|e| e.id), None);
+    }
+
+    #[test]
+    fn test_view_offset_follows_the_ship() {
+        let mut game = lone_enemy(50.0);
+        assert_eq!(game.view_offset(0.0, 0.0, 50.0), Some((0.0, 0.0)));
+        assert_eq!(game.view_offset(0.0, 0.0, 0.5), None);
+
+        game.ship_x = 0.3;
+        let (x, _) = game.view_offset(0.1, 0.0, 50.0).unwrap();
+        assert!((x - 0.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_shake_settles_after_hit() {
+        let mut game = GameState::new();
+        assert_eq!(game.shake_offset(), (0, 0));
+
+        game.hit_frames = HIT_FEEDBACK_FRAMES;
+        assert_ne!(game.shake_offset(), (0, 0));
+
+        for _ in 0..HIT_FEEDBACK_FRAMES {
+            game.update();
+        }
+        assert_eq!(game.hit_frames, 0);
+        assert_eq!(game.shake_offset(), (0, 0));
+    }
+
     #[test]
     fn test_laser_movement() {
         let mut game = GameState::new();
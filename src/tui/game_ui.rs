@@ -1,436 +1,1819 @@
 //! Game UI rendering
 
 use ratatui::{
+    buffer::Cell,
     prelude::*,
     widgets::{Block, Paragraph},
 };
 
-use super::game::GameState;
-
-/// Retro phosphor green colors
-const PHOSPHOR_GREEN: Color = Color::Rgb(0, 200, 0);
-const PHOSPHOR_GREEN_DIM: Color = Color::Rgb(0, 100, 0);
-const PHOSPHOR_GREEN_BRIGHT: Color = Color::Rgb(50, 255, 50);
-
-/// Cockpit ASCII art (ship from behind view)
-const COCKPIT: &[&str] = &[
-    r"          /\                    /\          ",
-    r"         /  \                  /  \         ",
-    r"        /    \                /    \        ",
-    r"       /      \______________/      \       ",
-    r"      /       |              |       \      ",
-    r"     /________|              |________\     ",
-    r"    |    _____|              |_____    |    ",
-    r"    |   /     \______________/     \   |    ",
-    r"    |  /                            \  |    ",
-    r"    | /          ________            \ |    ",
-    r"    |/          /   /\   \            \|    ",
-    r"   _|__________/   /  \   \____________|_   ",
-    r"  /            \__/    \__/              \  ",
-    r" /                                        \ ",
-];
-
-/// Compact Cockpit ASCII art (for smaller screens)
-const COCKPIT_SMALL: &[&str] = &[
-    r"      /_______|              |_______\      ",
-    r"     |   _____|              |_____   |     ",
-    r"     |  /                            \  |     ",
-    r"     | /          ________            \ |     ",
-    r"     |/          /   /\   \            \|     ",
-    r"    _|__________/   /  \   \____________|_    ",
-    r"   /            \__/    \__/              \   ",
-    r"  /                                        \  ",
-];
+use super::{
+    app::App,
+    assets::Assets,
+    controls,
+    environment::{BEAM_THICKNESS, CanyonShape, Environment, Obstacle, TrenchShape},
+    game::GameState,
+    hud::HudLayout,
+    menu::PauseItem,
+    particle::{Particle, ParticleKind},
+    raster::{DotGrid, RenderMode},
+    sprite::{Canvas, ColorHint, Sprite, SpriteSet, TRANSPARENT},
+    starfield::Star,
+    theme::Theme,
+};
+
+/// How far stars drift against the ship's movement, before perspective
+const STAR_PARALLAX: f32 = 0.2;
+/// Stars are drawn behind everything else in the view
+const STAR_DEPTH: f32 = f32::MAX;
+/// Radar panel width, including its frame
+const RADAR_WIDTH: u16 = 17;
+/// Frames an end-of-wave bonus stays on screen
+const BONUS_FRAMES: u64 = 45;
+/// Sideways distance from the ship at the radar's edges, in view units
+const RADAR_RANGE_X: f32 = 1.5;
+/// Depth at the top of the radar
+const RADAR_RANGE_Z: f32 = 100.0;
+/// Height difference from the ship beyond which a blip shows above/below
+const RADAR_LEVEL: f32 = 0.2;
 
 /// Render the entire game screen
-pub fn render(frame: &mut Frame, game: &GameState) {
+pub fn render(
+    frame: &mut Frame,
+    game: &GameState,
+    assets: &Assets,
+    hud: &HudLayout,
+    theme: &Theme,
+    mode: RenderMode,
+    pause: Option<&App<PauseItem>>,
+) {
     let area = frame.area();
 
     // Dark background
-    let block = Block::default().style(Style::default().bg(Color::Black));
+    let block = Block::default().style(Style::default().bg(theme.background));
     frame.render_widget(block, area);
 
     // Layout: Stars, Game view, Cockpit, HUD
     let height = area.height;
     let use_compact = height < 35;
 
-    let (layout, cockpit_art) = if use_compact {
-        let l = Layout::default()
+    let cockpit_set = cockpit_for_shield(assets, game);
+    let cockpit_art = cockpit_set.frame(height as f32);
+    let cockpit_height = cockpit_art.height();
+
+    let layout = if use_compact {
+        Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(3), // Reduced Sky
-                Constraint::Min(8),    // Game view
-                Constraint::Length(9), // Compact Cockpit (8 lines + 1 padding potentially)
-                Constraint::Length(1), // HUD
+                Constraint::Length(3),              // Reduced Sky
+                Constraint::Min(8),                 // Game view
+                Constraint::Length(cockpit_height), // Compact Cockpit
+                Constraint::Length(1),              // HUD
             ])
-            .split(area);
-        (l, COCKPIT_SMALL)
+            .split(area)
     } else {
-        let l = Layout::default()
+        Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(6),  // Top Sky/Stars
-                Constraint::Min(10),    // Game view
-                Constraint::Length(14), // Full Cockpit
-                Constraint::Length(1),  // HUD
+                Constraint::Length(6),              // Top Sky/Stars
+                Constraint::Min(10),                // Game view
+                Constraint::Length(cockpit_height), // Full Cockpit
+                Constraint::Length(1),              // HUD
             ])
-            .split(area);
-        (l, COCKPIT)
+            .split(area)
     };
 
-    render_sky(frame, layout[0], game);
-    // Combine layout[1] and part of layout[0] logic for proper specific rendering if needed,
-    // but here we treat layout[1] as the main viewport for the trench run.
-    render_trench(frame, layout[1], game);
-    render_enemies(frame, layout[1], game);
-    render_lasers(frame, layout[1], game);
-    render_cockpit(frame, layout[2], game, cockpit_art);
-    render_crosshair(frame, layout[1], game);
-    render_hud(frame, layout[3], game);
+    frame.render_widget(Sky::new(game, layout[1], theme), layout[0]);
+    // layout[1] is the main viewport for the trench run
+    render_view(frame.buffer_mut(), layout[1], game, assets, theme, mode);
+    if game.shield_critical() {
+        render_cracks(frame.buffer_mut(), layout[1], &assets.cracks, theme);
+    }
+    render_wave_bonus(frame.buffer_mut(), layout[1], game, theme);
+    frame.render_widget(
+        Cockpit::new(game, cockpit_art, cockpit_set.color(), theme),
+        layout[2],
+    );
+    if let Some(radar_area) = radar_area(layout[2], cockpit_art) {
+        frame.render_widget(Radar::new(game, theme), radar_area);
+    }
+
+    // Everything but the HUD shakes after a shield hit
+    let (dx, dy) = game.shake_offset();
+    let shaken = layout[0].union(layout[2]);
+    shake(frame.buffer_mut(), shaken, dx, dy, theme);
+
+    render_hud(frame, layout[3], game, hud, theme);
 
     // Pause overlay
-    if game.paused {
-        render_pause_overlay(frame, area);
-    }
-}
-
-/// Render sky/stars
-fn render_sky(frame: &mut Frame, area: Rect, game: &GameState) {
-    let width = area.width as usize;
-    // Stars still shift for parallax against ship movement?
-    // If we want "Cockpit" centered, the universe should move opposite.
-    // That means the "Sky" should shift opposite to ship_x.
-    let offset = (game.ship_x * 10.0) as i32;
-    let stars: String = (0..width)
-        .map(|i| {
-            let pos = (i as i32 + offset).rem_euclid(7);
-            if pos == 0 || pos == 3 { '*' } else { ' ' }
-        })
-        .collect();
+    if game.is_over() {
+        render_game_over_overlay(frame, area, game, theme);
+    } else if game.paused {
+        match pause {
+            Some(menu) if menu.selected_action() == Some(PauseItem::Controls) => {
+                render_controls_overlay(frame, area, theme);
+            }
+            Some(menu) => render_pause_menu(frame, area, menu, theme),
+            None => render_pause_overlay(frame, area, theme),
+        }
+    }
+}
+
+/// Cockpit art reflecting how much shield is left
+fn cockpit_for_shield<'a>(assets: &'a Assets, game: &GameState) -> &'a SpriteSet {
+    if game.shield_critical() {
+        &assets.cockpit_critical
+    } else if game.shield_damaged() {
+        &assets.cockpit_damaged
+    } else {
+        &assets.cockpit
+    }
+}
+
+/// Draw cracked canopy glass over the view, with the impact point a third of
+/// the way in
+fn render_cracks(buf: &mut Buffer, area: Rect, cracks: &SpriteSet, theme: &Theme) {
+    let art = cracks.frame(0.0);
+    let (anchor_x, anchor_y) = art.anchor();
+    let style = theme.style(cracks.color());
+    let left = (area.width / 3) as i32 - anchor_x as i32;
+    let top = (area.height / 3) as i32 - anchor_y as i32;
+
+    for (dy, row) in art.rows().iter().enumerate() {
+        for (dx, ch) in row.chars().enumerate() {
+            let y = top + dy as i32;
+            if ch != TRANSPARENT && y >= 0 && y < area.height as i32 {
+                put(buf, area, left + dx as i32, y as u16, ch, style);
+            }
+        }
+    }
+}
+
+/// Move the contents of `area` by an offset, blanking the cells left behind
+fn shake(buf: &mut Buffer, area: Rect, dx: i16, dy: i16, theme: &Theme) {
+    if (dx, dy) == (0, 0) {
+        return;
+    }
+    let blank = Cell::default().set_bg(theme.background).clone();
+
+    // Walk against the direction of travel so every cell is read before it
+    // is overwritten
+    for row in 0..area.height {
+        let y = if dy > 0 {
+            area.bottom() - 1 - row
+        } else {
+            area.top() + row
+        };
+        for col in 0..area.width {
+            let x = if dx > 0 {
+                area.right() - 1 - col
+            } else {
+                area.left() + col
+            };
+            let source_x = x as i32 - dx as i32;
+            let source_y = y as i32 - dy as i32;
+            let inside = source_x >= area.left() as i32
+                && source_x < area.right() as i32
+                && source_y >= area.top() as i32
+                && source_y < area.bottom() as i32;
+            buf[(x, y)] = if inside {
+                buf[(source_x as u16, source_y as u16)].clone()
+            } else {
+                blank.clone()
+            };
+        }
+    }
+}
+
+/// Draw the trench, entities and crosshair into the main viewport
+///
+/// The trench and entities share one z-buffer; the crosshair is an overlay
+/// drawn on top of everything. `mode` picks how lines and distant sprites
+/// are rasterized.
+pub fn render_view(
+    buf: &mut Buffer,
+    area: Rect,
+    game: &GameState,
+    assets: &Assets,
+    theme: &Theme,
+    mode: RenderMode,
+) {
+    let mut canvas = Canvas::new(buf, area);
+    Stars::new(game, theme).draw(&mut canvas);
+    match game.environment {
+        Environment::Trench(shape) => Trench::new(game, shape, theme, mode).draw(&mut canvas),
+        Environment::Canyon(shape) => Canyon::new(game, shape, theme, mode).draw(&mut canvas),
+        Environment::OpenSpace | Environment::AsteroidField(_) => {}
+    }
+    Obstacles::new(game, &assets.asteroid, &assets.debris, theme, mode).draw(&mut canvas);
+    let enemies = game.enemies.iter().map(|e| (e.x, e.y, e.z));
+    SpriteLayer::new(game, enemies, &assets.fighter, theme, mode).draw(&mut canvas);
+    let lasers = game.lasers.iter().map(|l| (l.x, l.y, l.z));
+    SpriteLayer::new(game, lasers, &assets.laser, theme, mode).draw(&mut canvas);
+    let missiles = game.missiles.iter().map(|m| (m.x, m.y, m.z));
+    SpriteLayer::new(game, missiles, &assets.missile, theme, mode).draw(&mut canvas);
+    Particles::new(game, theme).draw(&mut canvas);
+    Crosshair::new(game, theme).render(area, buf);
+    ThreatArrows::new(game, theme).render(area, buf);
+}
+
+/// Something drawn into the depth-tested game view
+pub trait Layer {
+    fn draw(&self, canvas: &mut Canvas);
+}
+
+/// Perspective projection shared by everything drawn in the game view
+#[derive(Clone, Copy, Debug)]
+struct Projection {
+    width: f32,
+    height: f32,
+    /// Vanishing point, which moves opposite to the ship
+    vp_x: f32,
+    vp_y: f32,
+}
+
+impl Projection {
+    fn new(area: Rect, game: &GameState) -> Self {
+        let width = area.width as f32;
+        let height = area.height as f32;
+        Self {
+            width,
+            height,
+            vp_x: width / 2.0 - (game.ship_x * (width / 3.0)),
+            vp_y: height / 2.0 - (game.ship_y * (height / 3.0)),
+        }
+    }
 
-    let star_line = Paragraph::new(stars).style(Style::default().fg(PHOSPHOR_GREEN_DIM));
-    frame.render_widget(star_line, area);
+    /// Project a world point to view-local coordinates and its depth scale
+    ///
+    /// z=0 is the camera and z=100 is far; points behind the near plane
+    /// return `None`. Points off-screen are still returned so that large
+    /// sprites can be partially drawn.
+    fn project(&self, x: f32, y: f32, z: f32) -> Option<(f32, f32, f32)> {
+        if z <= 1.0 {
+            return None;
+        }
+        let scale = 100.0 / z;
+
+        // Entity x/y is -1.0 to 1.0, relative to the vanishing point
+        let proj_x = self.vp_x + (x * self.width * scale * 0.5);
+        let proj_y = self.vp_y + (y * self.height * scale * 0.5);
+        Some((proj_x, proj_y, scale))
+    }
 }
 
-/// Render the "Trench Run" perspective grid
-fn render_trench(frame: &mut Frame, area: Rect, game: &GameState) {
-    let width = area.width as usize;
-    let height = area.height as usize;
-    let center_x = width / 2;
-    let center_y = height / 2;
+/// Draw a sprite at its smallest tier as a sub-cell blob, so distant
+/// objects grow and move smoothly instead of jumping a cell at a time
+fn draw_dot_sprite(dots: &mut DotGrid, x: f32, y: f32, scale: f32, z: f32, style: Style) {
+    let (rx, ry) = dots.resolution();
+    // A quarter cell across per unit of scale, in sub-pixels
+    let radius = scale * 0.25 * rx;
+    dots.blob(x * rx, y * ry, radius, z, style);
+}
 
-    // Animation phase
-    let phase = (game.frame as f32 * 0.5) % 8.0;
+/// Write a single-line sprite at a view-local position, clipped to `area`
+fn draw_sprite(buf: &mut Buffer, area: Rect, x: u16, y: u16, sprite: &str, style: Style) {
+    let max_width = area.width.saturating_sub(x) as usize;
+    buf.set_stringn(area.x + x, area.y + y, sprite, max_width, style);
+}
 
-    // Vanishing point moves with ship
-    let vp_x = center_x as i32 - (game.ship_x * (width as f32 / 3.0)) as i32;
-    let vp_y = center_y as i32 - (game.ship_y * (height as f32 / 3.0)) as i32;
+/// Set one cell in the buffer if it falls inside `area`
+fn put(buf: &mut Buffer, area: Rect, x: i32, y: u16, ch: char, style: Style) {
+    if x >= 0
+        && x < area.width as i32
+        && let Some(cell) = buf.cell_mut((area.x + x as u16, area.y + y))
+    {
+        cell.set_char(ch).set_style(style);
+    }
+}
 
-    let mut buffer = vec![vec![' '; width]; height];
+/// Project a star to a view-local cell
+///
+/// Stars shift against the ship's movement on top of the moving vanishing
+/// point, and nearer stars shift further, giving parallax.
+fn project_star(projection: &Projection, game: &GameState, star: &Star) -> Option<(i32, i32)> {
+    let x = star.x - game.ship_x * STAR_PARALLAX;
+    let y = star.y - game.ship_y * STAR_PARALLAX;
+    let (x, y, _) = projection.project(x, y, star.z)?;
+    Some((x.floor() as i32, y.floor() as i32))
+}
 
-    for (y, row) in buffer.iter_mut().enumerate().take(height) {
-        let dy = y as i32 - vp_y;
-        if dy == 0 {
-            continue;
-        } // Horizon line
+/// Style for a star, brighter as it nears
+fn star_style(star: &Star, theme: &Theme) -> Style {
+    theme.style(match star.glyph() {
+        '.' => ColorHint::Dim,
+        '+' => ColorHint::Normal,
+        _ => ColorHint::Bright,
+    })
+}
+
+/// The part of the starfield above the game view
+///
+/// Stars are projected as if the view extended up over the sky band, so
+/// they flow continuously from one into the other.
+pub struct Sky<'a> {
+    game: &'a GameState,
+    /// The game view the sky sits above
+    view: Rect,
+    theme: &'a Theme,
+}
 
-        // Simulating ceiling and floor
-        let is_floor = dy > 0;
-        let dist_factor = (height as f32 / dy.abs() as f32).max(1.0);
+impl<'a> Sky<'a> {
+    pub fn new(game: &'a GameState, view: Rect, theme: &'a Theme) -> Self {
+        Self { game, view, theme }
+    }
+}
+
+impl Widget for Sky<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let projection = Projection::new(self.view, self.game);
+        let offset = self.view.y as i32 - area.y as i32;
+
+        for star in self.game.stars.iter() {
+            let Some((x, y)) = project_star(&projection, self.game, star) else {
+                continue;
+            };
+            let y = y + offset;
+            if (0..area.height as i32).contains(&y) {
+                let style = star_style(star, self.theme);
+                put(buf, area, x, y as u16, star.glyph(), style);
+            }
+        }
+    }
+}
+
+/// The starfield behind the trench
+pub struct Stars<'a> {
+    game: &'a GameState,
+    theme: &'a Theme,
+}
 
-        // Perspective lines (Vertical walls/corridor)
-        // We draw two main perspective lines defining the "trench"
-        let trench_width_at_depth = (width as f32 / dist_factor) * 0.8;
+impl<'a> Stars<'a> {
+    pub fn new(game: &'a GameState, theme: &'a Theme) -> Self {
+        Self { game, theme }
+    }
+}
 
-        let left_wall_x = (vp_x as f32 - trench_width_at_depth) as i32;
-        let right_wall_x = (vp_x as f32 + trench_width_at_depth) as i32;
+impl Layer for Stars<'_> {
+    fn draw(&self, canvas: &mut Canvas) {
+        let projection = Projection::new(canvas.area(), self.game);
 
-        // Draw Side Walls
-        if left_wall_x >= 0 && left_wall_x < width as i32 {
-            row[left_wall_x as usize] = if is_floor { '/' } else { '\\' };
+        for star in self.game.stars.iter() {
+            if let Some((x, y)) = project_star(&projection, self.game, star) {
+                let style = star_style(star, self.theme);
+                canvas.plot(x, y, STAR_DEPTH, star.glyph(), style);
+            }
         }
-        if right_wall_x >= 0 && right_wall_x < width as i32 {
-            row[right_wall_x as usize] = if is_floor { '\\' } else { '/' };
+    }
+}
+
+/// The "Trench Run" perspective grid
+pub struct Trench<'a> {
+    game: &'a GameState,
+    shape: TrenchShape,
+    theme: &'a Theme,
+    mode: RenderMode,
+}
+
+impl<'a> Trench<'a> {
+    pub fn new(
+        game: &'a GameState,
+        shape: TrenchShape,
+        theme: &'a Theme,
+        mode: RenderMode,
+    ) -> Self {
+        Self {
+            game,
+            shape,
+            theme,
+            mode,
         }
+    }
 
-        // Horizontal Grid Lines (moving towards player)
-        // distance Z calculation approximation
-        let z_depth = 100.0 / dist_factor;
-        let grid_pos = (z_depth + phase) % 10.0;
+    /// Whether a row at this animated depth has a floor grid line
+    fn is_grid_line(&self, z_depth: f32, phase: f32) -> bool {
+        (z_depth + phase) % self.shape.grid_spacing < 1.0
+    }
 
-        if grid_pos < 1.0 {
-            // Draw horizontal line
-            let start = left_wall_x.max(0) as usize;
-            let end = right_wall_x.min(width as i32) as usize;
-            for (x, cell) in row.iter_mut().enumerate().take(end).skip(start) {
-                // Gaps in the middle to simulate individual floor/ceiling tiles
-                if x % 10 != 0 {
-                    *cell = '-';
+    /// Whether a row at this animated depth has pillars, which are a tenth
+    /// of the spacing deep
+    fn is_pillar(&self, z_depth: f32, phase: f32) -> bool {
+        (z_depth + phase) % self.shape.pillar_spacing < self.shape.pillar_spacing / 10.0
+    }
+
+    /// The same grid traced in sub-pixels, one pass per sub-row
+    fn draw_dots(&self, canvas: &mut Canvas) {
+        let game = self.game;
+        let area = canvas.area();
+        let mut dots = DotGrid::new(area, self.mode);
+        let (rx, ry) = dots.resolution();
+        let (sub_width, sub_height) = dots.size();
+        let width = area.width as f32;
+        let height = area.height as f32;
+        let style = self.theme.style(ColorHint::Dim);
+
+        let phase = (game.frame as f32 * 0.5) % 8.0;
+
+        // Vanishing point in cells, kept fractional so the walls move smoothly
+        let vp_x = width / 2.0 - game.ship_x * (width / 3.0);
+        let vp_y = height / 2.0 - game.ship_y * (height / 3.0);
+
+        for sy in 0..sub_height {
+            // Middle of this sub-row, in cells
+            let dy = (sy as f32 + 0.5) / ry - vp_y;
+            if dy.abs() < 0.5 / ry {
+                continue;
+            }
+
+            let dist_factor = (height / dy.abs()).max(1.0);
+            let depth = 50.0 * dist_factor;
+            let trench_width_at_depth = (width / dist_factor) * self.shape.width;
+            let left_wall_x = ((vp_x - trench_width_at_depth) * rx).floor() as i32;
+            let right_wall_x = ((vp_x + trench_width_at_depth) * rx).floor() as i32;
+
+            dots.set(left_wall_x, sy, depth, style);
+            dots.set(right_wall_x, sy, depth, style);
+
+            let z_depth = 100.0 / dist_factor;
+            if self.is_grid_line(z_depth, phase) {
+                for x in left_wall_x.max(0)..right_wall_x.min(sub_width) {
+                    // Keep the ASCII grid's gap every tenth cell
+                    if (x / rx as i32) % 10 != 0 {
+                        dots.set(x, sy, depth, style);
+                    }
+                }
+            }
+
+            if self.is_pillar(z_depth, phase) {
+                // Alternate sub-columns so pillars read as vertical lines
+                // rather than a solid block
+                let span = (10.0 * rx) as i32;
+                let pillars = ((left_wall_x - span).max(0)..left_wall_x.min(sub_width))
+                    .chain(right_wall_x.max(0)..(right_wall_x + span).min(sub_width));
+                for x in pillars.filter(|x| x % 2 == 0) {
+                    dots.set(x, sy, depth, style);
                 }
             }
         }
 
-        // Vertical pillars on the side walls passing by
-        let pillar_interval = (z_depth + phase) % 20.0;
-        if pillar_interval < 2.0 {
-            // Draw "pillar" lines outside the trench
-            // Left side pillars
-            let outer_left = (left_wall_x - 10).max(0);
-            if left_wall_x > 0 {
-                for cell in row
-                    .iter_mut()
-                    .take(left_wall_x as usize)
-                    .skip(outer_left as usize)
-                {
-                    *cell = '|';
+        dots.flush(canvas);
+    }
+}
+
+impl Layer for Trench<'_> {
+    fn draw(&self, canvas: &mut Canvas) {
+        if self.mode != RenderMode::Ascii {
+            self.draw_dots(canvas);
+            return;
+        }
+
+        let game = self.game;
+        let area = canvas.area();
+        let width = area.width as i32;
+        let height = area.height as i32;
+        let center_x = width / 2;
+        let center_y = height / 2;
+        let style = self.theme.style(ColorHint::Dim);
+
+        // Animation phase
+        let phase = (game.frame as f32 * 0.5) % 8.0;
+
+        // Vanishing point moves with ship
+        let vp_x = center_x - (game.ship_x * (width as f32 / 3.0)) as i32;
+        let vp_y = center_y - (game.ship_y * (height as f32 / 3.0)) as i32;
+
+        for y in 0..height {
+            let dy = y - vp_y;
+            if dy == 0 {
+                continue;
+            } // Horizon line
+
+            // Simulating ceiling and floor
+            let is_floor = dy > 0;
+            let dist_factor = (height as f32 / dy.abs() as f32).max(1.0);
+            // Floor and ceiling sit at y = +-1, which projects to this row at
+            // a depth of 50 * dist_factor
+            let depth = 50.0 * dist_factor;
+
+            // Perspective lines (Vertical walls/corridor)
+            // We draw two main perspective lines defining the "trench"
+            let trench_width_at_depth = (width as f32 / dist_factor) * self.shape.width;
+
+            let left_wall_x = (vp_x as f32 - trench_width_at_depth) as i32;
+            let right_wall_x = (vp_x as f32 + trench_width_at_depth) as i32;
+
+            // Draw Side Walls
+            let (left, right) = if is_floor { ('/', '\\') } else { ('\\', '/') };
+            canvas.plot(left_wall_x, y, depth, left, style);
+            canvas.plot(right_wall_x, y, depth, right, style);
+
+            // Horizontal Grid Lines (moving towards player)
+            // distance Z calculation approximation
+            let z_depth = 100.0 / dist_factor;
+
+            if self.is_grid_line(z_depth, phase) {
+                // Gaps in the middle to simulate individual floor/ceiling tiles
+                for x in left_wall_x.max(0)..right_wall_x.min(width) {
+                    if x % 10 != 0 {
+                        canvas.plot(x, y, depth, '-', style);
+                    }
                 }
             }
-            // Right side pillars
-            let outer_right = (right_wall_x + 10).min(width as i32);
-            if right_wall_x < width as i32 {
-                for cell in row
-                    .iter_mut()
-                    .take(outer_right as usize)
-                    .skip(right_wall_x as usize)
-                {
-                    *cell = '|';
+
+            // Vertical pillars on the side walls passing by
+            if self.is_pillar(z_depth, phase) {
+                // Draw "pillar" lines outside the trench
+                if left_wall_x > 0 {
+                    for x in (left_wall_x - 10).max(0)..left_wall_x.min(width) {
+                        canvas.plot(x, y, depth, '|', style);
+                    }
+                }
+                if (0..width).contains(&right_wall_x) {
+                    for x in right_wall_x..(right_wall_x + 10).min(width) {
+                        canvas.plot(x, y, depth, '|', style);
+                    }
                 }
             }
         }
     }
+}
 
-    // Convert buffer to widgets
-    let lines: Vec<Line> = buffer
-        .into_iter()
-        .map(|row| {
-            let s: String = row.into_iter().collect();
-            Line::from(Span::styled(s, Style::default().fg(PHOSPHOR_GREEN_DIM)))
-        })
-        .collect();
+/// Rock walls that curve from side to side, with strata scrolling past
+pub struct Canyon<'a> {
+    game: &'a GameState,
+    shape: CanyonShape,
+    theme: &'a Theme,
+    mode: RenderMode,
+}
 
-    frame.render_widget(Paragraph::new(lines), area);
+impl<'a> Canyon<'a> {
+    pub fn new(
+        game: &'a GameState,
+        shape: CanyonShape,
+        theme: &'a Theme,
+        mode: RenderMode,
+    ) -> Self {
+        Self {
+            game,
+            shape,
+            theme,
+            mode,
+        }
+    }
 }
 
-/// Render enemies scaled by distance
-fn render_enemies(frame: &mut Frame, area: Rect, game: &GameState) {
-    let width = area.width as f32;
-    let height = area.height as f32;
-    let vp_x = width / 2.0 - (game.ship_x * (width / 3.0));
-    let vp_y = height / 2.0 - (game.ship_y * (height / 3.0));
+impl Layer for Canyon<'_> {
+    fn draw(&self, canvas: &mut Canvas) {
+        let game = self.game;
+        let area = canvas.area();
+        let projection = Projection::new(area, game);
+        let mut dots = DotGrid::new(area, self.mode);
+        let (rx, ry) = dots.resolution();
+        let (_, sub_height) = dots.size();
+        let edge_style = self.theme.style(ColorHint::Normal);
+        let rock_style = self.theme.style(ColorHint::Dim);
 
-    for enemy in &game.enemies {
-        // Simple perspective projection
-        // z=0 is camera, z=100 is far
-        if enemy.z <= 1.0 {
-            continue;
+        for sy in 0..sub_height {
+            // Middle of this sub-row, in cells
+            let dy = (sy as f32 + 0.5) / ry - projection.vp_y;
+            if dy.abs() < 0.5 / ry {
+                continue;
+            }
+
+            // Depth at which the wall tops and bottoms (y = +-1) meet this row
+            let z = 50.0 * (projection.height / dy.abs()).max(1.0);
+            let bend = self.shape.bend(z, game.distance);
+            let Some((left, _, _)) = projection.project(bend - self.shape.width, 0.0, z) else {
+                continue;
+            };
+            let Some((right, _, _)) = projection.project(bend + self.shape.width, 0.0, z) else {
+                continue;
+            };
+
+            // Rock texture is drawn once per cell row
+            if sy % ry as i32 == 0 {
+                let y = sy / ry as i32;
+                let strata = (z + game.distance) % self.shape.spacing < self.shape.spacing * 0.15;
+                let rock = |offset: i32| {
+                    if strata {
+                        Some('=')
+                    } else if offset % 6 == 3 {
+                        Some(':')
+                    } else {
+                        None
+                    }
+                };
+                for x in 0..left.floor() as i32 {
+                    if let Some(ch) = rock(left.floor() as i32 - x) {
+                        canvas.plot(x, y, z, ch, rock_style);
+                    }
+                }
+                for x in right.floor() as i32 + 1..area.width as i32 {
+                    if let Some(ch) = rock(x - right.floor() as i32) {
+                        canvas.plot(x, y, z, ch, rock_style);
+                    }
+                }
+            }
+
+            if self.mode == RenderMode::Ascii {
+                let (l, r) = if dy > 0.0 { ('/', '\\') } else { ('\\', '/') };
+                canvas.plot(left.floor() as i32, sy, z, l, edge_style);
+                canvas.plot(right.floor() as i32, sy, z, r, edge_style);
+            } else {
+                dots.set((left * rx).floor() as i32, sy, z, edge_style);
+                dots.set((right * rx).floor() as i32, sy, z, edge_style);
+            }
         }
 
-        let scale = 100.0 / enemy.z;
+        dots.flush(canvas);
+    }
+}
 
-        // Projected Position relative to Vanishing Point
-        // Enemy x/y is -1.0 to 1.0
-        let world_x = enemy.x * width;
-        let world_y = enemy.y * height;
+/// Beams, debris and asteroids the ship has to steer around
+pub struct Obstacles<'a> {
+    game: &'a GameState,
+    asteroid: &'a SpriteSet,
+    debris: &'a SpriteSet,
+    theme: &'a Theme,
+    mode: RenderMode,
+}
 
-        let proj_x = vp_x + (world_x * scale * 0.5);
-        let proj_y = vp_y + (world_y * scale * 0.5);
+impl<'a> Obstacles<'a> {
+    pub fn new(
+        game: &'a GameState,
+        asteroid: &'a SpriteSet,
+        debris: &'a SpriteSet,
+        theme: &'a Theme,
+        mode: RenderMode,
+    ) -> Self {
+        Self {
+            game,
+            asteroid,
+            debris,
+            theme,
+            mode,
+        }
+    }
 
-        // Sprite Selection based on scale (distance)
-        let sprite = if scale < 2.0 {
-            "."
-        } else if scale < 5.0 {
-            "-o-"
-        } else {
-            r"/-\" // Simple fighter shape
+    /// Draw a girder across the trench as a lattice between two rails
+    fn draw_beam(&self, canvas: &mut Canvas, projection: &Projection, y: f32, z: f32, span: f32) {
+        let Some((left, top, _)) = projection.project(-span, y - BEAM_THICKNESS, z) else {
+            return;
         };
+        let Some((right, bottom, _)) = projection.project(span, y + BEAM_THICKNESS, z) else {
+            return;
+        };
+        let rail_style = self.theme.style(ColorHint::Normal);
+        let lattice_style = self.theme.style(ColorHint::Dim);
+        let area = canvas.area();
+        let (top, bottom) = (top.round() as i32, bottom.round() as i32);
+        let (left, right) = (left.round() as i32, right.round() as i32);
 
-        // Don't render if out of bounds
-        if proj_x < 0.0 || proj_x >= width || proj_y < 0.0 || proj_y >= height {
-            continue;
+        for y in top.max(0)..=bottom.min(area.height as i32 - 1) {
+            for x in left.max(0)..=right.min(area.width as i32 - 1) {
+                if y == top || y == bottom {
+                    canvas.plot(x, y, z, '=', rail_style);
+                } else {
+                    let ch = if x % 2 == 0 { '/' } else { '\\' };
+                    canvas.plot(x, y, z, ch, lattice_style);
+                }
+            }
         }
+    }
+}
 
-        let x = proj_x as u16;
-        let y = proj_y as u16;
+impl Layer for Obstacles<'_> {
+    fn draw(&self, canvas: &mut Canvas) {
+        let projection = Projection::new(canvas.area(), self.game);
+        let mut dots = DotGrid::new(canvas.area(), self.mode);
 
-        // Using a centralized rect for the enemy widget
-        let enemy_area = Rect {
-            x: area.x + x,
-            y: area.y + y,
-            width: sprite.len() as u16,
-            height: 1,
-        };
+        for obstacle in self.game.obstacles() {
+            let (rock, sprites) = match obstacle {
+                Obstacle::Beam { y, z, span } => {
+                    self.draw_beam(canvas, &projection, y, z, span);
+                    continue;
+                }
+                Obstacle::Debris(rock) => (rock, self.debris),
+                Obstacle::Asteroid(rock) => (rock, self.asteroid),
+            };
+            let Some((x, y, scale)) = projection.project(rock.x, rock.y, rock.z) else {
+                continue;
+            };
+            let style = self.theme.style(sprites.color());
 
-        // Clip to game area
-        let render_area = area.intersection(enemy_area);
+            // Bigger rocks reach each tier sooner
+            let measure = scale * rock.radius * 10.0;
+            if self.mode != RenderMode::Ascii && sprites.tier(measure) == 0 {
+                draw_dot_sprite(&mut dots, x, y, measure, rock.z, style);
+                continue;
+            }
 
-        if render_area.area() > 0 {
-            frame.render_widget(
-                Paragraph::new(sprite).style(Style::default().fg(PHOSPHOR_GREEN_BRIGHT)),
-                render_area,
-            );
+            canvas.blit(sprites.frame(measure), x, y, rock.z, style);
         }
+        dots.flush(canvas);
     }
 }
 
-/// Render lasers
-fn render_lasers(frame: &mut Frame, area: Rect, game: &GameState) {
-    let width = area.width as f32;
-    let height = area.height as f32;
-    // same VP logic
-    let vp_x = width / 2.0 - (game.ship_x * (width / 3.0));
-    let vp_y = height / 2.0 - (game.ship_y * (height / 3.0));
+/// Enemies, lasers or missiles: one sprite at each point, scaled by
+/// distance
+pub struct SpriteLayer<'a> {
+    game: &'a GameState,
+    /// Where each sprite sits in the world, as (x, y, z)
+    points: Vec<(f32, f32, f32)>,
+    sprites: &'a SpriteSet,
+    theme: &'a Theme,
+    mode: RenderMode,
+}
 
-    for laser in &game.lasers {
-        if laser.z <= 1.0 {
-            continue;
+impl<'a> SpriteLayer<'a> {
+    pub fn new(
+        game: &'a GameState,
+        points: impl IntoIterator<Item = (f32, f32, f32)>,
+        sprites: &'a SpriteSet,
+        theme: &'a Theme,
+        mode: RenderMode,
+    ) -> Self {
+        Self {
+            game,
+            points: points.into_iter().collect(),
+            sprites,
+            theme,
+            mode,
         }
-        let scale = 100.0 / laser.z;
+    }
+}
 
-        // Projected Position
-        let world_x = laser.x * width;
-        let world_y = laser.y * height;
+impl Layer for SpriteLayer<'_> {
+    fn draw(&self, canvas: &mut Canvas) {
+        let projection = Projection::new(canvas.area(), self.game);
+        let style = self.theme.style(self.sprites.color());
+        let mut dots = DotGrid::new(canvas.area(), self.mode);
 
-        let proj_x = vp_x + (world_x * scale * 0.5);
-        let proj_y = vp_y + (world_y * scale * 0.5);
+        for &(x, y, z) in &self.points {
+            let Some((x, y, scale)) = projection.project(x, y, z) else {
+                continue;
+            };
 
-        if proj_x < 0.0 || proj_x >= width || proj_y < 0.0 || proj_y >= height {
-            continue;
+            if self.mode != RenderMode::Ascii && self.sprites.tier(scale) == 0 {
+                draw_dot_sprite(&mut dots, x, y, scale, z, style);
+                continue;
+            }
+
+            // Sprite Selection based on scale (distance)
+            canvas.blit(self.sprites.frame(scale), x, y, z, style);
         }
+        dots.flush(canvas);
+    }
+}
+
+/// Explosion debris, sparks and shockwaves
+pub struct Particles<'a> {
+    game: &'a GameState,
+    theme: &'a Theme,
+}
 
-        let x = proj_x as u16;
-        let y = proj_y as u16;
+impl<'a> Particles<'a> {
+    pub fn new(game: &'a GameState, theme: &'a Theme) -> Self {
+        Self { game, theme }
+    }
 
-        let sprite = match scale {
-            s if s < 2.0 => ".",
-            s if s < 5.0 => "|",
-            _ => "||",
+    /// Glyph and color for a particle at its current age
+    fn appearance(particle: &Particle) -> (char, ColorHint) {
+        let fade = particle.fade();
+        let color = match fade {
+            f if f < 0.35 => ColorHint::Bright,
+            f if f < 0.7 => ColorHint::Normal,
+            _ => ColorHint::Dim,
+        };
+        let glyph = match particle.kind {
+            ParticleKind::Debris => match fade {
+                f if f < 0.3 => '#',
+                f if f < 0.6 => '%',
+                f if f < 0.85 => '*',
+                _ => '.',
+            },
+            ParticleKind::Spark => {
+                if fade < 0.5 {
+                    '+'
+                } else {
+                    '\''
+                }
+            }
+            ParticleKind::Shockwave => {
+                if fade < 0.5 {
+                    'o'
+                } else {
+                    '.'
+                }
+            }
         };
+        (glyph, color)
+    }
+}
+
+impl Layer for Particles<'_> {
+    fn draw(&self, canvas: &mut Canvas) {
+        let projection = Projection::new(canvas.area(), self.game);
+
+        for particle in self.game.particles.iter() {
+            let Some((x, y, scale)) = projection.project(particle.x, particle.y, particle.z) else {
+                continue;
+            };
+            let (glyph, color) = Self::appearance(particle);
+            let style = self.theme.style(color);
+
+            if particle.kind == ParticleKind::Shockwave {
+                // Ring grows with age and is twice as wide as it is tall to
+                // make up for terminal cell proportions
+                let radius = f32::from(particle.age + 1) * scale * 0.3;
+                let points = (radius * 4.0).clamp(8.0, 64.0) as usize;
+                for i in 0..points {
+                    let angle = i as f32 / points as f32 * std::f32::consts::TAU;
+                    let px = x + angle.cos() * radius * 2.0;
+                    let py = y + angle.sin() * radius;
+                    canvas.plot(
+                        px.floor() as i32,
+                        py.floor() as i32,
+                        particle.z,
+                        glyph,
+                        style,
+                    );
+                }
+            } else {
+                canvas.plot(x.floor() as i32, y.floor() as i32, particle.z, glyph, style);
+            }
+        }
+    }
+}
 
-        // Simple 1x1 or 2x1 laser
-        // Center the sprite? Laser is small.
-        let laser_area = Rect {
-            x: area.x + x,
-            y: area.y + y,
-            width: sprite.len() as u16,
-            height: 1,
+/// Targeting crosshair, strictly centered in the view
+pub struct Crosshair<'a> {
+    game: &'a GameState,
+    theme: &'a Theme,
+}
+
+impl<'a> Crosshair<'a> {
+    pub fn new(game: &'a GameState, theme: &'a Theme) -> Self {
+        Self { game, theme }
+    }
+}
+
+impl Widget for Crosshair<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Center the five-cell crosshair on the middle column
+        let Some(x) = (area.width / 2).checked_sub(2) else {
+            return;
         };
+        if area.height == 0 {
+            return;
+        }
+        let y = area.height / 2;
 
-        let render_area = area.intersection(laser_area);
+        let locked = self.game.locked_enemy();
+        let (crosshair, hint) = match (locked, self.game.lock.progress()) {
+            (Some(_), _) => ("[>+<]", ColorHint::Alert),
+            // Blink while the lock is building
+            (None, Some(frames)) if frames % 4 < 2 => ("( + )", ColorHint::Bright),
+            _ => ("[ + ]", ColorHint::Bright),
+        };
+        let style = self.theme.style(hint).add_modifier(Modifier::BOLD);
+        draw_sprite(buf, area, x, y, crosshair, style);
 
-        if render_area.area() > 0 {
-            frame.render_widget(
-                Paragraph::new(sprite).style(Style::default().fg(Color::Red)),
-                render_area,
-            );
+        // Distance to the locked enemy under the crosshair
+        if let Some(enemy) = locked
+            && y + 1 < area.height
+        {
+            let readout = format!("{:03}", enemy.z.max(0.0).round() as u32);
+            let readout_x = (area.width / 2).saturating_sub(1);
+            draw_sprite(buf, area, readout_x, y + 1, &readout, style);
         }
     }
 }
 
-/// Render crosshair
-fn render_crosshair(frame: &mut Frame, area: Rect, _game: &GameState) {
-    let width = area.width;
-    let height = area.height;
+/// Cockpit/ship view, anchored to the middle of the screen
+pub struct Cockpit<'a> {
+    game: &'a GameState,
+    art: &'a Sprite,
+    color: ColorHint,
+    theme: &'a Theme,
+}
 
-    // Crosshair strictly centered
-    let center_x = (width / 2) as i16;
-    let center_y = (height / 2) as i16;
+impl<'a> Cockpit<'a> {
+    pub fn new(game: &'a GameState, art: &'a Sprite, color: ColorHint, theme: &'a Theme) -> Self {
+        Self {
+            game,
+            art,
+            color,
+            theme,
+        }
+    }
 
-    let ch_x = center_x - 2; // Center "[ + ]"
-    let ch_y = center_y;
+    /// Whether the frame should be drawn red this frame
+    ///
+    /// It flashes right after a hit, and blinks as a warning while the shield
+    /// is critical.
+    fn flashing(&self) -> bool {
+        let game = self.game;
+        let hit_flash = game.hit_frames > 0 && (game.hit_frames / 2).is_multiple_of(2);
+        let warning = game.shield_critical() && game.frame % 20 < 4;
+        hit_flash || warning
+    }
+}
 
-    if ch_x >= 0 && ch_x < width as i16 && ch_y >= 0 && ch_y < height as i16 {
-        let ch_area = Rect {
-            x: area.x + ch_x as u16,
-            y: area.y + ch_y as u16,
-            width: 5,
-            height: 1,
+impl Widget for Cockpit<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Center the cockpit (no parallax)
+        let (anchor_x, _) = self.art.anchor();
+        let left = (area.width / 2).saturating_sub(anchor_x);
+        let color = if self.flashing() {
+            ColorHint::Alert
+        } else {
+            self.color
         };
+        let style = self.theme.style(color);
 
-        let render_area = area.intersection(ch_area);
-        if render_area.area() > 0 {
-            frame.render_widget(
-                Paragraph::new("[ + ]").style(
-                    Style::default()
-                        .fg(PHOSPHOR_GREEN_BRIGHT)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                render_area,
-            );
+        for (row, line) in self
+            .art
+            .rows()
+            .iter()
+            .take(area.height as usize)
+            .enumerate()
+        {
+            draw_sprite(buf, area, left, row as u16, line, style);
+        }
+    }
+}
+
+/// Space for the radar to the right of the cockpit art, if it fits
+fn radar_area(cockpit: Rect, art: &Sprite) -> Option<Rect> {
+    let (anchor_x, _) = art.anchor();
+    let art_right = (cockpit.width / 2).saturating_sub(anchor_x) + art.width();
+    let left = art_right + 1;
+    if cockpit.height < 5 || left + RADAR_WIDTH > cockpit.width {
+        return None;
+    }
+    Some(Rect::new(
+        cockpit.x + left,
+        cockpit.y,
+        RADAR_WIDTH,
+        cockpit.height,
+    ))
+}
+
+/// Top-down scope of every enemy: bearing across, distance up
+///
+/// Blips show `^` above the ship, `v` below and `o` level with it; the
+/// locked target is `X`.
+pub struct Radar<'a> {
+    game: &'a GameState,
+    theme: &'a Theme,
+}
+
+impl<'a> Radar<'a> {
+    pub fn new(game: &'a GameState, theme: &'a Theme) -> Self {
+        Self { game, theme }
+    }
+}
+
+impl Widget for Radar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < 5 || area.height < 5 {
+            return;
+        }
+        let frame_style = self.theme.style(ColorHint::Dim);
+        let inner_width = area.width - 2;
+        let inner_height = area.height - 2;
+
+        let title = "RADAR";
+        let top = format!("+{:-^width$}+", title, width = inner_width as usize);
+        let bottom = format!("+{}+", "-".repeat(inner_width as usize));
+        draw_sprite(buf, area, 0, 0, &top, frame_style);
+        draw_sprite(buf, area, 0, area.height - 1, &bottom, frame_style);
+        for y in 1..area.height - 1 {
+            draw_sprite(buf, area, 0, y, "|", frame_style);
+            draw_sprite(buf, area, area.width - 1, y, "|", frame_style);
+        }
+
+        // The ship sits at the bottom middle, looking up the scope
+        let inner = Rect::new(1, 1, inner_width, inner_height);
+        let ship_x = inner.x + inner_width / 2;
+        let ship_y = inner.bottom() - 1;
+        draw_sprite(
+            buf,
+            area,
+            ship_x,
+            ship_y,
+            "A",
+            self.theme.style(ColorHint::Normal),
+        );
+
+        let game = self.game;
+        let target = game.lock.target();
+        for enemy in game.enemies.iter().filter(|e| e.is_visible()) {
+            let bearing = ((enemy.x - game.ship_x) / RADAR_RANGE_X).clamp(-1.0, 1.0);
+            let range = (enemy.z / RADAR_RANGE_Z).clamp(0.0, 1.0);
+            let x = ship_x as f32 + bearing * (inner_width / 2) as f32;
+            let y = ship_y as f32 - range * (inner_height - 1) as f32;
+
+            let (glyph, hint) = if Some(enemy.id) == target {
+                ("X", ColorHint::Alert)
+            } else if enemy.y < game.ship_y - RADAR_LEVEL {
+                ("^", ColorHint::Bright)
+            } else if enemy.y > game.ship_y + RADAR_LEVEL {
+                ("v", ColorHint::Bright)
+            } else {
+                ("o", ColorHint::Bright)
+            };
+            let (x, y) = (x.round() as u16, y.round() as u16);
+            draw_sprite(buf, area, x, y, glyph, self.theme.style(hint));
         }
     }
 }
 
-/// Render cockpit/ship view
-fn render_cockpit(frame: &mut Frame, area: Rect, _game: &GameState, art: &[&str]) {
-    let width = area.width as usize;
+/// Arrows on the edge of the view pointing at enemies outside it
+pub struct ThreatArrows<'a> {
+    game: &'a GameState,
+    theme: &'a Theme,
+}
+
+impl<'a> ThreatArrows<'a> {
+    pub fn new(game: &'a GameState, theme: &'a Theme) -> Self {
+        Self { game, theme }
+    }
+}
+
+impl Widget for ThreatArrows<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let projection = Projection::new(area, self.game);
+        let style = self.theme.style(ColorHint::Alert);
+        let (right, bottom) = (area.width as f32 - 1.0, area.height as f32 - 1.0);
+
+        for enemy in &self.game.enemies {
+            let Some((x, y, _)) = projection.project(enemy.x, enemy.y, enemy.z) else {
+                continue;
+            };
+            let glyph = if x < 0.0 {
+                '<'
+            } else if x >= right + 1.0 {
+                '>'
+            } else if y < 0.0 {
+                '^'
+            } else if y >= bottom + 1.0 {
+                'v'
+            } else {
+                continue;
+            };
+            let edge_x = x.clamp(0.0, right) as i32;
+            let edge_y = y.clamp(0.0, bottom) as u16;
+            put(buf, area, edge_x, edge_y, glyph, style);
+        }
+    }
+}
+
+/// Render HUD bar at bottom
+fn render_hud(frame: &mut Frame, area: Rect, game: &GameState, hud: &HudLayout, theme: &Theme) {
+    let (readings, gap) = hud.fit(game, area.width);
+    let mut spans = Vec::with_capacity(readings.len() * 2);
+    for (i, (text, hint)) in readings.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(gap));
+        }
+        spans.push(Span::styled(text, theme.style(hint)));
+    }
+
+    let hud_widget = Paragraph::new(Line::from(spans))
+        .style(theme.style(ColorHint::Bright))
+        .alignment(Alignment::Center);
+    frame.render_widget(hud_widget, area);
+}
+
+/// Announce the last end-of-wave bonus along the top of the view
+fn render_wave_bonus(buf: &mut Buffer, area: Rect, game: &GameState, theme: &Theme) {
+    let Some(bonus) = game.last_bonus else {
+        return;
+    };
+    if bonus.total() == 0 || game.frame.saturating_sub(bonus.frame) >= BONUS_FRAMES {
+        return;
+    }
+    let mut text = format!("WAVE {} CLEAR", bonus.wave);
+    if bonus.no_damage > 0 {
+        text += &format!("   NO DAMAGE +{}", bonus.no_damage);
+    }
+    if bonus.accuracy > 0 {
+        text += &format!("   ACCURACY +{}", bonus.accuracy);
+    }
+    let width = text.chars().count() as u16;
+    if area.height == 0 || width > area.width {
+        return;
+    }
+    let x = area.x + (area.width - width) / 2;
+    buf.set_string(x, area.y, text, theme.style(ColorHint::Alert).bold());
+}
+
+/// Label a demo game along the top of the screen
+pub fn render_demo_banner(frame: &mut Frame, theme: &Theme) {
+    let area = frame.area();
+    let text = "DEMO - PRESS ANY KEY";
+    let width = text.chars().count() as u16;
+    if area.height < 2 || width > area.width {
+        return;
+    }
+    let x = area.x + (area.width - width) / 2;
+    let style = theme.style(ColorHint::Bright).bold();
+    frame.buffer_mut().set_string(x, area.y + 1, text, style);
+}
 
-    // Center the cockpit (no parallax)
-    let cockpit_width = art.first().map(|s| s.len()).unwrap_or(0);
-    let padding = ((width as i32 - cockpit_width as i32) / 2).max(0) as usize;
+/// Render pause overlay for a game nobody is steering, such as a replay
+fn render_pause_overlay(frame: &mut Frame, area: Rect, theme: &Theme) {
+    render_overlay(frame, area, theme, "[ PAUSED ]", Vec::new());
+}
 
-    let lines: Vec<Line> = art
+/// Render the pause menu with the selected item highlighted
+fn render_pause_menu(frame: &mut Frame, area: Rect, menu: &App<PauseItem>, theme: &Theme) {
+    let lines = menu
+        .items()
         .iter()
-        .map(|&s| {
-            let padded = format!("{:>width$}", s, width = padding + s.len());
-            Line::from(Span::styled(padded, Style::default().fg(PHOSPHOR_GREEN)))
+        .enumerate()
+        .map(|(i, item)| {
+            let is_selected = i == menu.selected_index();
+            let (prefix, hint) = if is_selected {
+                ("> ", ColorHint::Normal)
+            } else {
+                ("  ", ColorHint::Dim)
+            };
+            // Pad so the labels line up under the centered title
+            let text = format!("{prefix}{:<12}", item.label());
+            Line::from(Span::styled(text, theme.style(hint)))
         })
         .collect();
-
-    let cockpit = Paragraph::new(lines);
-    frame.render_widget(cockpit, area);
+    render_overlay(frame, area, theme, "[ PAUSED ]", lines);
 }
 
-/// Render HUD bar at bottom
-fn render_hud(frame: &mut Frame, area: Rect, game: &GameState) {
-    // Shield bar: "SHIELD: ||||||||"
-    // Using simple pipe chars
-    let shield_str: String = (0..8)
-        .map(|i| if i < game.shield as usize { '|' } else { ' ' })
+/// Render the key bindings, one action per line
+fn render_controls_overlay(frame: &mut Frame, area: Rect, theme: &Theme) {
+    let help = controls::help();
+    let width = help.iter().map(|(_, keys)| keys.len()).max().unwrap_or(0);
+    let mut lines: Vec<Line> = help
+        .into_iter()
+        .map(|(label, keys)| {
+            Line::from(vec![
+                Span::styled(format!("{label:<16}"), theme.style(ColorHint::Normal)),
+                Span::styled(format!("{keys:<width$}"), theme.style(ColorHint::Dim)),
+            ])
+        })
         .collect();
+    lines.push(Line::from(""));
+    lines.push(hint_line("Press any key to go back", theme));
+    render_overlay(frame, area, theme, "[ CONTROLS ]", lines);
+}
 
-    // Layout: SHIELD  LASER  ALTITUDE  SCORE
-    // Using distinct spacing
-    let hud = format!(
-        "SHIELD: {}   LASER: READY   ALTITUDE: {:>4}   SCORE: {:06}",
-        shield_str, game.altitude, game.score
-    );
+/// Render game over overlay
+fn render_game_over_overlay(frame: &mut Frame, area: Rect, game: &GameState, theme: &Theme) {
+    let score = format!("Final score {:06}", game.score);
+    let lines = vec![
+        hint_line(&score, theme),
+        hint_line("Press ENTER to return to menu", theme),
+    ];
+    render_overlay(frame, area, theme, "[ GAME OVER ]", lines);
+}
 
-    let hud_widget = Paragraph::new(hud)
-        .style(Style::default().fg(PHOSPHOR_GREEN_BRIGHT))
-        .alignment(Alignment::Center);
-    frame.render_widget(hud_widget, area);
+/// A dim line of help text
+fn hint_line<'a>(hint: &str, theme: &Theme) -> Line<'a> {
+    Line::from(Span::styled(hint.to_string(), theme.style(ColorHint::Dim)))
 }
 
-/// Render pause overlay
-fn render_pause_overlay(frame: &mut Frame, area: Rect) {
-    let pause_text = vec![
+/// Render a centered box with a title and the given lines below it
+fn render_overlay(frame: &mut Frame, area: Rect, theme: &Theme, title: &str, lines: Vec<Line>) {
+    let mut text = vec![
         Line::from(""),
-        Line::from(Span::styled(
-            "[ PAUSED ]",
-            Style::default().fg(PHOSPHOR_GREEN_BRIGHT).bold(),
-        )),
+        Line::from(Span::styled(title, theme.style(ColorHint::Bright).bold())),
         Line::from(""),
-        Line::from(Span::styled(
-            "Press ENTER to resume",
-            Style::default().fg(PHOSPHOR_GREEN_DIM),
-        )),
-        Line::from(Span::styled(
-            "Press Q to return to menu",
-            Style::default().fg(PHOSPHOR_GREEN_DIM),
-        )),
     ];
+    text.extend(lines);
 
-    let pause_height = 5u16;
-    let pause_width = 30u16;
-    let pause_area = Rect {
-        x: area.x + (area.width.saturating_sub(pause_width)) / 2,
-        y: area.y + (area.height.saturating_sub(pause_height)) / 2,
-        width: pause_width,
-        height: pause_height,
+    let overlay_height = text.len() as u16;
+    // Wide enough for the longest line with a margin either side
+    let overlay_width = text
+        .iter()
+        .map(|line| line.width() as u16 + 4)
+        .max()
+        .unwrap_or(0)
+        .max(30);
+    let overlay_area = Rect {
+        x: area.x + (area.width.saturating_sub(overlay_width)) / 2,
+        y: area.y + (area.height.saturating_sub(overlay_height)) / 2,
+        width: overlay_width.min(area.width),
+        height: overlay_height.min(area.height),
     };
 
-    let pause_widget = Paragraph::new(pause_text)
-        .style(Style::default().bg(Color::Black))
+    let overlay_widget = Paragraph::new(text)
+        .style(Style::default().bg(theme.background))
         .alignment(Alignment::Center);
-    frame.render_widget(pause_widget, pause_area);
+    frame.render_widget(overlay_widget, overlay_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::{
+        Cycle,
+        enemy::Enemy,
+        game::{Difficulty, HIT_FEEDBACK_FRAMES, Laser},
+        hud::Gauge,
+        targeting::LOCK_FRAMES,
+        theme::{ColorDepth, Palette},
+    };
+
+    /// A frame with enemies and lasers at several sizes and a fresh explosion
+    fn busy_scene() -> GameState {
+        let mut game = GameState::new();
+        game.enemies = [(-0.25, 0.1, 60.0), (0.12, -0.2, 25.0), (-0.05, 0.05, 10.0)]
+            .into_iter()
+            .map(|(x, y, z)| {
+                let mut enemy = Enemy::new(0);
+                (enemy.x, enemy.y, enemy.z) = (x, y, z);
+                enemy
+            })
+            .collect();
+        game.lasers = vec![
+            Laser {
+                x: 0.1,
+                y: 0.2,
+                z: 70.0,
+            },
+            Laser {
+                x: 0.15,
+                y: -0.1,
+                z: 30.0,
+            },
+        ];
+        game.particles.spawn_explosion(0.3, 0.3, 40.0);
+        game.particles.update();
+        game
+    }
+
+    fn render_scene(game: &GameState, theme: &Theme, mode: RenderMode) -> Buffer {
+        let area = Rect::new(0, 0, 40, 14);
+        let mut buf = Buffer::empty(area);
+        render_view(&mut buf, area, game, &Assets::embedded(), theme, mode);
+        buf
+    }
+
+    /// The cockpit widget for `game`, drawn the way `render` picks its art
+    fn cockpit_buffer(game: &GameState, theme: &Theme) -> Buffer {
+        let assets = Assets::embedded();
+        let set = cockpit_for_shield(&assets, game);
+        let area = Rect::new(0, 0, 80, 14);
+        let mut buf = Buffer::empty(area);
+        Cockpit::new(game, set.frame(40.0), set.color(), theme).render(area, &mut buf);
+        buf
+    }
+
+    /// Each row of glyphs next to a row of modifier codes: `.` plain, `d` dim,
+    /// `b` bold and `R` bold reversed
+    fn snapshot(buf: &Buffer) -> Vec<String> {
+        let area = buf.area;
+        let mut out = Vec::new();
+        for y in area.top()..area.bottom() {
+            let glyphs: String = (area.left()..area.right())
+                .map(|x| buf[(x, y)].symbol())
+                .collect();
+            let modifiers: String = (area.left()..area.right())
+                .map(|x| {
+                    let modifier = buf[(x, y)].modifier;
+                    if modifier.contains(Modifier::REVERSED) {
+                        'R'
+                    } else if modifier.contains(Modifier::BOLD) {
+                        'b'
+                    } else if modifier.contains(Modifier::DIM) {
+                        'd'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            out.push(format!("{glyphs}|{modifiers}"));
+        }
+        out
+    }
+
+    #[test]
+    fn test_no_color_snapshot() {
+        let theme = Theme::new(Palette::Phosphor, ColorDepth::NoColor);
+        let buf = render_scene(&busy_scene(), &theme, RenderMode::Ascii);
+
+        // Lasers are reversed, enemies bold and the trench dim, so the laser
+        // crossing a pillar on row 4 still stands out
+        let expected = [
+            r"    ------ --------- --------- -----/   |....dddddd.ddddddddd.ddddddddd.dddddd...",
+            r"      \   .                 -o-  /      |......d...d.................bbb..d......",
+            r"        \          +*  x       /     .  |........d...........b..d.......d.....d..",
+            r"          \                  /          |..........d..................d..........",
+            r"   ||||||||||\          + ||||||||||    |...ddddddddddd............ddddRddddd....",
+            r"    .          \        / .           . |....d..........d........d.d...........d.",
+            r"                 \ ,  /                 |.................d.d..d.................",
+            r"                  [ + ]         .       |..................bbbbb.........d.......",
+            r".         ..     /, . \                 |d.........db.....dd.d.d.................",
+            r"         /-\   /      . \               |.........bbb...d......R.d...............",
+            r"=========|o|=======================o====|...ddddddbbbddd...........dddddddddb....",
+            r"/\/\/\/\/\-//\/\/\/\/\/\/\/\/\/\o\/\/o/\|dddddddddbbbddddddddddddddddddddbddddbdd",
+            r"================================o=++==o=|........d......................db.bb..b.",
+            r"      /                  .   +  o\ o o  |......d..................d......bd.b.b..",
+        ];
+        assert_eq!(snapshot(&buf), expected);
+    }
+
+    #[test]
+    fn test_no_color_keeps_every_distinction() {
+        use std::collections::HashMap;
+
+        let game = busy_scene();
+        let color = Theme::new(Palette::Phosphor, ColorDepth::TrueColor);
+        let mono = Theme::new(Palette::Phosphor, ColorDepth::NoColor);
+        let color = render_scene(&game, &color, RenderMode::Ascii);
+        let mono = render_scene(&game, &mono, RenderMode::Ascii);
+
+        // Cells told apart only by hue must be told apart some other way
+        let mut seen = HashMap::new();
+        for (color_cell, mono_cell) in color.content.iter().zip(&mono.content) {
+            let color_look = (color_cell.symbol(), color_cell.fg);
+            let mono_look = (mono_cell.symbol(), mono_cell.modifier);
+            assert_eq!(mono_cell.fg, Color::Reset);
+
+            let first = *seen.entry(mono_look).or_insert(color_look);
+            assert_eq!(
+                first, color_look,
+                "{mono_look:?} is used for both {first:?} and {color_look:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_monochrome_palette_separates_lasers_from_enemies() {
+        // Lasers and enemies are both white in this palette
+        let theme = Theme::new(Palette::Monochrome, ColorDepth::TrueColor);
+        let buf = render_scene(&busy_scene(), &theme, RenderMode::Ascii);
+        let laser = &buf[(30, 4)];
+        let enemy = &buf[(28, 1)];
+        assert_eq!((laser.symbol(), enemy.symbol()), ("|", "-"));
+        assert_eq!(laser.fg, enemy.fg);
+        assert!(laser.modifier.contains(Modifier::REVERSED));
+        assert!(!enemy.modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn test_sub_cell_modes_replace_ascii_lines() {
+        let mut game = busy_scene();
+        // Beams keep their ASCII lattice, so leave them out
+        game.environment = Environment::Trench(TrenchShape {
+            width: 0.8,
+            grid_spacing: 10.0,
+            pillar_spacing: 20.0,
+            beam_spacing: None,
+            debris_spacing: None,
+        });
+        let theme = Theme::default();
+        let ascii = render_scene(&game, &theme, RenderMode::Ascii);
+
+        for (mode, glyphs) in [
+            (RenderMode::Braille, '\u{2801}'..='\u{28FF}'),
+            (RenderMode::HalfBlock, '\u{2580}'..='\u{2588}'),
+        ] {
+            let buf = render_scene(&game, &theme, mode);
+            let is_dots = |x: u16, y: u16| {
+                let symbol = buf[(x, y)].symbol();
+                symbol.chars().all(|c| glyphs.contains(&c))
+            };
+
+            // The only slashes left belong to the near enemy's art
+            for (i, cell) in buf.content.iter().enumerate() {
+                if matches!(cell.symbol(), "/" | "\\") {
+                    assert!((9..12).contains(&(i % 40)), "{mode:?} wall at {i}");
+                }
+            }
+            // The far enemy and laser are dots instead of `.`
+            assert_eq!(ascii[(11, 8)].symbol(), ".");
+            assert!(is_dots(11, 8), "{mode:?}");
+            assert!(is_dots(22, 9), "{mode:?}");
+            // Nearer sprites keep their ASCII art
+            assert_eq!(buf[(28, 1)].symbol(), "-");
+            // The crosshair is unaffected
+            assert_eq!(buf[(20, 7)].symbol(), "+");
+        }
+    }
+
+    #[test]
+    fn test_near_stars_shift_further() {
+        let view = Rect::new(0, 0, 80, 20);
+        let shift = |z| {
+            let star = Star { x: 0.1, y: 0.0, z };
+            let mut game = GameState::new();
+            let projection = Projection::new(view, &game);
+            let (before, _) = project_star(&projection, &game, &star).unwrap();
+            game.ship_x = 0.5;
+            let projection = Projection::new(view, &game);
+            let (after, _) = project_star(&projection, &game, &star).unwrap();
+            before - after
+        };
+        assert!(shift(90.0) > 0);
+        assert!(shift(10.0) > shift(90.0));
+    }
+
+    #[test]
+    fn test_sky_continues_the_view() {
+        let game = GameState::new();
+        let sky = Rect::new(0, 0, 80, 4);
+        let view = Rect::new(0, 4, 80, 20);
+        let mut buf = Buffer::empty(sky.union(view));
+        Sky::new(&game, view, &Theme::default()).render(sky, &mut buf);
+
+        // Every star projecting into the rows just above the view is drawn
+        // in the sky band
+        let projection = Projection::new(view, &game);
+        let mut above = 0;
+        for star in game.stars.iter() {
+            let Some((x, y)) = project_star(&projection, &game, star) else {
+                continue;
+            };
+            if (0..80).contains(&x) && (-4..0).contains(&y) {
+                above += 1;
+                assert_ne!(buf[(x as u16, (y + 4) as u16)].symbol(), " ");
+            }
+        }
+        assert!(above > 0);
+    }
+
+    /// Glyphs drawn by the scenery layer alone
+    fn scenery(environment: Environment, mode: RenderMode) -> Vec<String> {
+        let mut game = GameState::new();
+        game.environment = environment;
+        game.distance = 30.0;
+        let assets = Assets::embedded();
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, 40, 14);
+        let mut buf = Buffer::empty(area);
+        let mut canvas = Canvas::new(&mut buf, area);
+        match environment {
+            Environment::Trench(shape) => Trench::new(&game, shape, &theme, mode).draw(&mut canvas),
+            Environment::Canyon(shape) => Canyon::new(&game, shape, &theme, mode).draw(&mut canvas),
+            Environment::OpenSpace | Environment::AsteroidField(_) => {}
+        }
+        Obstacles::new(&game, &assets.asteroid, &assets.debris, &theme, mode).draw(&mut canvas);
+        snapshot(&buf)
+            .into_iter()
+            .map(|row| row[..row.find('|').unwrap()].to_string())
+            .collect()
+    }
+
+    fn drawn(rows: &[String]) -> usize {
+        rows.iter()
+            .flat_map(|row| row.chars())
+            .filter(|&ch| ch != ' ')
+            .count()
+    }
+
+    #[test]
+    fn test_open_space_has_no_scenery() {
+        assert_eq!(
+            drawn(&scenery(Environment::OpenSpace, RenderMode::Ascii)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_trench_width_moves_the_walls() {
+        let wall = |width| {
+            let shape = TrenchShape {
+                width,
+                grid_spacing: 10.0,
+                pillar_spacing: 20.0,
+                beam_spacing: None,
+                debris_spacing: None,
+            };
+            let rows = scenery(Environment::Trench(shape), RenderMode::Ascii);
+            rows[13].find('/').unwrap()
+        };
+        assert!(wall(0.5) > wall(0.8));
+    }
+
+    #[test]
+    fn test_canyon_and_asteroids_render() {
+        for &environment in crate::tui::environment::LEVELS {
+            if environment == Environment::OpenSpace {
+                continue;
+            }
+            for &mode in RenderMode::ALL {
+                let rows = scenery(environment, mode);
+                assert!(drawn(&rows) > 0, "{} {mode:?}", environment.label());
+            }
+        }
+    }
+
+    #[test]
+    fn test_canyon_walls_follow_the_bend() {
+        let shape = CanyonShape {
+            width: 0.5,
+            curvature: 0.0,
+            spacing: 12.0,
+            debris_spacing: None,
+        };
+        let straight = scenery(Environment::Canyon(shape), RenderMode::Ascii);
+        let curved = scenery(
+            Environment::Canyon(CanyonShape {
+                curvature: 0.5,
+                ..shape
+            }),
+            RenderMode::Ascii,
+        );
+        assert_ne!(straight, curved);
+    }
+
+    #[test]
+    fn test_crosshair_shows_lock_and_distance() {
+        let mut game = GameState::with_seed(1);
+        game.enemies.clear();
+        let mut enemy = Enemy::new(0);
+        (enemy.id, enemy.x, enemy.y, enemy.z) = (5, 0.0, 0.0, 80.0);
+        game.enemies.push(enemy);
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, 40, 14);
+        let crosshair = |game: &GameState| {
+            let mut buf = Buffer::empty(area);
+            Crosshair::new(game, &theme).render(area, &mut buf);
+            let row = |y: u16| -> String { (18..23).map(|x| buf[(x, y)].symbol()).collect() };
+            (row(7), row(8))
+        };
+        assert_eq!(crosshair(&game).0, "[ + ]");
+
+        game.update();
+        assert_eq!(crosshair(&game).0, "( + )");
+
+        for _ in 1..LOCK_FRAMES {
+            game.update();
+        }
+        let (sight, readout) = crosshair(&game);
+        assert_eq!(sight, "[>+<]");
+        let z = game.enemies[0].z.round() as u32;
+        assert_eq!(readout.trim(), format!("{z:03}"));
+    }
+
+    #[test]
+    fn test_view_offset_matches_projection() {
+        let mut game = GameState::new();
+        game.ship_x = 0.4;
+        game.ship_y = -0.2;
+        let area = Rect::new(0, 0, 80, 24);
+        let projection = Projection::new(area, &game);
+        let (x, y, _) = projection.project(0.3, 0.1, 40.0).unwrap();
+        let (off_x, off_y) = game.view_offset(0.3, 0.1, 40.0).unwrap();
+        assert!((x - (40.0 + off_x * 80.0)).abs() < 1e-3);
+        assert!((y - (12.0 + off_y * 24.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_radar_plots_bearing_and_distance() {
+        let mut game = GameState::with_seed(1);
+        game.enemies.clear();
+        for (id, x, y, z) in [
+            (1, 0.0, 0.0, 100.0),
+            (2, 1.5, -0.5, 50.0),
+            (3, -3.0, 0.5, 0.0),
+        ] {
+            let mut enemy = Enemy::new(0);
+            (enemy.id, enemy.x, enemy.y, enemy.z) = (id, x, y, z);
+            game.enemies.push(enemy);
+        }
+        game.enemies[2].z = 1.0;
+        let area = Rect::new(0, 0, RADAR_WIDTH, 8);
+        let mut buf = Buffer::empty(area);
+        Radar::new(&game, &Theme::default()).render(area, &mut buf);
+        let rows: Vec<String> = (0..8)
+            .map(|y| (0..RADAR_WIDTH).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+
+        assert_eq!(
+            rows,
+            [
+                "+-----RADAR-----+",
+                "|       o       |",
+                "|               |",
+                "|               |",
+                "|              ^|",
+                "|               |",
+                "|v      A       |",
+                "+---------------+",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_radar_needs_room_beside_the_cockpit() {
+        let art = Assets::embedded().cockpit.frame(20.0).clone();
+        assert_eq!(radar_area(Rect::new(0, 0, 60, 8), &art), None);
+        let area = radar_area(Rect::new(0, 30, 100, 8), &art).unwrap();
+        assert_eq!((area.y, area.width, area.height), (30, RADAR_WIDTH, 8));
+        assert!(area.x >= 50 + 22);
+    }
+
+    #[test]
+    fn test_arrows_point_at_enemies_off_view() {
+        let mut game = GameState::with_seed(1);
+        game.enemies.clear();
+        for (x, y) in [(-1.0, 0.0), (1.0, 0.1), (0.0, -1.0), (0.05, 0.05)] {
+            let mut enemy = Enemy::new(0);
+            (enemy.x, enemy.y, enemy.z) = (x, y, 20.0);
+            game.enemies.push(enemy);
+        }
+        let area = Rect::new(0, 0, 40, 14);
+        let mut buf = Buffer::empty(area);
+        ThreatArrows::new(&game, &Theme::default()).render(area, &mut buf);
+
+        assert_eq!(buf[(0, 7)].symbol(), "<");
+        assert_eq!(buf[(39, 10)].symbol(), ">");
+        assert_eq!(buf[(20, 0)].symbol(), "^");
+        // Enemies in view get no arrow
+        let arrows = buf.content.iter().filter(|c| c.symbol() != " ").count();
+        assert_eq!(arrows, 3);
+    }
+
+    /// Draw a paused game with `menu` and return the screen as text
+    fn paused_screen(menu: &App<PauseItem>) -> String {
+        let mut game = GameState::with_seed(1);
+        game.toggle_pause();
+        let mut terminal = Terminal::new(backend::TestBackend::new(80, 40)).unwrap();
+        terminal
+            .draw(|frame| {
+                let (assets, theme) = (Assets::embedded(), Theme::default());
+                let hud = HudLayout::default();
+                render(
+                    frame,
+                    &game,
+                    &assets,
+                    &hud,
+                    &theme,
+                    RenderMode::Ascii,
+                    Some(menu),
+                );
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn test_pause_menu_and_controls_page() {
+        let mut menu = App::pause();
+        menu.next();
+        let screen = paused_screen(&menu);
+        assert!(screen.contains("[ PAUSED ]"));
+        assert!(screen.contains("> RESTART"));
+        assert!(screen.contains("QUIT GAME"));
+
+        menu.next();
+        menu.next();
+        menu.select();
+        let screen = paused_screen(&menu);
+        assert!(screen.contains("[ CONTROLS ]"));
+        assert!(screen.contains("LEFT / A"));
+        assert!(!screen.contains("RESTART"));
+    }
+
+    #[test]
+    fn test_demo_banner() {
+        let game = GameState::with_seed(1);
+        let mut terminal = Terminal::new(backend::TestBackend::new(80, 40)).unwrap();
+        terminal
+            .draw(|frame| {
+                let (assets, theme) = (Assets::embedded(), Theme::default());
+                let hud = HudLayout::default();
+                render(frame, &game, &assets, &hud, &theme, RenderMode::Ascii, None);
+                render_demo_banner(frame, &theme);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..80).map(|x| buffer[(x, 1)].symbol()).collect();
+        assert_eq!(row.trim(), "DEMO - PRESS ANY KEY");
+    }
+
+    #[test]
+    fn test_damage_follows_the_starting_shield() {
+        let theme = Theme::default();
+        let intact = cockpit_buffer(&GameState::with_seed(1), &theme);
+        let hard = GameState::with_seed(1).with_difficulty(Difficulty::Hard);
+        assert_eq!(cockpit_buffer(&hard, &theme), intact);
+        let (readings, _) = HudLayout::new(&[Gauge::Shield]).fit(&hard, 80);
+        assert_eq!(readings[0].0, "SHIELD: ||||||");
+
+        // Down to half a hard shield is damage a normal game wouldn't show yet
+        let mut hard = hard;
+        hard.shield = 3;
+        assert!(hard.shield_damaged() && !hard.shield_critical());
+        assert_ne!(cockpit_buffer(&hard, &theme), intact);
+        let mut normal = GameState::with_seed(1);
+        normal.shield = 7;
+        assert!(!normal.shield_damaged());
+    }
+
+    /// Just the glyphs of each row
+    fn glyphs(buf: &Buffer) -> Vec<String> {
+        snapshot(buf)
+            .into_iter()
+            .map(|row| row.split('|').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_cockpit_art_at_each_threshold() {
+        let assets = Assets::embedded();
+        let theme = Theme::default();
+        let art = |set: &SpriteSet| {
+            let area = Rect::new(0, 0, 80, 14);
+            let mut buf = Buffer::empty(area);
+            let game = GameState::with_seed(1);
+            Cockpit::new(&game, set.frame(40.0), set.color(), &theme).render(area, &mut buf);
+            glyphs(&buf)
+        };
+        for (shield, set) in [
+            (10, &assets.cockpit),
+            (7, &assets.cockpit),
+            (6, &assets.cockpit_damaged),
+            (4, &assets.cockpit_damaged),
+            (3, &assets.cockpit_critical),
+            (1, &assets.cockpit_critical),
+        ] {
+            let mut game = GameState::with_seed(1);
+            game.shield = shield;
+            assert_eq!(glyphs(&cockpit_buffer(&game, &theme)), art(set), "{shield}");
+        }
+        assert_ne!(art(&assets.cockpit), art(&assets.cockpit_damaged));
+        assert_ne!(art(&assets.cockpit_damaged), art(&assets.cockpit_critical));
+    }
+
+    #[test]
+    fn test_hit_flash_marks_the_cockpit() {
+        let mut game = GameState::with_seed(1);
+        // A cell of the frame itself, not the blank space around it
+        let drawn = |buf: &Buffer| {
+            buf.content
+                .iter()
+                .find(|cell| cell.symbol() != " ")
+                .unwrap()
+                .clone()
+        };
+
+        let cues = Theme::new(Palette::default(), ColorDepth::NoColor);
+        let color = Theme::new(Palette::default(), ColorDepth::TrueColor);
+        let calm = drawn(&cockpit_buffer(&game, &cues));
+        assert!(!calm.modifier.contains(Modifier::REVERSED));
+        assert_ne!(
+            drawn(&cockpit_buffer(&game, &color)).fg,
+            color.color(ColorHint::Alert)
+        );
+
+        game.hit_frames = HIT_FEEDBACK_FRAMES;
+        let flash = drawn(&cockpit_buffer(&game, &cues));
+        assert!(flash.modifier.contains(Modifier::REVERSED));
+        assert_eq!(
+            drawn(&cockpit_buffer(&game, &color)).fg,
+            color.color(ColorHint::Alert)
+        );
+
+        // The flash blinks off between its on frames
+        game.hit_frames = 2;
+        assert!(
+            !drawn(&cockpit_buffer(&game, &cues))
+                .modifier
+                .contains(Modifier::REVERSED)
+        );
+    }
+
+    #[test]
+    fn test_shake_moves_cells() {
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, 10, 5);
+        let mut buf = Buffer::empty(area);
+        buf[(4, 2)].set_symbol("X");
+        buf[(0, 0)].set_symbol("Y");
+
+        shake(&mut buf, area, 2, 1, &theme);
+        assert_eq!(buf[(6, 3)].symbol(), "X");
+        assert_eq!(buf[(2, 1)].symbol(), "Y");
+        assert_eq!(buf[(4, 2)].symbol(), " ");
+        // Cells shaken in from outside are blank background
+        assert_eq!(buf[(0, 0)].symbol(), " ");
+        assert_eq!(buf[(0, 0)].bg, theme.background);
+        let drawn = buf.content.iter().filter(|c| c.symbol() != " ").count();
+        assert_eq!(drawn, 2);
+
+        shake(&mut buf, area, -2, -1, &theme);
+        assert_eq!(buf[(4, 2)].symbol(), "X");
+        assert_eq!(buf[(0, 0)].symbol(), "Y");
+
+        // No offset leaves the buffer alone
+        let before = buf.clone();
+        shake(&mut buf, area, 0, 0, &theme);
+        assert_eq!(buf, before);
+    }
 }
@@ -0,0 +1,255 @@
+//! HUD bar layout
+//!
+//! The HUD is a row of gauges, each with a full and a short form. When the
+//! full forms don't fit the width, every gauge switches to its short form,
+//! and if that still doesn't fit the least important gauges are dropped.
+
+use super::{game::GameState, sprite::ColorHint};
+
+/// Gap between full-form gauges
+const WIDE_GAP: &str = "   ";
+/// Gap between short-form gauges
+const NARROW_GAP: &str = "  ";
+
+/// A value shown on the HUD
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gauge {
+    Shield,
+    Laser,
+    Speed,
+    Altitude,
+    Wave,
+    Kills,
+    Accuracy,
+    Combo,
+    Score,
+}
+
+impl Gauge {
+    /// Get all gauges in their default order
+    pub fn all() -> &'static [Gauge] {
+        &[
+            Gauge::Shield,
+            Gauge::Laser,
+            Gauge::Speed,
+            Gauge::Altitude,
+            Gauge::Wave,
+            Gauge::Kills,
+            Gauge::Accuracy,
+            Gauge::Combo,
+            Gauge::Score,
+        ]
+    }
+
+    /// Get the name used to pick this gauge
+    pub fn label(&self) -> &'static str {
+        match self {
+            Gauge::Shield => "shield",
+            Gauge::Laser => "laser",
+            Gauge::Speed => "speed",
+            Gauge::Altitude => "altitude",
+            Gauge::Wave => "wave",
+            Gauge::Kills => "kills",
+            Gauge::Accuracy => "accuracy",
+            Gauge::Combo => "combo",
+            Gauge::Score => "score",
+        }
+    }
+
+    /// Parse a gauge name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Gauge::all()
+            .iter()
+            .copied()
+            .find(|g| g.label().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Rank for keeping the gauge on a narrow HUD; lower is kept longer
+    fn priority(&self) -> u8 {
+        match self {
+            Gauge::Shield => 0,
+            Gauge::Score => 1,
+            Gauge::Combo => 2,
+            Gauge::Laser => 3,
+            Gauge::Wave => 4,
+            Gauge::Kills => 5,
+            Gauge::Accuracy => 6,
+            Gauge::Altitude => 7,
+            Gauge::Speed => 8,
+        }
+    }
+
+    /// Text and color of this gauge for the current game
+    pub fn reading(&self, game: &GameState, short: bool) -> (String, ColorHint) {
+        let normal = ColorHint::Bright;
+        match self {
+            Gauge::Shield => {
+                // One segment per point the game started with
+                let bar: String = (0..game.max_shield())
+                    .map(|i| if i < game.shield { '|' } else { '.' })
+                    .collect();
+                let hint = if game.shield_critical() {
+                    ColorHint::Alert
+                } else {
+                    normal
+                };
+                let label = if short { "SH" } else { "SHIELD:" };
+                (format!("{label} {bar}"), hint)
+            }
+            Gauge::Laser => {
+                let state = if game.laser_ready() { "READY" } else { "-----" };
+                let label = if short { "LSR" } else { "LASER:" };
+                (format!("{label} {state}"), normal)
+            }
+            Gauge::Speed => {
+                // A tap sideways reads about 20 and fades back to 0
+                let speed = (game.speed() * 1000.0).round().min(999.0) as u32;
+                let label = if short { "SPD" } else { "SPEED:" };
+                (format!("{label} {speed:>3}"), normal)
+            }
+            Gauge::Altitude => {
+                let label = if short { "ALT" } else { "ALTITUDE:" };
+                (format!("{label} {:>4}", game.altitude()), normal)
+            }
+            Gauge::Wave => {
+                let label = if short { "W" } else { "WAVE:" };
+                (format!("{label} {}", game.waves), normal)
+            }
+            Gauge::Kills => {
+                let label = if short { "K" } else { "KILLS:" };
+                (format!("{label} {}", game.kills), normal)
+            }
+            Gauge::Accuracy => {
+                let accuracy = match game.accuracy() {
+                    Some(accuracy) => format!("{:>3}%", (accuracy * 100.0).round() as u32),
+                    None => " --%".to_string(),
+                };
+                let label = if short { "ACC" } else { "ACCURACY:" };
+                (format!("{label} {accuracy}"), normal)
+            }
+            Gauge::Combo => {
+                let label = if short { "" } else { "COMBO: " };
+                (format!("{label}x{}", game.multiplier()), normal)
+            }
+            Gauge::Score => {
+                let label = if short { "" } else { "SCORE: " };
+                (format!("{label}{:06}", game.score), normal)
+            }
+        }
+    }
+}
+
+/// Which gauges the HUD shows, in order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HudLayout {
+    gauges: Vec<Gauge>,
+}
+
+impl HudLayout {
+    /// Show `gauges` left to right
+    pub fn new(gauges: &[Gauge]) -> Self {
+        Self {
+            gauges: gauges.to_vec(),
+        }
+    }
+
+    /// The configured gauges in order
+    pub fn gauges(&self) -> &[Gauge] {
+        &self.gauges
+    }
+
+    /// Readings that fit in `width` cells, and the gap to put between them
+    pub fn fit(&self, game: &GameState, width: u16) -> (Vec<(String, ColorHint)>, &'static str) {
+        let width = width as usize;
+        let total = |readings: &[(String, ColorHint)], gap: &str| {
+            let text: usize = readings.iter().map(|(text, _)| text.chars().count()).sum();
+            text + gap.len() * readings.len().saturating_sub(1)
+        };
+
+        let full: Vec<_> = self.gauges.iter().map(|g| g.reading(game, false)).collect();
+        if total(&full, WIDE_GAP) <= width {
+            return (full, WIDE_GAP);
+        }
+
+        // Short forms, dropping the least important gauges until they fit
+        let mut kept = self.gauges.clone();
+        loop {
+            let short: Vec<_> = kept.iter().map(|g| g.reading(game, true)).collect();
+            if total(&short, NARROW_GAP) <= width || short.is_empty() {
+                return (short, NARROW_GAP);
+            }
+            let drop = kept
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, g)| g.priority())
+                .map(|(i, _)| i)
+                .unwrap_or_default();
+            kept.remove(drop);
+        }
+    }
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        Self::new(Gauge::all())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(readings: &[(String, ColorHint)]) -> Vec<&str> {
+        readings.iter().map(|(text, _)| text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_names_round_trip() {
+        for gauge in Gauge::all() {
+            assert_eq!(Gauge::from_name(gauge.label()), Some(*gauge));
+        }
+        assert_eq!(Gauge::from_name(" Score "), Some(Gauge::Score));
+        assert_eq!(Gauge::from_name("fuel"), None);
+    }
+
+    #[test]
+    fn test_wide_hud_uses_full_forms() {
+        let mut game = GameState::with_seed(1);
+        game.shield = 7;
+        game.score = 120;
+        let hud = HudLayout::new(&[Gauge::Shield, Gauge::Accuracy, Gauge::Score]);
+        let (readings, gap) = hud.fit(&game, 80);
+        assert_eq!(gap, WIDE_GAP);
+        assert_eq!(
+            texts(&readings),
+            ["SHIELD: |||||||...", "ACCURACY:  --%", "SCORE: 000120"]
+        );
+    }
+
+    #[test]
+    fn test_narrow_hud_shortens_then_drops() {
+        let game = GameState::with_seed(1);
+        let hud = HudLayout::default();
+
+        let (readings, gap) = hud.fit(&game, 60);
+        assert_eq!(gap, NARROW_GAP);
+        assert_eq!(texts(&readings)[0], "SH ||||||||||");
+        assert!(readings.len() < Gauge::all().len());
+        // Speed goes first, the shield and score stay longest
+        assert!(!texts(&readings).iter().any(|t| t.starts_with("SPD")));
+
+        let (readings, _) = hud.fit(&game, 22);
+        assert_eq!(texts(&readings), ["SH ||||||||||", "000000"]);
+
+        let (readings, _) = hud.fit(&game, 3);
+        assert!(readings.is_empty());
+    }
+
+    #[test]
+    fn test_low_shield_alerts() {
+        let mut game = GameState::with_seed(1);
+        game.shield = 2;
+        let (_, hint) = Gauge::Shield.reading(&game, false);
+        assert_eq!(hint, ColorHint::Alert);
+    }
+}
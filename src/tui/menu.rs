@@ -4,8 +4,10 @@
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MenuItem {
     StartGame,
+    Continue,
     Options,
     HighScores,
+    Replay,
     Exit,
 }
 
@@ -14,8 +16,10 @@ impl MenuItem {
     pub fn all() -> &'static [MenuItem] {
         &[
             MenuItem::StartGame,
+            MenuItem::Continue,
             MenuItem::Options,
             MenuItem::HighScores,
+            MenuItem::Replay,
             MenuItem::Exit,
         ]
     }
@@ -24,9 +28,51 @@ impl MenuItem {
     pub fn label(&self) -> &'static str {
         match self {
             MenuItem::StartGame => "START GAME",
+            MenuItem::Continue => "CONTINUE",
             MenuItem::Options => "OPTIONS",
             MenuItem::HighScores => "HIGH SCORES",
+            MenuItem::Replay => "WATCH REPLAY",
             MenuItem::Exit => "EXIT",
         }
     }
 }
+
+/// Represents an option on the pause menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseItem {
+    Resume,
+    Restart,
+    Options,
+    Controls,
+    SaveAndQuit,
+    QuitToMenu,
+    QuitGame,
+}
+
+impl PauseItem {
+    /// Get all pause menu items in display order
+    pub fn all() -> &'static [PauseItem] {
+        &[
+            PauseItem::Resume,
+            PauseItem::Restart,
+            PauseItem::Options,
+            PauseItem::Controls,
+            PauseItem::SaveAndQuit,
+            PauseItem::QuitToMenu,
+            PauseItem::QuitGame,
+        ]
+    }
+
+    /// Get the display text for this menu item
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseItem::Resume => "RESUME",
+            PauseItem::Restart => "RESTART",
+            PauseItem::Options => "OPTIONS",
+            PauseItem::Controls => "CONTROLS",
+            PauseItem::SaveAndQuit => "SAVE & QUIT",
+            PauseItem::QuitToMenu => "QUIT TO MENU",
+            PauseItem::QuitGame => "QUIT GAME",
+        }
+    }
+}
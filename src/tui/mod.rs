@@ -3,15 +3,49 @@
 //! This module provides the retro-styled start menu and UI components
 //! using ratatui for rendering.
 
+mod ai;
 mod app;
+pub mod assets;
+mod attract;
+pub mod audio;
+pub mod autopilot;
+mod controls;
+mod crt;
+mod cycle;
 mod enemy;
+pub mod environment;
+mod formation;
 mod game;
-mod game_ui;
+pub mod game_ui;
+mod hud;
 mod menu;
+mod options;
+pub mod particle;
+pub mod raster;
+pub mod replay;
+pub mod save;
+pub mod scores;
+mod settings;
+pub mod soak;
+#[cfg(feature = "synth")]
+mod speaker;
+pub mod sprite;
+pub mod starfield;
+pub mod synth;
+mod targeting;
+pub mod theme;
 mod ui;
 
 pub use app::App;
-pub use menu::MenuItem;
+pub use assets::Assets;
+pub use cycle::Cycle;
+pub use enemy::{Enemy, EnemyType};
+pub use game::{Difficulty, GameState, HitCause, Laser, time_seed};
+pub use hud::{Gauge, HudLayout};
+pub use menu::{MenuItem, PauseItem};
+pub use raster::RenderMode;
+pub use settings::{DEFAULT_FPS, Settings};
+pub use theme::{ColorDepth, Palette, Theme};
 
 use color_eyre::Result;
 use crossterm::{
@@ -20,12 +54,43 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::prelude::*;
-use std::io;
+use std::{io, time::Instant};
 
-use game::GameState;
+use attract::Attract;
+use audio::{AudioSink, Sound};
+use crt::Crt;
+use replay::{Action, Replay};
+
+/// Where the TUI starts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Start {
+    /// The start menu
+    Menu,
+    /// Straight into a game from `seed` (or a seed from the clock),
+    /// leaving when it ends
+    Play { seed: Option<u64> },
+    /// Watch a recording, leaving when it ends
+    Replay(Replay),
+}
+
+/// Why the TUI closed, when the player chose to leave it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// Exit from the menu, or quit game from the pause menu
+    Quit,
+    /// High scores from the menu, to be printed after the TUI closes
+    HighScores,
+}
+
+/// Run the TUI application and return how the player left it
+///
+/// `settings` are the initial options, and `depth` the colors the terminal
+/// is drawn with. Quitting the menu with Q, or finishing a game or replay
+/// started from the command line, returns `None`.
+pub fn run(settings: Settings, depth: ColorDepth, start: Start) -> Result<Option<Exit>> {
+    // Load art before touching the terminal so asset errors print normally
+    let assets = Assets::load(assets::user_dir().as_deref())?;
 
-/// Run the TUI application and return the selected menu action
-pub fn run() -> Result<Option<MenuItem>> {
     // Setup terminal
     io::stdout().execute(EnterAlternateScreen)?;
     enable_raw_mode()?;
@@ -33,7 +98,44 @@ pub fn run() -> Result<Option<MenuItem>> {
     let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
     terminal.clear()?;
 
-    let result = run_main_loop(&mut terminal);
+    let result = match start {
+        Start::Menu => run_main_loop(&mut terminal, &assets, settings, depth),
+        Start::Play { seed } => {
+            let mut settings = settings;
+            let seed = seed.unwrap_or_else(game::time_seed);
+            let replay = Replay::new(seed).with_difficulty(settings.difficulty);
+            let game = replay.start();
+            let mut replay = Some(replay);
+            let unicode = raster::unicode_supported();
+            let mut audio = audio::default_sink();
+            run_game(
+                &mut terminal,
+                game,
+                &mut replay,
+                &assets,
+                &mut settings,
+                depth,
+                unicode,
+                audio.as_mut(),
+            )
+            .and_then(|end| finish_game(end, replay.as_ref()))
+            .map(|_| None)
+        }
+        Start::Replay(replay) => {
+            let unicode = raster::unicode_supported();
+            let mut audio = audio::default_sink();
+            run_replay(
+                &mut terminal,
+                &replay,
+                &assets,
+                &settings,
+                depth,
+                unicode,
+                audio.as_mut(),
+            )
+            .map(|_| None)
+        }
+    };
 
     // Restore terminal
     disable_raw_mode()?;
@@ -43,25 +145,112 @@ pub fn run() -> Result<Option<MenuItem>> {
 }
 
 /// Main application loop handling menu and game states
-fn run_main_loop<B: Backend>(terminal: &mut Terminal<B>) -> Result<Option<MenuItem>> {
+fn run_main_loop<B: Backend>(
+    terminal: &mut Terminal<B>,
+    assets: &Assets,
+    mut settings: Settings,
+    depth: ColorDepth,
+) -> Result<Option<Exit>> {
+    let unicode = raster::unicode_supported();
+    let mut audio = audio::default_sink();
+
+    // Shown under the menu the next time it opens
+    let mut notice = None;
+
     loop {
+        let theme = Theme::new(settings.palette, depth).with_shape_cues(settings.shape_cues);
+
+        let mut crt = Crt::new(settings.crt);
+
         // Run menu and get selection
-        let mut app = App::new();
-        run_menu(terminal, &mut app)?;
+        let mut app = App::new().with_notice(notice.take());
+        run_menu(
+            terminal,
+            &mut app,
+            assets,
+            &theme,
+            &mut crt,
+            &settings,
+            unicode,
+            audio.as_mut(),
+        )?;
 
         match app.selected_action() {
             Some(MenuItem::StartGame) => {
-                // Run the game
-                let mut game = GameState::new();
-                run_game(terminal, &mut game)?;
+                // Run the game, recording it for replay
+                let replay = Replay::new(game::time_seed()).with_difficulty(settings.difficulty);
+                let game = replay.start();
+                let mut replay = Some(replay);
+                let end = run_game(
+                    terminal,
+                    game,
+                    &mut replay,
+                    assets,
+                    &mut settings,
+                    depth,
+                    unicode,
+                    audio.as_mut(),
+                )?;
+                if finish_game(end, replay.as_ref())? {
+                    return Ok(Some(Exit::Quit));
+                }
                 // Game exited - loop back to menu
             }
-            Some(MenuItem::Exit) => {
-                return Ok(Some(MenuItem::Exit));
+            Some(MenuItem::Continue) => {
+                // Nothing to continue until a game has been saved
+                if let Some(path) = save::path().filter(|p| p.is_file()) {
+                    let game = match save::load(&path) {
+                        Ok(game) => game,
+                        Err(err) => {
+                            // A bad save shouldn't end the program, now or
+                            // on every Continue after
+                            save::set_aside(&path)?;
+                            notice = Some(format!("SAVE UNREADABLE: {}", err.root_cause()));
+                            continue;
+                        }
+                    };
+                    // Each save resumes once
+                    std::fs::remove_file(&path)?;
+                    // No recording until the player restarts with a new seed
+                    let mut replay = None;
+                    let end = run_game(
+                        terminal,
+                        game,
+                        &mut replay,
+                        assets,
+                        &mut settings,
+                        depth,
+                        unicode,
+                        audio.as_mut(),
+                    )?;
+                    if finish_game(end, replay.as_ref())? {
+                        return Ok(Some(Exit::Quit));
+                    }
+                }
             }
-            Some(action) => {
-                // Other menu items - for now just return
-                return Ok(Some(action));
+            Some(MenuItem::Replay) => {
+                // Nothing to watch until a game has been played
+                if let Some(path) = replay::last_path().filter(|p| p.is_file()) {
+                    let replay = Replay::load(&path)?;
+                    run_replay(
+                        terminal,
+                        &replay,
+                        assets,
+                        &settings,
+                        depth,
+                        unicode,
+                        audio.as_mut(),
+                    )?;
+                }
+            }
+            Some(MenuItem::Options) => {
+                run_options(terminal, &mut settings, assets, depth, audio.as_mut())?;
+            }
+            Some(MenuItem::HighScores) => {
+                return Ok(Some(Exit::HighScores));
+            }
+            Some(MenuItem::Exit) => {
+                return Ok(Some(Exit::Quit));
             }
             None => {
                 // User quit with 'q'
@@ -71,19 +260,45 @@ fn run_main_loop<B: Backend>(terminal: &mut Terminal<B>) -> Result<Option<MenuIt
     }
 }
 
-/// Run the menu loop
-fn run_menu<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+/// Run the menu loop, showing a demo game whenever it is left idle
+#[allow(clippy::too_many_arguments)]
+fn run_menu<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    assets: &Assets,
+    theme: &Theme,
+    crt: &mut Crt,
+    settings: &Settings,
+    unicode: bool,
+    audio: &mut dyn AudioSink,
+) -> Result<()> {
+    let mut idle_since = Instant::now();
     while app.is_running() {
-        terminal.draw(|frame| ui::render(frame, app))?;
+        if idle_since.elapsed() >= attract::IDLE_TIME {
+            run_attract(terminal, assets, theme, crt, settings, unicode)?;
+            idle_since = Instant::now();
+        }
+
+        terminal.draw(|frame| {
+            ui::render(frame, app, assets, theme);
+            crt.apply(frame.buffer_mut(), theme);
+        })?;
 
         if event::poll(std::time::Duration::from_millis(100))?
             && let Event::Key(key) = event::read()?
             && key.kind == KeyEventKind::Press
         {
+            idle_since = Instant::now();
             match key.code {
                 KeyCode::Char('q') => app.quit(),
-                KeyCode::Up | KeyCode::Char('k') => app.previous(),
-                KeyCode::Down | KeyCode::Char('j') => app.next(),
+                KeyCode::Up | KeyCode::Char('k') => {
+                    app.previous();
+                    audio.play(Sound::MenuMove);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    app.next();
+                    audio.play(Sound::MenuMove);
+                }
                 KeyCode::Enter => app.select(),
                 _ => {}
             }
@@ -92,31 +307,257 @@ fn run_menu<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
     Ok(())
 }
 
-/// Run the game loop
-fn run_game<B: Backend>(terminal: &mut Terminal<B>, game: &mut GameState) -> Result<()> {
+/// Fly a demo game until it ends or any key is pressed
+fn run_attract<B: Backend>(
+    terminal: &mut Terminal<B>,
+    assets: &Assets,
+    theme: &Theme,
+    crt: &mut Crt,
+    settings: &Settings,
+    unicode: bool,
+) -> Result<()> {
+    let mode = settings.render_mode.fallback(unicode);
+    let mut demo = Attract::new(game::time_seed(), settings.difficulty);
+    while !demo.is_finished() {
+        demo.tick();
+        terminal.draw(|frame| {
+            game_ui::render(frame, &demo.game, assets, &settings.hud, theme, mode, None);
+            game_ui::render_demo_banner(frame, theme);
+            crt.apply(frame.buffer_mut(), theme);
+        })?;
+
+        if event::poll(settings.frame_time())?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Run the options screen until the player backs out
+fn run_options<B: Backend>(
+    terminal: &mut Terminal<B>,
+    settings: &mut Settings,
+    assets: &Assets,
+    depth: ColorDepth,
+    audio: &mut dyn AudioSink,
+) -> Result<()> {
+    let mut options = App::options();
+    let mut crt = Crt::new(settings.crt);
+
+    while options.is_running() {
+        // Resolve every frame so changes preview immediately
+        let theme = Theme::new(settings.palette, depth).with_shape_cues(settings.shape_cues);
+        crt.set_enabled(settings.crt);
+        terminal.draw(|frame| {
+            ui::render_options(frame, &options, settings, assets, &theme);
+            crt.apply(frame.buffer_mut(), &theme);
+        })?;
+
+        if event::poll(std::time::Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => options.quit(),
+                KeyCode::Up | KeyCode::Char('k') => {
+                    options.previous();
+                    audio.play(Sound::MenuMove);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    options.next();
+                    audio.play(Sound::MenuMove);
+                }
+                KeyCode::Left | KeyCode::Char('h') => options.adjust(settings, false),
+                KeyCode::Right | KeyCode::Char('l') => options.adjust(settings, true),
+                KeyCode::Enter => options.activate(settings),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How a game loop finished
+enum GameEnd {
+    /// Back to the menu, after game over or quitting from the pause menu
+    Menu(GameState),
+    /// Saved from the pause menu to finish later
+    Saved,
+    /// The player chose to quit the whole game
+    Quit(GameState),
+}
+
+/// Record a finished game's score and, given its recording, save it as the
+/// last replay; returns whether the player asked to quit
+fn finish_game(end: GameEnd, replay: Option<&Replay>) -> Result<bool> {
+    let (game, quit) = match end {
+        GameEnd::Menu(game) => (game, false),
+        // A saved game isn't over yet, so it has no score or replay
+        GameEnd::Saved => return Ok(false),
+        GameEnd::Quit(game) => (game, true),
+    };
+    scores::record(&game)?;
+    if let Some(replay) = replay
+        && let Some(path) = replay::last_path()
+    {
+        replay.save(&path)?;
+    }
+    Ok(quit)
+}
+
+/// Run the game loop from `game`, recording input into `replay`
+///
+/// A resumed game can't be replayed without its start, so it runs with no
+/// recording. Pausing opens the pause menu, whose options screen changes
+/// `settings` for the rest of the game. Restarting begins a fresh recording.
+#[allow(clippy::too_many_arguments)]
+fn run_game<B: Backend>(
+    terminal: &mut Terminal<B>,
+    mut game: GameState,
+    replay: &mut Option<Replay>,
+    assets: &Assets,
+    settings: &mut Settings,
+    depth: ColorDepth,
+    unicode: bool,
+    audio: &mut dyn AudioSink,
+) -> Result<GameEnd> {
+    let mut theme = Theme::new(settings.palette, depth).with_shape_cues(settings.shape_cues);
+    let mut mode = settings.render_mode.fallback(unicode);
+    let mut crt = Crt::new(settings.crt);
+    let mut pause = App::pause();
+    let mut tick = 0;
     while game.is_running() {
         // Update game state
         game.update();
+        game.drain_sounds(audio);
 
         // Render
-        terminal.draw(|frame| game_ui::render(frame, game))?;
+        let menu = (game.paused && !game.is_over()).then_some(&pause);
+        terminal.draw(|frame| {
+            game_ui::render(frame, &game, assets, &settings.hud, &theme, mode, menu);
+            crt.apply(frame.buffer_mut(), &theme);
+        })?;
 
         // Handle input
-        if event::poll(std::time::Duration::from_millis(50))?
+        if event::poll(settings.frame_time())?
             && let Event::Key(key) = event::read()?
             && key.kind == KeyEventKind::Press
         {
-            match key.code {
-                KeyCode::Left | KeyCode::Char('a') => game.move_left(),
-                KeyCode::Right | KeyCode::Char('d') => game.move_right(),
-                KeyCode::Up | KeyCode::Char('w') => game.move_up(),
-                KeyCode::Down | KeyCode::Char('s') => game.move_down(),
-                KeyCode::Char(' ') => game.fire_laser(),
-                KeyCode::Enter => game.toggle_pause(),
-                KeyCode::Char('q') => game.exit_to_menu(),
-                _ => {}
+            let action = if menu.is_none() {
+                controls::action_for(key.code)
+            } else if !pause.is_running() {
+                // Any key leaves the controls page
+                pause.reopen();
+                None
+            } else {
+                match key.code {
+                    KeyCode::Up | KeyCode::Char('k' | 'w') => {
+                        pause.previous();
+                        audio.play(Sound::MenuMove);
+                        None
+                    }
+                    KeyCode::Down | KeyCode::Char('j' | 's') => {
+                        pause.next();
+                        audio.play(Sound::MenuMove);
+                        None
+                    }
+                    KeyCode::Esc => Some(Action::Pause),
+                    KeyCode::Char('q') => Some(Action::Quit),
+                    KeyCode::Enter => {
+                        pause.select();
+                        match pause.selected_action() {
+                            Some(PauseItem::Resume) => Some(Action::Pause),
+                            Some(PauseItem::Restart) => {
+                                let fresh = Replay::new(game::time_seed())
+                                    .with_difficulty(settings.difficulty);
+                                game = fresh.start();
+                                *replay = Some(fresh);
+                                tick = 0;
+                                continue;
+                            }
+                            Some(PauseItem::Options) => {
+                                run_options(terminal, settings, assets, depth, audio)?;
+                                theme = Theme::new(settings.palette, depth)
+                                    .with_shape_cues(settings.shape_cues);
+                                mode = settings.render_mode.fallback(unicode);
+                                crt.set_enabled(settings.crt);
+                                pause.reopen();
+                                None
+                            }
+                            // Shown until the next key
+                            Some(PauseItem::Controls) => None,
+                            Some(PauseItem::SaveAndQuit) => {
+                                if let Some(path) = save::path() {
+                                    save::store(&game, &path)?;
+                                    return Ok(GameEnd::Saved);
+                                }
+                                pause.reopen();
+                                None
+                            }
+                            Some(PauseItem::QuitToMenu) => Some(Action::Quit),
+                            Some(PauseItem::QuitGame) => {
+                                if let Some(replay) = replay {
+                                    replay.finish(tick);
+                                }
+                                return Ok(GameEnd::Quit(game));
+                            }
+                            None => None,
+                        }
+                    }
+                    _ => None,
+                }
+            };
+            if let Some(action) = action {
+                action.apply(&mut game);
+                if let Some(replay) = replay {
+                    replay.record(tick, action);
+                }
+                if game.paused {
+                    pause = App::pause();
+                }
             }
         }
+        tick += 1;
+    }
+    if let Some(replay) = replay {
+        replay.finish(tick);
+    }
+    Ok(GameEnd::Menu(game))
+}
+
+/// Play back a recorded game until it ends or the viewer presses Q
+fn run_replay<B: Backend>(
+    terminal: &mut Terminal<B>,
+    replay: &Replay,
+    assets: &Assets,
+    settings: &Settings,
+    depth: ColorDepth,
+    unicode: bool,
+    audio: &mut dyn AudioSink,
+) -> Result<()> {
+    let theme = Theme::new(settings.palette, depth).with_shape_cues(settings.shape_cues);
+    let mode = settings.render_mode.fallback(unicode);
+    let mut crt = Crt::new(settings.crt);
+    let mut game = replay.start();
+    for tick in 0..replay.ticks {
+        replay.step(&mut game, tick);
+        game.drain_sounds(audio);
+
+        terminal.draw(|frame| {
+            game_ui::render(frame, &game, assets, &settings.hud, &theme, mode, None);
+            crt.apply(frame.buffer_mut(), &theme);
+        })?;
+
+        if event::poll(settings.frame_time())?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        {
+            break;
+        }
     }
     Ok(())
 }
@@ -0,0 +1,168 @@
+//! Options screen state
+
+use super::{app::App, cycle::Cycle, settings::Settings};
+
+/// Represents a row on the options screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionItem {
+    Theme,
+    Renderer,
+    ShapeCues,
+    Crt,
+    Difficulty,
+    Back,
+}
+
+impl OptionItem {
+    /// Get all option rows in display order
+    pub fn all() -> &'static [OptionItem] {
+        &[
+            OptionItem::Theme,
+            OptionItem::Renderer,
+            OptionItem::ShapeCues,
+            OptionItem::Crt,
+            OptionItem::Difficulty,
+            OptionItem::Back,
+        ]
+    }
+
+    /// Get the display text for this row
+    pub fn label(&self) -> &'static str {
+        match self {
+            OptionItem::Theme => "THEME",
+            OptionItem::Renderer => "RENDERER",
+            OptionItem::ShapeCues => "SHAPE CUES",
+            OptionItem::Crt => "CRT EFFECTS",
+            OptionItem::Difficulty => "DIFFICULTY",
+            OptionItem::Back => "BACK",
+        }
+    }
+
+    /// Current value of this row, if it has one
+    pub fn value(&self, settings: &Settings) -> Option<&'static str> {
+        match self {
+            OptionItem::Theme => Some(settings.palette.label()),
+            OptionItem::Renderer => Some(settings.render_mode.label()),
+            OptionItem::ShapeCues => Some(on_off(settings.shape_cues)),
+            OptionItem::Crt => Some(on_off(settings.crt)),
+            OptionItem::Difficulty => Some(settings.difficulty.label()),
+            OptionItem::Back => None,
+        }
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled { "ON" } else { "OFF" }
+}
+
+impl App<OptionItem> {
+    /// Change the selected setting to its next (or previous) value
+    pub fn adjust(&mut self, settings: &mut Settings, forward: bool) {
+        match self.items()[self.selected_index()] {
+            OptionItem::Theme => settings.palette = settings.palette.step(forward),
+            OptionItem::Renderer => settings.render_mode = settings.render_mode.step(forward),
+            OptionItem::ShapeCues => settings.shape_cues = !settings.shape_cues,
+            OptionItem::Crt => settings.crt = !settings.crt,
+            OptionItem::Difficulty => settings.difficulty = settings.difficulty.step(forward),
+            OptionItem::Back => {}
+        }
+    }
+
+    /// Activate the selected row: BACK closes the screen, any other row
+    /// steps its setting forward
+    pub fn activate(&mut self, settings: &mut Settings) {
+        match self.items()[self.selected_index()] {
+            OptionItem::Back => self.quit(),
+            _ => self.adjust(settings, true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::{game::Difficulty, raster::RenderMode, theme::Palette};
+
+    #[test]
+    fn test_navigation_wraps() {
+        let mut options = App::options();
+        options.previous();
+        assert_eq!(options.selected_index(), OptionItem::all().len() - 1);
+        options.next();
+        assert_eq!(options.selected_index(), 0);
+    }
+
+    #[test]
+    fn test_adjust_theme() {
+        let mut options = App::options();
+        let mut settings = Settings::default();
+
+        options.adjust(&mut settings, true);
+        assert_eq!(settings.palette, Palette::Amber);
+        options.adjust(&mut settings, false);
+        options.adjust(&mut settings, false);
+        assert_eq!(settings.palette, Palette::BlueYellowSafe);
+
+        options.activate(&mut settings);
+        assert_eq!(settings.palette, Palette::Phosphor);
+        assert!(options.is_running());
+    }
+
+    #[test]
+    fn test_adjust_renderer() {
+        let mut options = App::options();
+        let mut settings = Settings::default();
+        options.next();
+        assert_eq!(OptionItem::Renderer.value(&settings), Some("ASCII"));
+
+        options.adjust(&mut settings, false);
+        assert_eq!(settings.render_mode, RenderMode::Braille);
+        options.activate(&mut settings);
+        assert_eq!(settings.render_mode, RenderMode::Ascii);
+    }
+
+    #[test]
+    fn test_toggle_switches() {
+        let mut options = App::options();
+        let mut settings = Settings::default();
+        options.next();
+        options.next();
+        assert_eq!(OptionItem::ShapeCues.value(&settings), Some("OFF"));
+
+        options.activate(&mut settings);
+        assert!(settings.shape_cues);
+        assert_eq!(OptionItem::ShapeCues.value(&settings), Some("ON"));
+        options.adjust(&mut settings, false);
+        assert!(!settings.shape_cues);
+
+        options.next();
+        options.activate(&mut settings);
+        assert!(settings.crt);
+        assert_eq!(OptionItem::Crt.value(&settings), Some("ON"));
+    }
+
+    #[test]
+    fn test_adjust_difficulty() {
+        let mut options = App::options();
+        let mut settings = Settings::default();
+        for _ in 0..4 {
+            options.next();
+        }
+        assert_eq!(OptionItem::Difficulty.value(&settings), Some("NORMAL"));
+
+        options.activate(&mut settings);
+        assert_eq!(settings.difficulty, Difficulty::Hard);
+        options.adjust(&mut settings, true);
+        assert_eq!(settings.difficulty, Difficulty::Easy);
+    }
+
+    #[test]
+    fn test_back_closes() {
+        let mut options = App::options();
+        let mut settings = Settings::default();
+        options.previous();
+        options.activate(&mut settings);
+        assert!(!options.is_running());
+        assert_eq!(settings, Settings::default());
+    }
+}
@@ -0,0 +1,195 @@
+//! Particle effects for explosions and hits
+
+use std::collections::VecDeque;
+
+/// Most particles alive at once; the oldest are dropped past this
+pub const PARTICLE_BUDGET: usize = 256;
+
+/// Particle types
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleKind {
+    /// Tumbling wreckage from a destroyed enemy
+    Debris,
+    /// Short-lived bright flecks from impacts
+    Spark,
+    /// Expanding ring around an explosion
+    Shockwave,
+}
+
+/// A single particle in world space, using the same axes as enemies
+#[derive(Clone, Debug)]
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub vz: f32,
+    pub kind: ParticleKind,
+    /// Frames since spawning
+    pub age: u16,
+    /// Frames until the particle disappears
+    pub lifetime: u16,
+}
+
+impl Particle {
+    fn at(x: f32, y: f32, z: f32, kind: ParticleKind, lifetime: u16) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            vx: 0.0,
+            vy: 0.0,
+            vz: 0.0,
+            kind,
+            age: 0,
+            lifetime,
+        }
+    }
+
+    /// How far through its life the particle is (0.0 new, 1.0 gone)
+    pub fn fade(&self) -> f32 {
+        f32::from(self.age) / f32::from(self.lifetime.max(1))
+    }
+}
+
+/// All live particles, capped by [`PARTICLE_BUDGET`]
+#[derive(Clone, Debug, Default)]
+pub struct Particles {
+    particles: VecDeque<Particle>,
+}
+
+impl Particles {
+    /// Create an empty particle system
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Live particles, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Debris, sparks and a shockwave for a destroyed enemy
+    pub fn spawn_explosion(&mut self, x: f32, y: f32, z: f32) {
+        self.push(Particle::at(x, y, z, ParticleKind::Shockwave, 12));
+        self.burst((x, y, z), ParticleKind::Debris, 12, 0.003, 20);
+        self.burst((x, y, z), ParticleKind::Spark, 6, 0.006, 8);
+    }
+
+    /// A few sparks where a laser struck something
+    pub fn spawn_impact(&mut self, x: f32, y: f32, z: f32) {
+        self.burst((x, y, z), ParticleKind::Spark, 4, 0.004, 6);
+    }
+
+    /// Sparks spraying across the view when the ship's shield takes a hit
+    pub fn spawn_shield_hit(&mut self, x: f32, y: f32) {
+        self.burst((x, y, 6.0), ParticleKind::Spark, 10, 0.006, 10);
+    }
+
+    /// Age and move particles, dropping any that have expired
+    pub fn update(&mut self) {
+        for p in &mut self.particles {
+            p.age += 1;
+            p.x += p.vx;
+            p.y += p.vy;
+            p.z += p.vz;
+        }
+        self.particles.retain(|p| p.age < p.lifetime && p.z > 0.0);
+    }
+
+    /// Particles flying outward in evenly spread directions
+    fn burst(
+        &mut self,
+        (x, y, z): (f32, f32, f32),
+        kind: ParticleKind,
+        count: usize,
+        speed: f32,
+        lifetime: u16,
+    ) {
+        // Speeds are in world units per frame; one unit spans the whole view
+        // at z=50, so bursts stay a few cells wide. Golden-angle spacing
+        // gives an even spread without an RNG.
+        const GOLDEN_ANGLE: f32 = 2.399_963;
+        for i in 0..count {
+            let angle = i as f32 * GOLDEN_ANGLE;
+            // Vary speed so the burst has some depth to it
+            let speed = speed * (0.6 + 0.4 * ((i * 7) % 5) as f32 / 4.0);
+            let mut particle = Particle::at(x, y, z, kind, lifetime);
+            particle.vx = angle.cos() * speed;
+            particle.vy = angle.sin() * speed;
+            particle.vz = (angle * 0.5).sin() * speed * 100.0;
+            self.push(particle);
+        }
+    }
+
+    fn push(&mut self, particle: Particle) {
+        if self.particles.len() >= PARTICLE_BUDGET {
+            self.particles.pop_front();
+        }
+        self.particles.push_back(particle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explosion_spawns_every_kind() {
+        let mut particles = Particles::new();
+        particles.spawn_explosion(0.0, 0.0, 50.0);
+
+        let has = |kind| particles.iter().any(|p| p.kind == kind);
+        assert!(has(ParticleKind::Debris));
+        assert!(has(ParticleKind::Spark));
+        assert!(has(ParticleKind::Shockwave));
+    }
+
+    #[test]
+    fn test_particles_expire() {
+        let mut particles = Particles::new();
+        particles.spawn_impact(0.0, 0.0, 50.0);
+        assert!(!particles.is_empty());
+
+        for _ in 0..10 {
+            particles.update();
+        }
+        assert!(particles.is_empty());
+    }
+
+    #[test]
+    fn test_particles_move_and_fade() {
+        let mut particles = Particles::new();
+        particles.spawn_explosion(0.0, 0.0, 50.0);
+        particles.update();
+
+        let debris = particles
+            .iter()
+            .find(|p| p.kind == ParticleKind::Debris)
+            .unwrap();
+        assert!(debris.x != 0.0 || debris.y != 0.0);
+        assert!(debris.fade() > 0.0 && debris.fade() < 1.0);
+    }
+
+    #[test]
+    fn test_budget_drops_oldest() {
+        let mut particles = Particles::new();
+        particles.spawn_explosion(0.5, 0.5, 10.0);
+        for _ in 0..100 {
+            particles.spawn_explosion(0.0, 0.0, 90.0);
+        }
+
+        assert_eq!(particles.len(), PARTICLE_BUDGET);
+        // The first explosion has been pushed out by newer ones
+        assert!(particles.iter().all(|p| p.z != 10.0));
+    }
+}
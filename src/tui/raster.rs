@@ -0,0 +1,317 @@
+//! Sub-cell rasterizing with Unicode half-blocks and braille
+//!
+//! A [`DotGrid`] splits every cell of the view into sub-pixels (1x2 for
+//! half-blocks, 2x4 for braille), collects dots into it, and then writes
+//! each cell's dots as a single glyph through the depth-tested [`Canvas`].
+
+use std::env;
+
+use ratatui::prelude::*;
+
+use super::{cycle::Cycle, sprite::Canvas};
+
+/// How lines and distant sprites are drawn in the game view
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// One ASCII character per cell
+    #[default]
+    Ascii,
+    /// Upper and lower half-block characters, two dots per cell
+    HalfBlock,
+    /// Braille patterns, eight dots per cell
+    Braille,
+}
+
+impl Cycle for RenderMode {
+    const ALL: &'static [Self] = &[
+        RenderMode::Ascii,
+        RenderMode::HalfBlock,
+        RenderMode::Braille,
+    ];
+}
+
+impl RenderMode {
+    /// Get the display text for this mode
+    pub fn label(&self) -> &'static str {
+        match self {
+            RenderMode::Ascii => "ASCII",
+            RenderMode::HalfBlock => "HALF BLOCK",
+            RenderMode::Braille => "BRAILLE",
+        }
+    }
+
+    /// Parse a mode name such as `ascii` or `half-block`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase().replace(['_', ' '], "-");
+        match name.as_str() {
+            "ascii" => Some(RenderMode::Ascii),
+            "half-block" | "halfblock" => Some(RenderMode::HalfBlock),
+            "braille" => Some(RenderMode::Braille),
+            _ => None,
+        }
+    }
+
+    /// Sub-pixels per cell, across and down
+    pub fn resolution(&self) -> (u16, u16) {
+        match self {
+            RenderMode::Ascii => (1, 1),
+            RenderMode::HalfBlock => (1, 2),
+            RenderMode::Braille => (2, 4),
+        }
+    }
+
+    /// The mode to actually draw with, dropping to ASCII when the terminal
+    /// can't show Unicode
+    pub fn fallback(self, unicode: bool) -> Self {
+        if unicode { self } else { RenderMode::Ascii }
+    }
+}
+
+/// Guess whether the terminal can show Unicode from the locale variables
+pub fn unicode_supported() -> bool {
+    let lc_all = env::var("LC_ALL").ok();
+    let lc_ctype = env::var("LC_CTYPE").ok();
+    let lang = env::var("LANG").ok();
+    locale_is_utf8(lc_all.as_deref(), lc_ctype.as_deref(), lang.as_deref())
+}
+
+/// Whether the effective locale uses UTF-8, using the first of `LC_ALL`,
+/// `LC_CTYPE` and `LANG` that is set
+pub fn locale_is_utf8(lc_all: Option<&str>, lc_ctype: Option<&str>, lang: Option<&str>) -> bool {
+    [lc_all, lc_ctype, lang]
+        .into_iter()
+        .flatten()
+        .find(|value| !value.is_empty())
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+/// Dots collected for one cell
+#[derive(Clone, Copy, Debug)]
+struct DotCell {
+    bits: u8,
+    /// Depth and style of the nearest dot
+    z: f32,
+    style: Style,
+}
+
+impl Default for DotCell {
+    fn default() -> Self {
+        Self {
+            bits: 0,
+            z: f32::INFINITY,
+            style: Style::default(),
+        }
+    }
+}
+
+/// A sub-pixel grid over a canvas area
+///
+/// Cells are only allocated once the first dot is lit, so a layer with
+/// nothing to draw in dots, and every layer in ASCII mode, costs nothing.
+pub struct DotGrid {
+    mode: RenderMode,
+    width: u16,
+    height: u16,
+    cells: Vec<DotCell>,
+}
+
+impl DotGrid {
+    /// Create an empty grid covering `area` at the mode's resolution
+    pub fn new(area: Rect, mode: RenderMode) -> Self {
+        Self {
+            mode,
+            width: area.width,
+            height: area.height,
+            cells: Vec::new(),
+        }
+    }
+
+    /// Sub-pixels per cell, across and down
+    pub fn resolution(&self) -> (f32, f32) {
+        let (x, y) = self.mode.resolution();
+        (f32::from(x), f32::from(y))
+    }
+
+    /// Size of the grid in sub-pixels
+    pub fn size(&self) -> (i32, i32) {
+        let (rx, ry) = self.mode.resolution();
+        (i32::from(self.width * rx), i32::from(self.height * ry))
+    }
+
+    /// Light one sub-pixel; the cell takes the style of its nearest dot
+    pub fn set(&mut self, x: i32, y: i32, z: f32, style: Style) {
+        let (width, height) = self.size();
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return;
+        }
+        if self.cells.is_empty() {
+            self.cells =
+                vec![DotCell::default(); usize::from(self.width) * usize::from(self.height)];
+        }
+        let (rx, ry) = self.mode.resolution();
+        let (rx, ry) = (i32::from(rx), i32::from(ry));
+        let cell = &mut self.cells[((y / ry) * i32::from(self.width) + x / rx) as usize];
+        cell.bits |= dot_bit(self.mode, x % rx, y % ry);
+        if z <= cell.z {
+            cell.z = z;
+            cell.style = style;
+        }
+    }
+
+    /// Light a round blob of sub-pixels around a point
+    pub fn blob(&mut self, x: f32, y: f32, radius: f32, z: f32, style: Style) {
+        let reach = radius.ceil() as i32;
+        let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                if ((dx * dx + dy * dy) as f32) <= radius * radius + 0.25 {
+                    self.set(cx + dx, cy + dy, z, style);
+                }
+            }
+        }
+    }
+
+    /// Write every lit cell into the canvas as one glyph
+    pub fn flush(self, canvas: &mut Canvas) {
+        let width = i32::from(self.width);
+        for (i, cell) in self.cells.iter().enumerate() {
+            if cell.bits != 0 {
+                let (x, y) = (i as i32 % width, i as i32 / width);
+                canvas.plot(x, y, cell.z, glyph(self.mode, cell.bits), cell.style);
+            }
+        }
+    }
+}
+
+/// Bit for a sub-pixel within its cell
+fn dot_bit(mode: RenderMode, x: i32, y: i32) -> u8 {
+    match mode {
+        RenderMode::Ascii => 1,
+        RenderMode::HalfBlock => 1 << y,
+        // Braille numbers dots down the left column, then the right, with
+        // the bottom row added last
+        RenderMode::Braille => match (x, y) {
+            (0, 3) => 0x40,
+            (1, 3) => 0x80,
+            (0, y) => 1 << y,
+            (_, y) => 1 << (y + 3),
+        },
+    }
+}
+
+/// Character showing a cell's dots
+fn glyph(mode: RenderMode, bits: u8) -> char {
+    match mode {
+        RenderMode::Ascii => '.',
+        RenderMode::HalfBlock => match bits {
+            0b01 => '▀',
+            0b10 => '▄',
+            _ => '█',
+        },
+        RenderMode::Braille => char::from_u32(0x2800 + u32::from(bits)).unwrap_or('.'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flushed(grid: DotGrid, width: u16, height: u16) -> Buffer {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        grid.flush(&mut Canvas::new(&mut buf, area));
+        buf
+    }
+
+    #[test]
+    fn test_cells_allocated_on_first_dot() {
+        let area = Rect::new(0, 0, 80, 24);
+        let ascii = DotGrid::new(area, RenderMode::Ascii);
+        assert_eq!(ascii.cells.capacity(), 0);
+        assert!(
+            flushed(ascii, 80, 24)
+                .content
+                .iter()
+                .all(|c| c.symbol() == " ")
+        );
+
+        let mut braille = DotGrid::new(area, RenderMode::Braille);
+        assert_eq!(braille.cells.capacity(), 0);
+        // Off-grid dots draw nothing, so they don't allocate either
+        braille.set(-1, 0, 1.0, Style::default());
+        assert_eq!(braille.cells.capacity(), 0);
+        braille.set(0, 0, 1.0, Style::default());
+        assert_eq!(braille.cells.len(), 80 * 24);
+    }
+
+    #[test]
+    fn test_braille_dots() {
+        let mut grid = DotGrid::new(Rect::new(0, 0, 2, 1), RenderMode::Braille);
+        assert_eq!(grid.size(), (4, 4));
+        grid.set(0, 0, 1.0, Style::default());
+        grid.set(1, 3, 1.0, Style::default());
+        grid.set(3, 1, 1.0, Style::default());
+
+        let buf = flushed(grid, 2, 1);
+        // Dots 1 and 8, then dot 5
+        assert_eq!(buf[(0, 0)].symbol(), "⢁");
+        assert_eq!(buf[(1, 0)].symbol(), "⠐");
+    }
+
+    #[test]
+    fn test_half_blocks() {
+        let mut grid = DotGrid::new(Rect::new(0, 0, 3, 1), RenderMode::HalfBlock);
+        grid.set(0, 0, 1.0, Style::default());
+        grid.set(1, 1, 1.0, Style::default());
+        grid.set(2, 0, 1.0, Style::default());
+        grid.set(2, 1, 1.0, Style::default());
+
+        let buf = flushed(grid, 3, 1);
+        assert_eq!(buf[(0, 0)].symbol(), "▀");
+        assert_eq!(buf[(1, 0)].symbol(), "▄");
+        assert_eq!(buf[(2, 0)].symbol(), "█");
+    }
+
+    #[test]
+    fn test_nearest_dot_sets_style_and_depth() {
+        let near = Style::default().fg(Color::Red);
+        let far = Style::default().fg(Color::Green);
+        let mut grid = DotGrid::new(Rect::new(0, 0, 1, 1), RenderMode::Braille);
+        grid.set(0, 0, 5.0, near);
+        grid.set(1, 1, 50.0, far);
+
+        let area = Rect::new(0, 0, 1, 1);
+        let mut buf = Buffer::empty(area);
+        let mut canvas = Canvas::new(&mut buf, area);
+        grid.flush(&mut canvas);
+        assert_eq!(canvas.depth_at(0, 0), Some(5.0));
+        assert_eq!(buf[(0, 0)].fg, Color::Red);
+    }
+
+    #[test]
+    fn test_blob_grows_with_radius() {
+        let lit = |radius| {
+            let mut grid = DotGrid::new(Rect::new(0, 0, 4, 2), RenderMode::Braille);
+            grid.blob(4.0, 4.0, radius, 1.0, Style::default());
+            grid.cells.iter().map(|c| c.bits.count_ones()).sum::<u32>()
+        };
+        assert_eq!(lit(0.0), 1);
+        assert!(lit(1.0) > 1);
+        assert!(lit(2.0) > lit(1.0));
+    }
+
+    #[test]
+    fn test_unicode_detection_and_fallback() {
+        assert!(locale_is_utf8(None, None, Some("en_US.UTF-8")));
+        assert!(locale_is_utf8(Some("C.utf8"), None, Some("C")));
+        // LC_ALL wins over LANG
+        assert!(!locale_is_utf8(Some("C"), None, Some("en_US.UTF-8")));
+        assert!(!locale_is_utf8(None, None, None));
+
+        assert_eq!(RenderMode::Braille.fallback(true), RenderMode::Braille);
+        assert_eq!(RenderMode::Braille.fallback(false), RenderMode::Ascii);
+    }
+}
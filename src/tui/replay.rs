@@ -0,0 +1,374 @@
+//! Input recording and replay
+//!
+//! A game is fully determined by its seed and the player's input on each
+//! tick of the game loop, so a replay stores just those. Playing it back
+//! runs the same simulation and ends in the same state.
+//!
+//! Replay files are text: a header line
+//! `spacey-replay 2 <seed> <ticks> <difficulty>` followed by one token per
+//! input, the ticks since the previous input and
+//! a letter for the action (`12L 0F` is "left after 12 ticks, then fire on
+//! the same tick"). Version 1 files have no difficulty and play at normal.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, eyre},
+};
+
+use super::{
+    audio::NullSink,
+    game::{Difficulty, GameState},
+    scores,
+};
+
+/// Header word identifying a replay file
+const MAGIC: &str = "spacey-replay";
+/// Format version written in the header
+const VERSION: u32 = 2;
+/// Input tokens per line of a replay file
+const TOKENS_PER_LINE: usize = 16;
+
+/// Something the player did on one tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    Fire,
+    Missile,
+    /// Pause or resume, or leave once the game is over
+    Pause,
+    /// Leave for the menu while paused
+    Quit,
+}
+
+impl Action {
+    /// Get all actions
+    pub fn all() -> &'static [Action] {
+        &[
+            Action::Left,
+            Action::Right,
+            Action::Up,
+            Action::Down,
+            Action::Fire,
+            Action::Missile,
+            Action::Pause,
+            Action::Quit,
+        ]
+    }
+
+    /// Get the display text for this action
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Left => "STEER LEFT",
+            Action::Right => "STEER RIGHT",
+            Action::Up => "CLIMB",
+            Action::Down => "DIVE",
+            Action::Fire => "FIRE LASERS",
+            Action::Missile => "LAUNCH MISSILE",
+            Action::Pause => "PAUSE",
+            Action::Quit => "QUIT TO MENU",
+        }
+    }
+
+    /// Letter for the action in a replay file
+    fn code(&self) -> char {
+        match self {
+            Action::Left => 'L',
+            Action::Right => 'R',
+            Action::Up => 'U',
+            Action::Down => 'D',
+            Action::Fire => 'F',
+            Action::Missile => 'M',
+            Action::Pause => 'P',
+            Action::Quit => 'Q',
+        }
+    }
+
+    fn from_code(code: char) -> Option<Self> {
+        Action::all().iter().copied().find(|a| a.code() == code)
+    }
+
+    /// Carry out the action
+    pub fn apply(&self, game: &mut GameState) {
+        match self {
+            Action::Left => game.move_left(),
+            Action::Right => game.move_right(),
+            Action::Up => game.move_up(),
+            Action::Down => game.move_down(),
+            Action::Fire => game.fire_laser(),
+            Action::Missile => game.fire_missile(),
+            Action::Pause if game.is_over() => game.exit_to_menu(),
+            Action::Pause => game.toggle_pause(),
+            Action::Quit => game.exit_to_menu(),
+        }
+    }
+}
+
+/// A game's seed and every input, by tick
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    /// Inputs in order with the tick they happened on
+    inputs: Vec<(u32, Action)>,
+    /// Ticks the game ran for
+    pub ticks: u32,
+}
+
+impl Replay {
+    /// Start recording a game played from `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Self::default()
+        }
+    }
+
+    /// Record a game played at `difficulty`
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// The game as it stood before the first tick
+    pub fn start(&self) -> GameState {
+        GameState::with_seed(self.seed).with_difficulty(self.difficulty)
+    }
+
+    /// Note `action` on `tick`; ticks must not go backwards
+    pub fn record(&mut self, tick: u32, action: Action) {
+        self.inputs.push((tick, action));
+        self.ticks = self.ticks.max(tick + 1);
+    }
+
+    /// Note that the game ran for `ticks` ticks
+    pub fn finish(&mut self, ticks: u32) {
+        self.ticks = self.ticks.max(ticks);
+    }
+
+    /// Inputs on `tick`, in the order they happened
+    pub fn actions_at(&self, tick: u32) -> impl Iterator<Item = Action> + '_ {
+        let first = self.inputs.partition_point(|&(t, _)| t < tick);
+        self.inputs[first..]
+            .iter()
+            .take_while(move |&&(t, _)| t == tick)
+            .map(|&(_, action)| action)
+    }
+
+    /// Advance `game` by tick `tick`: the same step the game loop takes
+    pub fn step(&self, game: &mut GameState, tick: u32) {
+        game.update();
+        for action in self.actions_at(tick) {
+            action.apply(game);
+        }
+    }
+
+    /// Run the whole recording without drawing anything
+    pub fn play(&self) -> GameState {
+        let mut game = self.start();
+        for tick in 0..self.ticks {
+            self.step(&mut game, tick);
+            // Played without sound, so don't let the queue pile up
+            game.drain_sounds(&mut NullSink::default());
+        }
+        game
+    }
+
+    /// Write in the replay file format
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{MAGIC} {VERSION} {} {} {}\n",
+            self.seed,
+            self.ticks,
+            self.difficulty.label().to_ascii_lowercase()
+        );
+        let mut last = 0;
+        for line in self.inputs.chunks(TOKENS_PER_LINE) {
+            let tokens: Vec<String> = line
+                .iter()
+                .map(|&(tick, action)| {
+                    let token = format!("{}{}", tick - last, action.code());
+                    last = tick;
+                    token
+                })
+                .collect();
+            text += &tokens.join(" ");
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Read the replay file format
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut words = source.split_whitespace();
+        if words.next() != Some(MAGIC) {
+            bail!("not a replay file");
+        }
+        let mut number = |what: &str| -> Result<u64> {
+            let word = words.next().ok_or_else(|| eyre!("missing {what}"))?;
+            word.parse()
+                .wrap_err_with(|| format!("invalid {what} `{word}`"))
+        };
+        let version = number("version")?;
+        if version == 0 || version > VERSION as u64 {
+            bail!("unsupported replay version {version}");
+        }
+        let seed = number("seed")?;
+        let ticks = number("tick count")?.try_into()?;
+        let difficulty = if version >= 2 {
+            let name = words.next().ok_or_else(|| eyre!("missing difficulty"))?;
+            Difficulty::from_name(name).ok_or_else(|| eyre!("unknown difficulty `{name}`"))?
+        } else {
+            Difficulty::Normal
+        };
+
+        let mut replay = Self::new(seed).with_difficulty(difficulty);
+        let mut tick: u32 = 0;
+        for token in words {
+            let code = token.chars().last().unwrap_or_default();
+            let action = Action::from_code(code).ok_or_else(|| eyre!("unknown input `{token}`"))?;
+            let delta: u32 = token[..token.len() - code.len_utf8()]
+                .parse()
+                .wrap_err_with(|| format!("invalid input `{token}`"))?;
+            tick = tick
+                .checked_add(delta)
+                .ok_or_else(|| eyre!("input `{token}` is out of range"))?;
+            replay.record(tick, action);
+        }
+        if replay.ticks > ticks {
+            bail!("input after the last tick");
+        }
+        replay.finish(ticks);
+        Ok(replay)
+    }
+
+    /// Read a replay file
+    pub fn load(path: &Path) -> Result<Self> {
+        let source =
+            fs::read_to_string(path).wrap_err_with(|| format!("reading {}", path.display()))?;
+        Self::parse(&source).wrap_err_with(|| format!("in {}", path.display()))
+    }
+
+    /// Write a replay file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+        }
+        fs::write(path, self.to_text()).wrap_err_with(|| format!("writing {}", path.display()))
+    }
+}
+
+/// File the most recent game is recorded to
+pub fn last_path() -> Option<PathBuf> {
+    Some(scores::data_dir()?.join("last.replay"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Play `seed` live, holding fire and weaving, recording as the game
+    /// loop does
+    fn record_game(seed: u64, ticks: u32) -> (Replay, GameState) {
+        let mut replay = Replay::new(seed);
+        let mut game = replay.start();
+        for tick in 0..ticks {
+            game.update();
+            let action = match tick % 7 {
+                0 | 3 => Some(Action::Fire),
+                1 if tick % 60 < 30 => Some(Action::Left),
+                1 => Some(Action::Right),
+                5 if tick % 40 < 20 => Some(Action::Up),
+                5 => Some(Action::Down),
+                6 if tick % 50 == 6 => Some(Action::Missile),
+                _ => None,
+            };
+            if let Some(action) = action {
+                action.apply(&mut game);
+                replay.record(tick, action);
+            }
+        }
+        replay.finish(ticks);
+        (replay, game)
+    }
+
+    #[test]
+    fn test_replay_reaches_the_same_end() {
+        let (replay, live) = record_game(42, 900);
+        assert!(live.kills > 0 && live.shield < 10);
+
+        let replayed = replay.play();
+        assert_eq!(replayed.score, live.score);
+        assert_eq!(replayed.shield, live.shield);
+        assert_eq!(replayed.frame, live.frame);
+        assert_eq!(replayed.kills, live.kills);
+    }
+
+    #[test]
+    fn test_pause_and_quit_replay() {
+        let mut replay = Replay::new(7);
+        replay.record(10, Action::Pause);
+        replay.record(30, Action::Quit);
+        replay.finish(31);
+
+        let game = replay.play();
+        // Paused ticks don't advance the game
+        assert_eq!(game.frame, 11);
+        assert!(!game.is_running());
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let (replay, _) = record_game(99, 300);
+        let text = replay.to_text();
+        assert!(text.starts_with("spacey-replay 2 99 300 normal\n"));
+        assert_eq!(Replay::parse(&text).unwrap(), replay);
+
+        let mut same_tick = Replay::new(1);
+        same_tick.record(4, Action::Left);
+        same_tick.record(4, Action::Fire);
+        assert_eq!(same_tick.to_text(), "spacey-replay 2 1 5 normal\n4L 0F\n");
+        assert_eq!(
+            same_tick.actions_at(4).collect::<Vec<_>>(),
+            [Action::Left, Action::Fire]
+        );
+    }
+
+    #[test]
+    fn test_difficulty_is_recorded() {
+        let mut replay = Replay::new(5).with_difficulty(Difficulty::Hard);
+        replay.finish(10);
+        let text = replay.to_text();
+        assert_eq!(text, "spacey-replay 2 5 10 hard\n");
+        assert_eq!(Replay::parse(&text).unwrap(), replay);
+        assert_eq!(replay.start().shield, Difficulty::Hard.shield());
+
+        // Older files predate difficulty
+        let old = Replay::parse("spacey-replay 1 5 10\n3L").unwrap();
+        assert_eq!(old.difficulty, Difficulty::Normal);
+        assert_eq!(old.actions_at(3).collect::<Vec<_>>(), [Action::Left]);
+    }
+
+    #[test]
+    fn test_bad_files_are_rejected() {
+        for text in [
+            "",
+            "spacey-replay 3 1 10 normal",
+            "spacey-replay 2 1 10",
+            "spacey-replay 2 1 10 insane",
+            "spacey-replay 1 1",
+            "spacey-replay 1 1 10\n3X",
+            "spacey-replay 1 1 10\nF",
+            "spacey-replay 1 1 10\n20F",
+        ] {
+            assert!(Replay::parse(text).is_err(), "{text:?}");
+        }
+    }
+}
@@ -0,0 +1,617 @@
+//! Saved games
+//!
+//! Saving from the pause screen writes everything the simulation needs to
+//! carry on exactly where it stopped: the ship, shots, enemies, scenery,
+//! score and the spawn RNG. Particles, stars and queued sounds are only for
+//! show and start afresh on restore.
+//!
+//! Save files are text: a header line `spacey-save 2` followed by one
+//! record per line, a keyword and its fields. Numbers are written so they
+//! read back bit for bit, and `-` stands for a missing optional value.
+//! Version 1 saves have no `difficulty` record and play on at normal.
+
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::{FromStr, SplitWhitespace},
+};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, eyre},
+};
+
+use super::{
+    ai::Maneuver,
+    enemy::{Enemy, EnemyType, Role},
+    environment::{AsteroidShape, CanyonShape, Environment, TrenchShape},
+    formation::{self, Formation},
+    game::{Difficulty, GameState, HitCause, Laser, WaveBonus, WaveTally},
+    scores,
+    targeting::Missile,
+};
+
+/// Header word identifying a save file
+const MAGIC: &str = "spacey-save";
+/// Format version written in the header
+const VERSION: u32 = 2;
+/// Records every save file must have
+const REQUIRED: &[&str] = &[
+    "seed", "frame", "ship", "fired", "score", "shield", "wave", "scenery",
+];
+
+/// Write `game` in the save file format
+pub fn to_text(game: &GameState) -> String {
+    let mut lines = vec![
+        format!("{MAGIC} {VERSION}"),
+        format!("seed {} {}", game.seed, game.next_enemy_id),
+        format!("frame {} {}", game.frame, u8::from(game.paused)),
+        format!(
+            "difficulty {}",
+            game.difficulty.label().to_ascii_lowercase()
+        ),
+        format!("ship {} {}", game.ship_x, game.ship_y),
+        format!("fired {} {}", game.last_fire_frame, game.last_missile_frame),
+        format!(
+            "score {} {} {} {} {} {}",
+            game.score, game.kills, game.shots_fired, game.shots_hit, game.combo, game.best_combo
+        ),
+        format!("shield {} {}", game.shield, game.hit_frames),
+        format!("wave {} {}", game.waves, game.distance),
+        format!("scenery {}", environment_fields(&game.environment)),
+        format!("level {} {}", game.level, game.scenery_start),
+        format!(
+            "lock {} {} {}",
+            optional(game.lock.tracking.map(|(id, _)| id)),
+            optional(game.lock.tracking.map(|(_, frames)| frames)),
+            optional(game.lock.locked)
+        ),
+    ];
+    if !game.wave_kills.is_empty() {
+        let kills: Vec<String> = game.wave_kills.iter().map(u32::to_string).collect();
+        lines.push(format!("wave-kills {}", kills.join(" ")));
+    }
+    if !game.hits.is_empty() {
+        let causes: Vec<&str> = game.hits.iter().map(HitCause::label).collect();
+        lines.push(format!("hits {}", causes.join(" ")));
+    }
+    if let Some(bonus) = game.last_bonus {
+        lines.push(format!(
+            "bonus {} {} {} {}",
+            bonus.wave, bonus.no_damage, bonus.accuracy, bonus.frame
+        ));
+    }
+    for laser in &game.lasers {
+        lines.push(format!("laser {} {} {}", laser.x, laser.y, laser.z));
+    }
+    for missile in &game.missiles {
+        lines.push(format!(
+            "missile {} {} {} {}",
+            missile.x, missile.y, missile.z, missile.target
+        ));
+    }
+    for enemy in &game.enemies {
+        let role = match enemy.role {
+            Role::Leader => "leader".to_string(),
+            Role::Wingman {
+                leader,
+                slot,
+                formation,
+            } => format!("wingman {leader} {slot} {}", formation.label()),
+        };
+        lines.push(format!(
+            "enemy {} {} {} {} {} {} {} {} {role}",
+            enemy.id,
+            enemy.kind.label(),
+            enemy.x,
+            enemy.y,
+            enemy.z,
+            enemy.age,
+            enemy.closing,
+            pattern_name(enemy.pattern)
+        ));
+    }
+    for tally in &game.tallies {
+        lines.push(format!(
+            "tally {} {} {} {} {} {}",
+            tally.wave,
+            tally.first_id,
+            tally.size,
+            tally.shots_fired,
+            tally.shots_hit,
+            u8::from(tally.damaged)
+        ));
+    }
+    lines.iter().map(|line| line.clone() + "\n").collect()
+}
+
+/// Read the save file format back into a game
+pub fn parse(source: &str) -> Result<GameState> {
+    let mut lines = source.lines().enumerate();
+    let header = lines.next().map(|(_, line)| line).unwrap_or_default();
+    let mut words = header.split_whitespace();
+    if words.next() != Some(MAGIC) {
+        bail!("not a save file");
+    }
+    let version: u32 = words
+        .next()
+        .and_then(|word| word.parse().ok())
+        .ok_or_else(|| eyre!("missing save version"))?;
+    if version == 0 || version > VERSION {
+        bail!("unsupported save version {version}");
+    }
+
+    let mut game = GameState::with_seed(0);
+    game.enemies.clear();
+    game.tallies.clear();
+    let mut seen = Vec::new();
+    for (number, line) in lines {
+        let mut words = line.split_whitespace();
+        let Some(key) = words.next() else {
+            continue;
+        };
+        let mut fields = Fields { words };
+        read_record(&mut game, key, &mut fields)
+            .and_then(|_| fields.finish())
+            .wrap_err_with(|| format!("line {}: `{line}`", number + 1))?;
+        seen.push(key);
+    }
+    if let Some(missing) = REQUIRED.iter().find(|key| !seen.contains(key)) {
+        bail!("missing `{missing}` record");
+    }
+    check(&game)?;
+    Ok(game)
+}
+
+/// Reject values that parse but that no game could have reached
+fn check(game: &GameState) -> Result<()> {
+    if game.shield > game.max_shield() {
+        bail!(
+            "shield {} is above the {} a {} game starts with",
+            game.shield,
+            game.max_shield(),
+            game.difficulty.label()
+        );
+    }
+    for tally in &game.tallies {
+        if tally.wave == 0 {
+            bail!("tally for wave 0");
+        }
+        if tally.shots_fired > game.shots_fired || tally.shots_hit > game.shots_hit {
+            bail!(
+                "tally for wave {} counts more shots than the game",
+                tally.wave
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Fill in the part of `game` one record describes
+fn read_record(game: &mut GameState, key: &str, fields: &mut Fields) -> Result<()> {
+    match key {
+        "seed" => {
+            game.seed = fields.next("seed")?;
+            game.next_enemy_id = fields.next("next enemy id")?;
+        }
+        "frame" => {
+            game.frame = fields.next("frame")?;
+            game.paused = fields.flag("paused")?;
+        }
+        "difficulty" => {
+            let name = fields.word("difficulty")?;
+            game.difficulty =
+                Difficulty::from_name(name).ok_or_else(|| eyre!("unknown difficulty `{name}`"))?;
+        }
+        "ship" => {
+            game.ship_x = fields.next("ship x")?;
+            game.ship_y = fields.next("ship y")?;
+        }
+        "fired" => {
+            game.last_fire_frame = fields.next("last laser frame")?;
+            game.last_missile_frame = fields.next("last missile frame")?;
+        }
+        "score" => {
+            game.score = fields.next("score")?;
+            game.kills = fields.next("kills")?;
+            game.shots_fired = fields.next("shots fired")?;
+            game.shots_hit = fields.next("shots hit")?;
+            game.combo = fields.next("combo")?;
+            game.best_combo = fields.next("best combo")?;
+        }
+        "shield" => {
+            game.shield = fields.next("shield")?;
+            game.hit_frames = fields.next("hit frames")?;
+        }
+        "wave" => {
+            game.waves = fields.next("wave")?;
+            game.distance = fields.next("distance")?;
+        }
+        "scenery" => game.environment = read_environment(fields)?,
+        "level" => {
+            game.level = fields.next("level")?;
+            game.scenery_start = fields.next("scenery start")?;
+        }
+        "wave-kills" => {
+            while let Some(kills) = fields.maybe_next("wave kills")? {
+                game.wave_kills.push(kills);
+            }
+        }
+        "hits" => {
+            for name in fields.words.by_ref() {
+                let cause =
+                    HitCause::from_name(name).ok_or_else(|| eyre!("unknown hit cause `{name}`"))?;
+                game.hits.push(cause);
+            }
+        }
+        "lock" => {
+            let id = fields.optional("tracked enemy")?;
+            let frames = fields.optional("tracked frames")?;
+            game.lock.tracking = id.zip(frames);
+            game.lock.locked = fields.optional("locked enemy")?;
+        }
+        "bonus" => {
+            game.last_bonus = Some(WaveBonus {
+                wave: fields.next("bonus wave")?,
+                no_damage: fields.next("no damage bonus")?,
+                accuracy: fields.next("accuracy bonus")?,
+                frame: fields.next("bonus frame")?,
+            });
+        }
+        "laser" => game.lasers.push(Laser {
+            x: fields.next("laser x")?,
+            y: fields.next("laser y")?,
+            z: fields.next("laser z")?,
+        }),
+        "missile" => game.missiles.push(Missile {
+            x: fields.next("missile x")?,
+            y: fields.next("missile y")?,
+            z: fields.next("missile z")?,
+            target: fields.next("missile target")?,
+        }),
+        "enemy" => game.enemies.push(read_enemy(fields)?),
+        "tally" => game.tallies.push(WaveTally {
+            wave: fields.next("tally wave")?,
+            first_id: fields.next("tally first id")?,
+            size: fields.next("tally size")?,
+            shots_fired: fields.next("tally shots fired")?,
+            shots_hit: fields.next("tally shots hit")?,
+            damaged: fields.flag("tally damaged")?,
+        }),
+        _ => return Err(eyre!("unknown record `{key}`")),
+    }
+    Ok(())
+}
+
+fn read_enemy(fields: &mut Fields) -> Result<Enemy> {
+    let id = fields.next("enemy id")?;
+    let kind = fields.word("enemy type")?;
+    let kind = EnemyType::from_name(kind).ok_or_else(|| eyre!("unknown enemy type `{kind}`"))?;
+    let mut enemy = Enemy::new(0);
+    enemy.id = id;
+    enemy.kind = kind;
+    enemy.x = fields.next("enemy x")?;
+    enemy.y = fields.next("enemy y")?;
+    enemy.z = fields.next("enemy z")?;
+    enemy.age = fields.next("enemy age")?;
+    enemy.closing = fields.next("enemy closing")?;
+    enemy.pattern = read_pattern(fields.word("pattern")?)?;
+    enemy.role = match fields.word("role")? {
+        "leader" => Role::Leader,
+        "wingman" => {
+            let leader = fields.next("leader")?;
+            let slot = fields.next("slot")?;
+            let name = fields.word("formation")?;
+            let formation =
+                Formation::from_name(name).ok_or_else(|| eyre!("unknown formation `{name}`"))?;
+            Role::Wingman {
+                leader,
+                slot,
+                formation,
+            }
+        }
+        role => return Err(eyre!("unknown role `{role}`")),
+    };
+    Ok(enemy)
+}
+
+/// Pattern as comma-separated maneuver names, `-` for none
+fn pattern_name(pattern: &[Maneuver]) -> String {
+    if pattern.is_empty() {
+        return "-".to_string();
+    }
+    let names: Vec<&str> = pattern.iter().map(|m| m.name().unwrap_or("?")).collect();
+    names.join(",")
+}
+
+/// Find the spawn pattern written as `name`
+fn read_pattern(name: &str) -> Result<&'static [Maneuver]> {
+    formation::patterns()
+        .find(|&pattern| pattern_name(pattern) == name)
+        .ok_or_else(|| eyre!("unknown pattern `{name}`"))
+}
+
+fn environment_fields(environment: &Environment) -> String {
+    match environment {
+        Environment::Trench(shape) => format!(
+            "trench {} {} {} {} {}",
+            shape.width,
+            shape.grid_spacing,
+            shape.pillar_spacing,
+            optional(shape.beam_spacing),
+            optional(shape.debris_spacing)
+        ),
+        Environment::OpenSpace => "open-space".to_string(),
+        Environment::AsteroidField(shape) => {
+            format!(
+                "asteroids {} {} {}",
+                shape.spacing, shape.size, shape.spread
+            )
+        }
+        Environment::Canyon(shape) => format!(
+            "canyon {} {} {} {}",
+            shape.width,
+            shape.curvature,
+            shape.spacing,
+            optional(shape.debris_spacing)
+        ),
+    }
+}
+
+fn read_environment(fields: &mut Fields) -> Result<Environment> {
+    let environment = match fields.word("scenery")? {
+        "trench" => Environment::Trench(TrenchShape {
+            width: fields.next("trench width")?,
+            grid_spacing: fields.next("grid spacing")?,
+            pillar_spacing: fields.next("pillar spacing")?,
+            beam_spacing: fields.optional("beam spacing")?,
+            debris_spacing: fields.optional("debris spacing")?,
+        }),
+        "open-space" => Environment::OpenSpace,
+        "asteroids" => Environment::AsteroidField(AsteroidShape {
+            spacing: fields.next("asteroid spacing")?,
+            size: fields.next("asteroid size")?,
+            spread: fields.next("asteroid spread")?,
+        }),
+        "canyon" => Environment::Canyon(CanyonShape {
+            width: fields.next("canyon width")?,
+            curvature: fields.next("canyon curvature")?,
+            spacing: fields.next("canyon spacing")?,
+            debris_spacing: fields.optional("debris spacing")?,
+        }),
+        other => return Err(eyre!("unknown scenery `{other}`")),
+    };
+    Ok(environment)
+}
+
+fn optional<T: Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| v.to_string())
+}
+
+/// The fields of one record, read in order
+struct Fields<'a> {
+    words: SplitWhitespace<'a>,
+}
+
+impl<'a> Fields<'a> {
+    fn word(&mut self, what: &str) -> Result<&'a str> {
+        self.words.next().ok_or_else(|| eyre!("missing {what}"))
+    }
+
+    fn next<T: FromStr>(&mut self, what: &str) -> Result<T> {
+        let word = self.word(what)?;
+        word.parse().map_err(|_| eyre!("invalid {what} `{word}`"))
+    }
+
+    /// The next field if there is one
+    fn maybe_next<T: FromStr>(&mut self, what: &str) -> Result<Option<T>> {
+        match self.words.next() {
+            Some(word) => word
+                .parse()
+                .map(Some)
+                .map_err(|_| eyre!("invalid {what} `{word}`")),
+            None => Ok(None),
+        }
+    }
+
+    fn optional<T: FromStr>(&mut self, what: &str) -> Result<Option<T>> {
+        match self.word(what)? {
+            "-" => Ok(None),
+            word => word
+                .parse()
+                .map(Some)
+                .map_err(|_| eyre!("invalid {what} `{word}`")),
+        }
+    }
+
+    fn flag(&mut self, what: &str) -> Result<bool> {
+        match self.word(what)? {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            word => Err(eyre!("invalid {what} `{word}`")),
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match self.words.next() {
+            Some(word) => Err(eyre!("unexpected `{word}`")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Read a save file
+pub fn load(path: &Path) -> Result<GameState> {
+    let source =
+        fs::read_to_string(path).wrap_err_with(|| format!("reading {}", path.display()))?;
+    parse(&source).wrap_err_with(|| format!("in {}", path.display()))
+}
+
+/// Write `game` to `path`, creating its directory if needed
+pub fn store(game: &GameState, path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+    }
+    fs::write(path, to_text(game)).wrap_err_with(|| format!("writing {}", path.display()))
+}
+
+/// Move a save that could not be read out of the way, next to where it
+/// was, so Continue stops tripping on it but it can still be looked at
+pub fn set_aside(path: &Path) -> Result<PathBuf> {
+    let aside = path.with_extension("bad");
+    fs::rename(path, &aside).wrap_err_with(|| format!("moving {}", path.display()))?;
+    Ok(aside)
+}
+
+/// File a game saved from the pause screen is kept in
+pub fn path() -> Option<PathBuf> {
+    Some(scores::data_dir()?.join("save.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::tui::replay::Action;
+
+    /// A game some way in, with shots, a lock and a few waves in the air
+    fn game_in_progress() -> GameState {
+        let mut game = GameState::with_seed(31);
+        for tick in 0..460u32 {
+            game.update();
+            let action = match tick % 9 {
+                0 => Some(Action::Fire),
+                2 if tick % 80 < 40 => Some(Action::Left),
+                2 => Some(Action::Right),
+                4 if tick % 60 < 30 => Some(Action::Up),
+                4 => Some(Action::Down),
+                7 if tick % 45 == 7 => Some(Action::Missile),
+                _ => None,
+            };
+            if let Some(action) = action {
+                action.apply(&mut game);
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let game = game_in_progress();
+        assert!(!game.enemies.is_empty() && game.kills > 0);
+
+        let text = to_text(&game);
+        assert!(text.starts_with("spacey-save 2\n"));
+        let restored = parse(&text).unwrap();
+        assert_eq!(to_text(&restored), text);
+        assert_eq!(restored.score, game.score);
+        assert_eq!(restored.enemies.len(), game.enemies.len());
+    }
+
+    #[test]
+    fn test_restored_game_plays_on_the_same() {
+        let mut game = game_in_progress();
+        let mut restored = parse(&to_text(&game)).unwrap();
+        for tick in 0..200 {
+            game.update();
+            restored.update();
+            if tick % 5 == 0 {
+                game.fire_laser();
+                restored.fire_laser();
+            }
+        }
+        assert_eq!(to_text(&restored), to_text(&game));
+    }
+
+    #[test]
+    fn test_every_scenery_round_trips() {
+        for level in 0..5 {
+            let mut game = GameState::with_seed(3);
+            game.level = level;
+            game.environment = Environment::for_level(level);
+            game.scenery_start = 42.5;
+            let restored = parse(&to_text(&game)).unwrap();
+            assert_eq!(restored.environment, game.environment);
+            assert_eq!(restored.level, level);
+            assert_eq!(restored.scenery_start, 42.5);
+        }
+    }
+
+    #[test]
+    fn test_bad_files_are_rejected() {
+        let good = to_text(&GameState::with_seed(5));
+        let without = |key: &str| -> String {
+            good.lines()
+                .filter(|line| !line.starts_with(key))
+                .map(|line| line.to_string() + "\n")
+                .collect()
+        };
+        for text in [
+            String::new(),
+            good.replacen("spacey-save 2", "spacey-save 3", 1),
+            good.replacen("spacey-save 2", "spacey-replay 2", 1),
+            good.replacen("difficulty normal", "difficulty insane", 1),
+            without("ship"),
+            good.replacen("shield 10", "shield ten", 1),
+            good.replacen("shield 10 0", "shield 10 0 0", 1),
+            good.clone() + "warp 9\n",
+            good.replacen("fighter", "cruiser", 1),
+            good.replacen("shield 10", "shield 11", 1),
+            good.replacen("difficulty normal", "difficulty hard", 1),
+        ] {
+            assert!(parse(&text).is_err(), "{text:?}");
+        }
+
+        // Tallies that would underflow when their wave settles
+        let game = game_in_progress();
+        let text = to_text(&game);
+        let tally = text.lines().find(|line| line.starts_with("tally")).unwrap();
+        let fields: Vec<&str> = tally.split_whitespace().collect();
+        let with = |index: usize, value: String| {
+            let mut bad = fields.clone();
+            bad[index] = &value;
+            text.replacen(tally, &bad.join(" "), 1)
+        };
+        for text in [
+            with(1, "0".to_string()),
+            with(4, (game.shots_fired + 1).to_string()),
+            with(5, (game.shots_hit + 1).to_string()),
+        ] {
+            assert!(parse(&text).is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn test_difficulty_is_saved() {
+        let game = GameState::with_seed(8).with_difficulty(Difficulty::Easy);
+        let restored = parse(&to_text(&game)).unwrap();
+        assert_eq!(restored.difficulty, Difficulty::Easy);
+
+        // Version 1 saves predate difficulty
+        let old: String = to_text(&game)
+            .replacen("spacey-save 2", "spacey-save 1", 1)
+            .lines()
+            .filter(|line| !line.starts_with("difficulty"))
+            .map(|line| line.to_string() + "\n")
+            .collect();
+        assert_eq!(parse(&old).unwrap().difficulty, Difficulty::Normal);
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let dir = env::temp_dir().join(format!("spacey-save-{}", std::process::id()));
+        let path = dir.join("save.txt");
+        let game = game_in_progress();
+        store(&game, &path).unwrap();
+        assert_eq!(to_text(&load(&path).unwrap()), to_text(&game));
+
+        fs::write(&path, "spacey-save 2\nwarp 9\n").unwrap();
+        assert!(load(&path).is_err());
+        let aside = set_aside(&path).unwrap();
+        assert!(!path.exists());
+        assert!(aside.is_file());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
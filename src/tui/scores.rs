@@ -0,0 +1,216 @@
+//! High score table
+//!
+//! Scores are kept in a plain text file, one game per line as
+//! whitespace-separated numbers: score, kills, shots fired, shots hit, best
+//! combo and waves reached. Lines that don't parse are skipped, so a damaged
+//! file loses only those entries.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{Result, eyre::WrapErr};
+
+use super::game::GameState;
+
+/// Games kept in the table
+pub const MAX_ENTRIES: usize = 10;
+
+/// One finished game
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScoreEntry {
+    pub score: u32,
+    pub kills: u32,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub best_combo: u32,
+    pub waves: usize,
+}
+
+impl ScoreEntry {
+    /// Entry for the game as it stands
+    pub fn from_game(game: &GameState) -> Self {
+        Self {
+            score: game.score,
+            kills: game.kills,
+            shots_fired: game.shots_fired,
+            shots_hit: game.shots_hit,
+            best_combo: game.best_combo,
+            waves: game.waves,
+        }
+    }
+
+    /// Fraction of shots that hit, or `None` if none were fired
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots_fired > 0).then(|| self.shots_hit as f32 / self.shots_fired as f32)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let mut next = || fields.next()?.parse::<u64>().ok();
+        let entry = Self {
+            score: next()?.try_into().ok()?,
+            kills: next()?.try_into().ok()?,
+            shots_fired: next()?.try_into().ok()?,
+            shots_hit: next()?.try_into().ok()?,
+            best_combo: next()?.try_into().ok()?,
+            waves: next()?.try_into().ok()?,
+        };
+        Some(entry)
+    }
+
+    fn to_line(self) -> String {
+        format!(
+            "{} {} {} {} {} {}",
+            self.score, self.kills, self.shots_fired, self.shots_hit, self.best_combo, self.waves
+        )
+    }
+}
+
+/// Best games, highest score first
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HighScores {
+    entries: Vec<ScoreEntry>,
+}
+
+impl HighScores {
+    /// Read the table at `path`; a missing file is an empty table
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let source =
+            fs::read_to_string(path).wrap_err_with(|| format!("reading {}", path.display()))?;
+        Ok(Self::parse(&source))
+    }
+
+    /// Write the table to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+        }
+        fs::write(path, self.to_text()).wrap_err_with(|| format!("writing {}", path.display()))
+    }
+
+    /// Entries from highest score down
+    pub fn entries(&self) -> &[ScoreEntry] {
+        &self.entries
+    }
+
+    /// Add a game to the table, returning its rank (counting from 0) if it
+    /// made the cut
+    pub fn insert(&mut self, entry: ScoreEntry) -> Option<usize> {
+        if entry.score == 0 {
+            return None;
+        }
+        // Ties go below earlier games
+        let rank = self.entries.partition_point(|e| e.score >= entry.score);
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+
+    fn parse(source: &str) -> Self {
+        let mut table = Self::default();
+        for entry in source.lines().filter_map(ScoreEntry::parse) {
+            table.insert(entry);
+        }
+        table
+    }
+
+    fn to_text(&self) -> String {
+        self.entries.iter().map(|e| e.to_line() + "\n").collect()
+    }
+}
+
+/// Directory the game keeps its records in
+pub fn data_dir() -> Option<PathBuf> {
+    let data = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+    Some(data.join("spacey"))
+}
+
+/// File the high score table is kept in
+pub fn path() -> Option<PathBuf> {
+    if let Some(file) = env::var_os("SPACEY_SCORES_FILE") {
+        return Some(PathBuf::from(file));
+    }
+    Some(data_dir()?.join("scores.txt"))
+}
+
+/// Add `game` to the table on disk, returning its rank if it made the cut
+pub fn record(game: &GameState) -> Result<Option<usize>> {
+    let Some(path) = path() else {
+        return Ok(None);
+    };
+    let mut table = HighScores::load(&path)?;
+    let rank = table.insert(ScoreEntry::from_game(game));
+    if rank.is_some() {
+        table.save(&path)?;
+    }
+    Ok(rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32) -> ScoreEntry {
+        ScoreEntry {
+            score,
+            ..ScoreEntry::default()
+        }
+    }
+
+    #[test]
+    fn test_table_keeps_the_best() {
+        let mut table = HighScores::default();
+        for score in 1..=MAX_ENTRIES as u32 {
+            table.insert(entry(score * 100));
+        }
+        assert_eq!(table.insert(entry(50)), None);
+        assert_eq!(table.insert(entry(550)), Some(5));
+        assert_eq!(table.entries().len(), MAX_ENTRIES);
+        assert_eq!(table.entries()[0].score, 1000);
+        assert_eq!(table.entries()[MAX_ENTRIES - 1].score, 200);
+        assert_eq!(table.insert(entry(0)), None);
+    }
+
+    #[test]
+    fn test_round_trip_skips_bad_lines() {
+        let game = ScoreEntry {
+            score: 1200,
+            kills: 9,
+            shots_fired: 12,
+            shots_hit: 9,
+            best_combo: 6,
+            waves: 4,
+        };
+        let mut table = HighScores::default();
+        table.insert(game);
+        table.insert(entry(300));
+
+        let text = table.to_text() + "garbage\n1 2 3\n";
+        let parsed = HighScores::parse(&text);
+        assert_eq!(parsed, table);
+        assert_eq!(parsed.entries()[0].accuracy(), Some(0.75));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = env::temp_dir().join(format!("spacey-scores-{}", std::process::id()));
+        let path = dir.join("scores.txt");
+        assert_eq!(HighScores::load(&path).unwrap(), HighScores::default());
+
+        let mut table = HighScores::default();
+        table.insert(entry(700));
+        table.save(&path).unwrap();
+        assert_eq!(HighScores::load(&path).unwrap(), table);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
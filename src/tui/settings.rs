@@ -0,0 +1,48 @@
+//! User-adjustable settings
+
+use std::time::Duration;
+
+use super::{game::Difficulty, hud::HudLayout, raster::RenderMode, theme::Palette};
+
+/// Game frames per second unless told otherwise
+pub const DEFAULT_FPS: u32 = 20;
+
+/// Settings chosen on the options screen or the command line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    /// Color scheme for every screen
+    pub palette: Palette,
+    /// ASCII, half-block or braille lines in the game view
+    pub render_mode: RenderMode,
+    /// Tell entities apart by text modifiers as well as color
+    pub shape_cues: bool,
+    /// Scanlines, afterglow and flicker over every screen
+    pub crt: bool,
+    /// Difficulty of new games
+    pub difficulty: Difficulty,
+    /// Game frames per second
+    pub fps: u32,
+    /// Gauges on the HUD, in order
+    pub hud: HudLayout,
+}
+
+impl Settings {
+    /// Time between game frames
+    pub fn frame_time(&self) -> Duration {
+        Duration::from_secs(1) / self.fps.max(1)
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            palette: Palette::default(),
+            render_mode: RenderMode::default(),
+            shape_cues: false,
+            crt: false,
+            difficulty: Difficulty::default(),
+            fps: DEFAULT_FPS,
+            hud: HudLayout::default(),
+        }
+    }
+}
@@ -0,0 +1,455 @@
+//! Headless soak runs for tuning difficulty
+//!
+//! A soak flies many seeded games with the built-in `Autopilot`, stepping
+//! `GameState::update` directly with nothing drawn, and sums up how they
+//! went: how long the ship lasted, how scores spread, how many kills each
+//! wave gave up and what drained the shield. Reports are written as JSON or
+//! CSV by hand, so they can go straight into a spreadsheet or a script.
+
+use std::fmt::Write;
+
+use super::{
+    audio::NullSink,
+    autopilot::Autopilot,
+    game::{Difficulty, GameState, HitCause},
+    settings::DEFAULT_FPS,
+};
+
+/// Games flown when no count is given
+pub const DEFAULT_GAMES: u32 = 100;
+/// Ticks a game may last before the soak calls it survived: five minutes
+/// at the default frame rate
+pub const DEFAULT_MAX_TICKS: u64 = 5 * 60 * DEFAULT_FPS as u64;
+
+/// How a report is written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl Format {
+    /// Every format, in help order
+    pub fn all() -> &'static [Format] {
+        &[Format::Json, Format::Csv]
+    }
+
+    /// Name used on the command line
+    pub fn label(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+        }
+    }
+
+    /// Look up a format by name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Format::all()
+            .iter()
+            .copied()
+            .find(|f| f.label().eq_ignore_ascii_case(name))
+    }
+}
+
+/// What to fly
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Soak {
+    /// Number of games; game `n` uses seed `seed + n`
+    pub games: u32,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub max_ticks: u64,
+    /// Frame rate used to turn ticks into seconds
+    pub fps: u32,
+}
+
+impl Default for Soak {
+    fn default() -> Self {
+        Self {
+            games: DEFAULT_GAMES,
+            seed: 0,
+            difficulty: Difficulty::default(),
+            max_ticks: DEFAULT_MAX_TICKS,
+            fps: DEFAULT_FPS,
+        }
+    }
+}
+
+impl Soak {
+    /// Fly every game and collect the results
+    pub fn run(&self) -> Report {
+        let runs = (0..self.games)
+            .map(|n| self.fly(self.seed.wrapping_add(u64::from(n))))
+            .collect();
+        Report { soak: *self, runs }
+    }
+
+    /// Fly one game on `seed` until the shield runs out or time is up
+    pub fn fly(&self, seed: u64) -> GameStats {
+        let pilot = Autopilot;
+        let mut game = GameState::with_seed(seed).with_difficulty(self.difficulty);
+        while !game.is_over() && game.frame < self.max_ticks {
+            game.update();
+            for action in pilot.actions(&game) {
+                action.apply(&mut game);
+            }
+            // Nothing is heard, and `max_ticks` can be as long as asked
+            game.drain_sounds(&mut NullSink::default());
+        }
+        GameStats::from_game(seed, &game)
+    }
+}
+
+/// How one game went
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GameStats {
+    pub seed: u64,
+    pub ticks: u64,
+    /// Still flying when time ran out
+    pub survived: bool,
+    pub score: u32,
+    pub kills: u32,
+    pub waves: usize,
+    pub shield: u8,
+    /// Kills in each wave, first wave first
+    pub wave_kills: Vec<u32>,
+    /// Shield lost to each cause, in `HitCause::all` order
+    pub hits: Vec<u32>,
+}
+
+impl GameStats {
+    /// Stats for `game`, flown from `seed`
+    pub fn from_game(seed: u64, game: &GameState) -> Self {
+        let mut wave_kills = game.wave_kills.clone();
+        wave_kills.resize(wave_kills.len().max(game.waves), 0);
+        Self {
+            seed,
+            ticks: game.frame,
+            survived: !game.is_over(),
+            score: game.score,
+            kills: game.kills,
+            waves: game.waves,
+            shield: game.shield,
+            wave_kills,
+            hits: HitCause::all()
+                .iter()
+                .map(|cause| game.hits.iter().filter(|hit| *hit == cause).count() as u32)
+                .collect(),
+        }
+    }
+}
+
+/// Score spread across the games
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Spread {
+    pub min: u32,
+    pub p25: u32,
+    pub median: u32,
+    pub p75: u32,
+    pub max: u32,
+    pub mean: f64,
+}
+
+impl Spread {
+    /// Spread of `values`, all zero if there are none
+    fn of(values: &[u32]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        // Nearest rank, so every figure is a score some game really got
+        let at = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+        Self {
+            min: sorted[0],
+            p25: at(0.25),
+            median: at(0.5),
+            p75: at(0.75),
+            max: sorted[sorted.len() - 1],
+            mean: mean(sorted.iter().map(|&v| f64::from(v))),
+        }
+    }
+}
+
+/// Everything a soak found
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    pub soak: Soak,
+    pub runs: Vec<GameStats>,
+}
+
+impl Report {
+    /// Average ticks a game lasted
+    pub fn average_ticks(&self) -> f64 {
+        mean(self.runs.iter().map(|run| run.ticks as f64))
+    }
+
+    /// Average time a game lasted at the soak's frame rate
+    pub fn average_seconds(&self) -> f64 {
+        self.average_ticks() / f64::from(self.soak.fps.max(1))
+    }
+
+    /// Games still flying when time ran out
+    pub fn survivors(&self) -> usize {
+        self.runs.iter().filter(|run| run.survived).count()
+    }
+
+    /// Spread of final scores
+    pub fn scores(&self) -> Spread {
+        let scores: Vec<u32> = self.runs.iter().map(|run| run.score).collect();
+        Spread::of(&scores)
+    }
+
+    /// Average kills in each wave, over the games that reached it
+    pub fn kills_per_wave(&self) -> Vec<f64> {
+        let deepest = self.runs.iter().map(|run| run.wave_kills.len()).max();
+        (0..deepest.unwrap_or(0))
+            .map(|wave| {
+                mean(
+                    self.runs
+                        .iter()
+                        .filter_map(|run| run.wave_kills.get(wave))
+                        .map(|&k| f64::from(k)),
+                )
+            })
+            .collect()
+    }
+
+    /// Shield lost to each cause over all games
+    pub fn shield_loss(&self) -> Vec<(HitCause, u32)> {
+        HitCause::all()
+            .iter()
+            .enumerate()
+            .map(|(i, &cause)| (cause, self.runs.iter().map(|run| run.hits[i]).sum()))
+            .collect()
+    }
+
+    /// The summary and every game, as a JSON object
+    pub fn to_json(&self) -> String {
+        let scores = self.scores();
+        let mut json = String::from("{\n");
+        let _ = writeln!(json, "  \"games\": {},", self.runs.len());
+        let _ = writeln!(json, "  \"seed\": {},", self.soak.seed);
+        let _ = writeln!(
+            json,
+            "  \"difficulty\": \"{}\",",
+            difficulty_name(self.soak.difficulty)
+        );
+        let _ = writeln!(json, "  \"max_ticks\": {},", self.soak.max_ticks);
+        let _ = writeln!(json, "  \"fps\": {},", self.soak.fps);
+        let _ = writeln!(json, "  \"average_ticks\": {:.2},", self.average_ticks());
+        let _ = writeln!(
+            json,
+            "  \"average_seconds\": {:.2},",
+            self.average_seconds()
+        );
+        let _ = writeln!(json, "  \"survivors\": {},", self.survivors());
+        let _ = writeln!(
+            json,
+            "  \"score\": {{\"min\": {}, \"p25\": {}, \"median\": {}, \"p75\": {}, \"max\": {}, \"mean\": {:.2}}},",
+            scores.min, scores.p25, scores.median, scores.p75, scores.max, scores.mean
+        );
+        let waves: Vec<String> = self
+            .kills_per_wave()
+            .iter()
+            .map(|k| format!("{k:.2}"))
+            .collect();
+        let _ = writeln!(json, "  \"kills_per_wave\": [{}],", waves.join(", "));
+        let losses: Vec<String> = self
+            .shield_loss()
+            .iter()
+            .map(|(cause, lost)| format!("\"{}\": {lost}", cause.label()))
+            .collect();
+        let _ = writeln!(json, "  \"shield_loss\": {{{}}},", losses.join(", "));
+        json.push_str("  \"runs\": [");
+        for (i, run) in self.runs.iter().enumerate() {
+            let wave_kills: Vec<String> = run.wave_kills.iter().map(u32::to_string).collect();
+            let hits: Vec<String> = HitCause::all()
+                .iter()
+                .zip(&run.hits)
+                .map(|(cause, lost)| format!("\"{}\": {lost}", cause.label()))
+                .collect();
+            let _ = write!(
+                json,
+                "{}\n    {{\"seed\": {}, \"ticks\": {}, \"survived\": {}, \"score\": {}, \"kills\": {}, \"waves\": {}, \"shield\": {}, \"wave_kills\": [{}], \"shield_loss\": {{{}}}}}",
+                if i == 0 { "" } else { "," },
+                run.seed,
+                run.ticks,
+                run.survived,
+                run.score,
+                run.kills,
+                run.waves,
+                run.shield,
+                wave_kills.join(", "),
+                hits.join(", ")
+            );
+        }
+        json.push_str(if self.runs.is_empty() {
+            "]\n}\n"
+        } else {
+            "\n  ]\n}\n"
+        });
+        json
+    }
+
+    /// The summary as `stat,value` rows
+    pub fn to_csv(&self) -> String {
+        let scores = self.scores();
+        let mut rows = vec![
+            ("games".to_string(), self.runs.len().to_string()),
+            ("seed".to_string(), self.soak.seed.to_string()),
+            (
+                "difficulty".to_string(),
+                difficulty_name(self.soak.difficulty),
+            ),
+            ("max_ticks".to_string(), self.soak.max_ticks.to_string()),
+            ("fps".to_string(), self.soak.fps.to_string()),
+            (
+                "average_ticks".to_string(),
+                format!("{:.2}", self.average_ticks()),
+            ),
+            (
+                "average_seconds".to_string(),
+                format!("{:.2}", self.average_seconds()),
+            ),
+            ("survivors".to_string(), self.survivors().to_string()),
+            ("score_min".to_string(), scores.min.to_string()),
+            ("score_p25".to_string(), scores.p25.to_string()),
+            ("score_median".to_string(), scores.median.to_string()),
+            ("score_p75".to_string(), scores.p75.to_string()),
+            ("score_max".to_string(), scores.max.to_string()),
+            ("score_mean".to_string(), format!("{:.2}", scores.mean)),
+        ];
+        for (wave, kills) in self.kills_per_wave().iter().enumerate() {
+            rows.push((format!("kills_wave_{}", wave + 1), format!("{kills:.2}")));
+        }
+        for (cause, lost) in self.shield_loss() {
+            rows.push((format!("shield_loss_{}", cause.label()), lost.to_string()));
+        }
+
+        let mut csv = String::from("stat,value\n");
+        for (stat, value) in rows {
+            let _ = writeln!(csv, "{stat},{value}");
+        }
+        csv
+    }
+}
+
+/// Difficulty as written in reports
+fn difficulty_name(difficulty: Difficulty) -> String {
+    difficulty.label().to_ascii_lowercase()
+}
+
+/// Mean of `values`, or zero if there are none
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / f64::from(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_soak() -> Soak {
+        Soak {
+            games: 4,
+            seed: 7,
+            max_ticks: 600,
+            ..Soak::default()
+        }
+    }
+
+    #[test]
+    fn test_soak_is_repeatable() {
+        let soak = small_soak();
+        let report = soak.run();
+        assert_eq!(report, soak.run());
+        let seeds: Vec<u64> = report.runs.iter().map(|run| run.seed).collect();
+        assert_eq!(seeds, [7, 8, 9, 10]);
+        for run in &report.runs {
+            assert!(run.ticks <= 600);
+            assert_eq!(run.survived, run.shield > 0);
+            assert_eq!(run.wave_kills.iter().sum::<u32>(), run.kills);
+            assert_eq!(run.hits.len(), HitCause::all().len());
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let run = |score, ticks, survived, wave_kills: &[u32], hits: &[u32]| GameStats {
+            score,
+            ticks,
+            survived,
+            wave_kills: wave_kills.to_vec(),
+            hits: hits.to_vec(),
+            ..GameStats::default()
+        };
+        let report = Report {
+            soak: Soak::default(),
+            runs: vec![
+                run(100, 200, false, &[1], &[4, 3, 3]),
+                run(300, 400, true, &[2, 4], &[1, 0, 0]),
+                run(200, 600, false, &[3, 0], &[0, 10, 0]),
+            ],
+        };
+        assert_eq!(report.average_ticks(), 400.0);
+        assert_eq!(report.average_seconds(), 20.0);
+        assert_eq!(report.survivors(), 1);
+        let scores = report.scores();
+        assert_eq!((scores.min, scores.median, scores.max), (100, 200, 300));
+        assert_eq!(scores.mean, 200.0);
+        assert_eq!(report.kills_per_wave(), [2.0, 2.0]);
+        assert_eq!(
+            report.shield_loss(),
+            [
+                (HitCause::Enemy, 5),
+                (HitCause::Obstacle, 13),
+                (HitCause::Wall, 3)
+            ]
+        );
+
+        let json = report.to_json();
+        assert!(json.starts_with("{\n  \"games\": 3,\n"));
+        assert!(json.contains(
+            "  \"score\": {\"min\": 100, \"p25\": 200, \"median\": 200, \"p75\": 300, \"max\": 300, \"mean\": 200.00},\n"
+        ));
+        assert!(json.contains("  \"kills_per_wave\": [2.00, 2.00],\n"));
+        assert!(
+            json.contains("  \"shield_loss\": {\"enemy\": 5, \"obstacle\": 13, \"wall\": 3},\n")
+        );
+        assert!(json.contains("\"wave_kills\": [2, 4]"));
+        assert!(json.ends_with("}\n  ]\n}\n"));
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("stat,value\ngames,3\n"));
+        assert!(csv.contains("\naverage_seconds,20.00\n"));
+        assert!(csv.contains("\nkills_wave_2,2.00\n"));
+        assert!(csv.ends_with("\nshield_loss_wall,3\n"));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = Soak {
+            games: 0,
+            ..Soak::default()
+        }
+        .run();
+        assert_eq!(report.average_ticks(), 0.0);
+        assert_eq!(report.scores(), Spread::default());
+        assert!(report.to_json().ends_with("\"runs\": []\n}\n"));
+    }
+
+    #[test]
+    fn test_format_names() {
+        for format in Format::all() {
+            assert_eq!(Format::from_name(format.label()), Some(*format));
+        }
+        assert_eq!(Format::from_name("CSV"), Some(Format::Csv));
+        assert_eq!(Format::from_name("xml"), None);
+    }
+}
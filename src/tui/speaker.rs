@@ -0,0 +1,79 @@
+//! Sound through the system audio device (`synth` feature)
+//!
+//! The device's callback pulls samples from a `Mixer`, which plays each
+//! sound effect over the looping soundtrack.
+
+use std::sync::{Arc, Mutex};
+
+use color_eyre::{
+    Result,
+    eyre::{bail, eyre},
+};
+use cpal::{
+    SampleFormat, Stream,
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+};
+
+use super::{
+    audio::{AudioSink, Sound},
+    synth::{Mixer, THEME},
+};
+
+/// Plays sounds on the default output device
+pub struct Speaker {
+    /// Kept alive for as long as sound should play
+    _stream: Stream,
+    mixer: Arc<Mutex<Mixer>>,
+}
+
+impl Speaker {
+    /// Open the default output device and start the soundtrack
+    pub fn open() -> Result<Self> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| eyre!("no audio output device"))?;
+        let supported = device.default_output_config()?;
+        if supported.sample_format() != SampleFormat::F32 {
+            bail!("unsupported sample format {}", supported.sample_format());
+        }
+        let config = supported.config();
+        let channels = config.channels as usize;
+
+        let mixer = Arc::new(Mutex::new(
+            Mixer::new(config.sample_rate.0).with_music(&THEME),
+        ));
+        let source = Arc::clone(&mixer);
+        let mut mono = Vec::new();
+        let stream = device.build_output_stream(
+            &config,
+            move |data: &mut [f32], _| {
+                mono.resize(data.len() / channels, 0.0);
+                source
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .fill(&mut mono);
+                for (frame, &sample) in data.chunks_mut(channels).zip(&mono) {
+                    frame.fill(sample);
+                }
+            },
+            // Dropouts aren't worth interrupting the game for
+            |_| {},
+            None,
+        )?;
+        stream.play()?;
+
+        Ok(Self {
+            _stream: stream,
+            mixer,
+        })
+    }
+}
+
+impl AudioSink for Speaker {
+    fn play(&mut self, sound: Sound) {
+        self.mixer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .play(sound);
+    }
+}
@@ -0,0 +1,297 @@
+//! Multi-line ASCII sprites and depth-tested compositing
+//!
+//! Everything in the game view is drawn through a [`Canvas`], which keeps a
+//! z-buffer alongside the ratatui buffer so nearer cells always win no matter
+//! what order layers are drawn in.
+
+use ratatui::prelude::*;
+
+/// Character treated as see-through when blitting a sprite
+pub const TRANSPARENT: char = ' ';
+
+/// How a sprite would like to be colored; the renderer picks the actual color
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorHint {
+    #[default]
+    Normal,
+    Dim,
+    Bright,
+    Alert,
+}
+
+impl ColorHint {
+    /// Parse a hint as written in asset files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "normal" => Some(Self::Normal),
+            "dim" => Some(Self::Dim),
+            "bright" => Some(Self::Bright),
+            "alert" => Some(Self::Alert),
+            _ => None,
+        }
+    }
+}
+
+/// A block of ASCII art, drawn with its anchor cell on the target point
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sprite {
+    rows: Vec<String>,
+    /// Cell placed on the target point, defaults to the middle of the art
+    anchor: Option<(u16, u16)>,
+}
+
+impl Sprite {
+    /// Create a sprite from its rows, top to bottom
+    pub fn new<S: Into<String>>(rows: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            rows: rows.into_iter().map(Into::into).collect(),
+            anchor: None,
+        }
+    }
+
+    /// Use an explicit anchor cell instead of the middle of the art
+    pub fn with_anchor(mut self, x: u16, y: u16) -> Self {
+        self.anchor = Some((x, y));
+        self
+    }
+
+    /// Rows of art, top to bottom
+    pub fn rows(&self) -> &[String] {
+        &self.rows
+    }
+
+    /// Width in cells of the widest row
+    pub fn width(&self) -> u16 {
+        self.rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0) as u16
+    }
+
+    /// Height in cells
+    pub fn height(&self) -> u16 {
+        self.rows.len() as u16
+    }
+
+    /// Cell placed on the target point when drawn
+    pub fn anchor(&self) -> (u16, u16) {
+        self.anchor.unwrap_or((self.width() / 2, self.height() / 2))
+    }
+}
+
+/// A sprite drawn at different levels of detail
+///
+/// Each frame covers a size tier: it is used while the size measure (depth
+/// scale for world objects, available rows for the cockpit) is below the
+/// frame's limit. The last frame has no limit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteSet {
+    color: ColorHint,
+    frames: Vec<(f32, Sprite)>,
+}
+
+impl SpriteSet {
+    /// Create a set from `(limit, frame)` pairs in increasing limit order
+    pub fn new(color: ColorHint, frames: Vec<(f32, Sprite)>) -> Self {
+        Self { color, frames }
+    }
+
+    /// Preferred color of every frame
+    pub fn color(&self) -> ColorHint {
+        self.color
+    }
+
+    /// The frame for a size measure, falling back to the largest tier
+    pub fn frame(&self, measure: f32) -> &Sprite {
+        &self.frames[self.tier(measure)].1
+    }
+
+    /// Index of the size tier used for a measure, 0 being the smallest
+    pub fn tier(&self, measure: f32) -> usize {
+        self.frames
+            .iter()
+            .position(|(limit, _)| measure < *limit)
+            .unwrap_or(self.frames.len() - 1)
+    }
+}
+
+/// A ratatui buffer region paired with a z-buffer
+///
+/// Depth uses the same units as entity `z`: smaller is nearer the camera.
+pub struct Canvas<'a> {
+    buf: &'a mut Buffer,
+    area: Rect,
+    depth: Vec<f32>,
+}
+
+impl<'a> Canvas<'a> {
+    /// Create a canvas over `area` with every cell infinitely far away
+    pub fn new(buf: &'a mut Buffer, area: Rect) -> Self {
+        let area = area.intersection(buf.area);
+        Self {
+            buf,
+            area,
+            depth: vec![f32::INFINITY; area.area() as usize],
+        }
+    }
+
+    /// The region being drawn into
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    /// Depth currently stored at a view-local cell, if it is inside the canvas
+    pub fn depth_at(&self, x: i32, y: i32) -> Option<f32> {
+        self.index(x, y).map(|i| self.depth[i])
+    }
+
+    /// Draw one cell if it is at least as near as what is already there
+    ///
+    /// Ties go to the later draw, so a layer can overwrite its own cells.
+    pub fn plot(&mut self, x: i32, y: i32, z: f32, ch: char, style: Style) {
+        let Some(i) = self.index(x, y) else {
+            return;
+        };
+        if z > self.depth[i] {
+            return;
+        }
+        self.depth[i] = z;
+        let position = (self.area.x + x as u16, self.area.y + y as u16);
+        if let Some(cell) = self.buf.cell_mut(position) {
+            cell.set_char(ch).set_style(style);
+        }
+    }
+
+    /// Draw a sprite with its anchor on a view-local point, skipping
+    /// transparent cells
+    pub fn blit(&mut self, sprite: &Sprite, x: f32, y: f32, z: f32, style: Style) {
+        let (anchor_x, anchor_y) = sprite.anchor();
+        let left = x.floor() as i32 - anchor_x as i32;
+        let top = y.floor() as i32 - anchor_y as i32;
+
+        for (dy, row) in sprite.rows().iter().enumerate() {
+            for (dx, ch) in row.chars().enumerate() {
+                if ch != TRANSPARENT {
+                    self.plot(left + dx as i32, top + dy as i32, z, ch, style);
+                }
+            }
+        }
+    }
+
+    /// Offset into the z-buffer for a view-local cell
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let (width, height) = (self.area.width as i32, self.area.height as i32);
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return None;
+        }
+        Some((y * width + x) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn box_sprite() -> Sprite {
+        Sprite::new(["###", "# #", "###"])
+    }
+
+    fn symbol(buf: &Buffer, x: u16, y: u16) -> &str {
+        buf[(x, y)].symbol()
+    }
+
+    #[test]
+    fn test_sprite_dimensions() {
+        assert_eq!(box_sprite().width(), 3);
+        assert_eq!(box_sprite().height(), 3);
+        assert_eq!(box_sprite().anchor(), (1, 1));
+        assert_eq!(Sprite::new(["a", "abcd"]).width(), 4);
+    }
+
+    #[test]
+    fn test_blit_is_centered() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 9, 9));
+        let mut canvas = Canvas::new(&mut buf, Rect::new(0, 0, 9, 9));
+        canvas.blit(&box_sprite(), 4.5, 4.5, 10.0, Style::default());
+
+        // The middle of the sprite lands on the anchor cell
+        assert_eq!(symbol(&buf, 3, 3), "#");
+        assert_eq!(symbol(&buf, 5, 5), "#");
+        assert_eq!(symbol(&buf, 2, 2), " ");
+        assert_eq!(symbol(&buf, 6, 6), " ");
+    }
+
+    #[test]
+    fn test_blit_uses_explicit_anchor() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 5, 5));
+        let mut canvas = Canvas::new(&mut buf, Rect::new(0, 0, 5, 5));
+        let flag = Sprite::new(["ab", "c"]).with_anchor(0, 1);
+        canvas.blit(&flag, 2.0, 2.0, 1.0, Style::default());
+
+        assert_eq!(symbol(&buf, 2, 1), "a");
+        assert_eq!(symbol(&buf, 3, 1), "b");
+        assert_eq!(symbol(&buf, 2, 2), "c");
+    }
+
+    #[test]
+    fn test_sprite_set_picks_tier() {
+        let set = SpriteSet::new(
+            ColorHint::Bright,
+            vec![
+                (2.0, Sprite::new(["."])),
+                (5.0, Sprite::new(["-o-"])),
+                (f32::INFINITY, Sprite::new(["/-\\"])),
+            ],
+        );
+        assert_eq!(set.frame(1.0).rows()[0], ".");
+        assert_eq!(set.frame(2.0).rows()[0], "-o-");
+        assert_eq!(set.frame(50.0).rows()[0], "/-\\");
+        assert_eq!(set.tier(1.0), 0);
+        assert_eq!(set.tier(50.0), 2);
+    }
+
+    #[test]
+    fn test_transparent_cells_keep_background() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 3));
+        let mut canvas = Canvas::new(&mut buf, Rect::new(0, 0, 3, 3));
+        canvas.plot(1, 1, 50.0, '-', Style::default());
+        canvas.blit(&box_sprite(), 1.5, 1.5, 10.0, Style::default());
+
+        assert_eq!(symbol(&buf, 1, 1), "-");
+        assert_eq!(symbol(&buf, 0, 0), "#");
+    }
+
+    #[test]
+    fn test_nearer_sprite_occludes_farther_regardless_of_order() {
+        let near = Sprite::new(["N"]);
+        let far = Sprite::new(["F"]);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+        let mut canvas = Canvas::new(&mut buf, Rect::new(0, 0, 1, 1));
+        canvas.blit(&near, 0.5, 0.5, 5.0, Style::default());
+        canvas.blit(&far, 0.5, 0.5, 80.0, Style::default());
+        assert_eq!(symbol(&buf, 0, 0), "N");
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+        let mut canvas = Canvas::new(&mut buf, Rect::new(0, 0, 1, 1));
+        canvas.blit(&far, 0.5, 0.5, 80.0, Style::default());
+        canvas.blit(&near, 0.5, 0.5, 5.0, Style::default());
+        assert_eq!(symbol(&buf, 0, 0), "N");
+    }
+
+    #[test]
+    fn test_blit_clips_to_area() {
+        let area = Rect::new(2, 1, 4, 2);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 4));
+        let mut canvas = Canvas::new(&mut buf, area);
+        canvas.blit(&box_sprite(), -0.5, -0.5, 1.0, Style::default());
+        assert_eq!(canvas.depth_at(-1, 0), None);
+        assert_eq!(canvas.depth_at(0, 0), Some(1.0));
+
+        // Only the bottom-right corner of the sprite is inside the area
+        assert_eq!(symbol(&buf, 2, 1), "#");
+        assert_eq!(symbol(&buf, 1, 1), " ");
+        assert_eq!(symbol(&buf, 2, 0), " ");
+    }
+}
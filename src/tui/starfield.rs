@@ -0,0 +1,152 @@
+//! Background starfield
+//!
+//! Stars are points in the same world space as enemies, spread wide enough
+//! to cover the sky band as well as the view, that stream toward the camera
+//! and are replaced far away once they pass it.
+
+/// Number of stars alive at once
+pub const STAR_COUNT: usize = 300;
+/// Depth stars close in per frame
+const STAR_SPEED: f32 = 0.6;
+/// Farthest star depth, where replacements appear
+const FAR_Z: f32 = 100.0;
+/// Stars nearer than this have passed the camera
+const NEAR_Z: f32 = 2.0;
+/// Half-width of the region stars are scattered over, in world units
+const SPREAD: f32 = 2.0;
+
+/// A single star in world space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Star {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Star {
+    /// Glyph for this star; nearer stars look bigger
+    pub fn glyph(&self) -> char {
+        match self.z {
+            z if z > 60.0 => '.',
+            z if z > 25.0 => '+',
+            _ => '*',
+        }
+    }
+}
+
+/// All stars, with the generator used to place new ones
+#[derive(Clone, Debug)]
+pub struct Starfield {
+    stars: Vec<Star>,
+    seed: u64,
+}
+
+impl Starfield {
+    /// Scatter stars at every depth so the field starts full
+    pub fn new(seed: u64) -> Self {
+        let mut field = Self {
+            stars: Vec::with_capacity(STAR_COUNT),
+            seed,
+        };
+        for _ in 0..STAR_COUNT {
+            let z = NEAR_Z + field.next_unit() * (FAR_Z - NEAR_Z);
+            let star = field.place(z);
+            field.stars.push(star);
+        }
+        field
+    }
+
+    /// Live stars in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &Star> {
+        self.stars.iter()
+    }
+
+    /// Move every star toward the camera, replacing any that pass it
+    pub fn update(&mut self) {
+        for i in 0..self.stars.len() {
+            self.stars[i].z -= STAR_SPEED;
+            if self.stars[i].z < NEAR_Z {
+                self.stars[i] = self.place(FAR_Z);
+            }
+        }
+    }
+
+    /// A star at a random spot at depth `z`
+    fn place(&mut self, z: f32) -> Star {
+        let x = (self.next_unit() * 2.0 - 1.0) * SPREAD;
+        let y = (self.next_unit() * 2.0 - 1.0) * SPREAD;
+        Star { x, y, z }
+    }
+
+    /// Next pseudo-random value in `0.0..1.0` (xorshift64)
+    fn next_unit(&mut self) -> f32 {
+        let mut x = self.seed.max(1);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed = x;
+        (x >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+impl Default for Starfield {
+    fn default() -> Self {
+        Self::new(0x5EED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_starts_full_at_every_depth() {
+        let field = Starfield::default();
+        assert_eq!(field.iter().count(), STAR_COUNT);
+
+        let glyphs: Vec<char> = field.iter().map(Star::glyph).collect();
+        for glyph in ['.', '+', '*'] {
+            assert!(glyphs.contains(&glyph), "no {glyph} stars");
+        }
+        assert!(
+            field
+                .iter()
+                .all(|s| s.x.abs() <= SPREAD && s.y.abs() <= SPREAD)
+        );
+    }
+
+    #[test]
+    fn test_stars_stream_toward_camera() {
+        let mut field = Starfield::default();
+        let before: Vec<Star> = field.iter().copied().collect();
+        field.update();
+
+        for (old, new) in before.iter().zip(field.iter()) {
+            if old.z - STAR_SPEED >= NEAR_Z {
+                assert_eq!(new.z, old.z - STAR_SPEED);
+                assert_eq!((new.x, new.y), (old.x, old.y));
+            } else {
+                // Passed the camera and was replaced far away
+                assert_eq!(new.z, FAR_Z);
+            }
+        }
+    }
+
+    #[test]
+    fn test_field_never_empties() {
+        let mut field = Starfield::default();
+        for _ in 0..1000 {
+            field.update();
+        }
+        assert_eq!(field.iter().count(), STAR_COUNT);
+        assert!(field.iter().all(|s| (NEAR_Z..=FAR_Z).contains(&s.z)));
+    }
+
+    #[test]
+    fn test_same_seed_same_sky() {
+        let a = Starfield::new(7);
+        let b = Starfield::new(7);
+        assert!(a.iter().eq(b.iter()));
+        assert!(!a.iter().eq(Starfield::new(8).iter()));
+    }
+}
@@ -0,0 +1,605 @@
+//! Chiptune synthesizer
+//!
+//! Sounds are built from notes on three kinds of channel, square, triangle
+//! and noise, each shaped by an ADSR envelope, and rendered to mono PCM at
+//! any sample rate. Sound effects are short note lists; the soundtrack is
+//! written as note names per channel and rendered as one loop. A `Mixer`
+//! plays effects over the looping soundtrack as game events arrive, and
+//! everything can be written to a WAV file to check offline.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use super::audio::{AudioSink, Sound};
+
+/// Most effects that play at once; the oldest is cut off past this
+const MAX_VOICES: usize = 8;
+/// Loudness of the soundtrack under the effects
+const MUSIC_VOLUME: f32 = 0.5;
+/// Fraction of a step a soundtrack note is held for
+const NOTE_HOLD: f32 = 0.8;
+
+/// Shape of a channel's wave
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    /// Pulse wave, high for `duty` of each cycle
+    Square {
+        duty: f32,
+    },
+    Triangle,
+    /// Pseudo-random bits clocked at the note's pitch
+    Noise,
+}
+
+/// How a note's loudness rises and falls, in seconds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    /// Level held after the decay, 0.0 to 1.0
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    /// Short blip that dies away quickly
+    pub const PLUCK: Self = Self {
+        attack: 0.002,
+        decay: 0.05,
+        sustain: 0.4,
+        release: 0.03,
+    };
+    /// Hits hard and rings on
+    pub const BLAST: Self = Self {
+        attack: 0.001,
+        decay: 0.1,
+        sustain: 0.6,
+        release: 0.25,
+    };
+    /// Steady tone for melody
+    pub const ORGAN: Self = Self {
+        attack: 0.01,
+        decay: 0.05,
+        sustain: 0.7,
+        release: 0.05,
+    };
+
+    /// Level `t` seconds into a note held for `held` seconds
+    pub fn level(&self, t: f32, held: f32) -> f32 {
+        let before_release = |t: f32| {
+            if t < self.attack {
+                t / self.attack
+            } else if t < self.attack + self.decay {
+                1.0 - (1.0 - self.sustain) * (t - self.attack) / self.decay
+            } else {
+                self.sustain
+            }
+        };
+        if t < held {
+            before_release(t)
+        } else if t < held + self.release {
+            before_release(held) * (1.0 - (t - held) / self.release)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// One note on one channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Note {
+    pub wave: Waveform,
+    /// Seconds from the start of the sound
+    pub start: f32,
+    /// Seconds held before the release
+    pub length: f32,
+    /// Pitch in Hz at the start
+    pub pitch: f32,
+    /// Pitch in Hz the note has glided to by the end of its hold
+    pub slide_to: f32,
+    /// Peak level, 0.0 to 1.0
+    pub volume: f32,
+    pub envelope: Envelope,
+}
+
+impl Note {
+    /// A steady note at full volume with a plucked envelope
+    pub fn new(wave: Waveform, pitch: f32, start: f32, length: f32) -> Self {
+        Self {
+            wave,
+            start,
+            length,
+            pitch,
+            slide_to: pitch,
+            volume: 1.0,
+            envelope: Envelope::PLUCK,
+        }
+    }
+
+    /// Glide to `pitch` over the hold
+    pub fn with_slide(mut self, pitch: f32) -> Self {
+        self.slide_to = pitch;
+        self
+    }
+
+    /// Peak at `volume` instead of full volume
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Shape the note with `envelope` instead of a pluck
+    pub fn with_envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    /// Seconds from the start of the sound to the end of the release
+    pub fn end(&self) -> f32 {
+        self.start + self.length + self.envelope.release
+    }
+
+    /// Add this note into `out`, which starts at the sound's start
+    fn render_into(&self, out: &mut [f32], sample_rate: u32) {
+        let rate = sample_rate as f32;
+        let first = (self.start * rate) as usize;
+        let count = ((self.length + self.envelope.release) * rate) as usize;
+        let mut phase = 0.0f32;
+        // 15-bit shift register, as in the noise channel of 8-bit consoles
+        let mut lfsr: u16 = 1;
+
+        for (i, sample) in out.iter_mut().skip(first).take(count).enumerate() {
+            let t = i as f32 / rate;
+            let glide = (t / self.length).min(1.0);
+            let pitch = self.pitch + (self.slide_to - self.pitch) * glide;
+            phase += pitch / rate;
+            if phase >= 1.0 {
+                phase = phase.fract();
+                let bit = (lfsr ^ (lfsr >> 1)) & 1;
+                lfsr = (lfsr >> 1) | (bit << 14);
+            }
+            let wave = match self.wave {
+                Waveform::Square { duty } => {
+                    if phase < duty {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                Waveform::Triangle => 4.0 * (phase - 0.5).abs() - 1.0,
+                Waveform::Noise => {
+                    if lfsr & 1 == 1 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+            };
+            *sample += wave * self.volume * self.envelope.level(t, self.length);
+        }
+    }
+}
+
+/// Mono samples from -1.0 to 1.0
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pcm {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl Pcm {
+    /// Render `notes` together, long enough for the last to finish
+    pub fn render(notes: &[Note], sample_rate: u32) -> Self {
+        let end = notes.iter().map(Note::end).fold(0.0, f32::max);
+        let mut samples = vec![0.0; (end * sample_rate as f32).ceil() as usize];
+        for note in notes {
+            note.render_into(&mut samples, sample_rate);
+        }
+        for sample in &mut samples {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+        Self {
+            sample_rate,
+            samples,
+        }
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+
+    /// Loudest sample's level
+    pub fn peak(&self) -> f32 {
+        self.samples.iter().fold(0.0, |peak, s| s.abs().max(peak))
+    }
+
+    /// Write as a 16-bit mono WAV file
+    pub fn write_wav(&self, mut out: impl Write) -> io::Result<()> {
+        let data_len = self.samples.len() as u32 * 2;
+        out.write_all(b"RIFF")?;
+        out.write_all(&(36 + data_len).to_le_bytes())?;
+        out.write_all(b"WAVEfmt ")?;
+        out.write_all(&16u32.to_le_bytes())?;
+        // PCM, one channel
+        out.write_all(&1u16.to_le_bytes())?;
+        out.write_all(&1u16.to_le_bytes())?;
+        out.write_all(&self.sample_rate.to_le_bytes())?;
+        out.write_all(&(self.sample_rate * 2).to_le_bytes())?;
+        out.write_all(&2u16.to_le_bytes())?;
+        out.write_all(&16u16.to_le_bytes())?;
+        out.write_all(b"data")?;
+        out.write_all(&data_len.to_le_bytes())?;
+        for sample in &self.samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            out.write_all(&value.to_le_bytes())?;
+        }
+        out.flush()
+    }
+}
+
+/// Notes for a sound effect
+pub fn effect(sound: Sound) -> Vec<Note> {
+    let pulse = Waveform::Square { duty: 0.25 };
+    let square = Waveform::Square { duty: 0.5 };
+    match sound {
+        Sound::LaserFire => vec![
+            Note::new(pulse, 1400.0, 0.0, 0.08)
+                .with_slide(500.0)
+                .with_volume(0.4),
+        ],
+        Sound::MissileLaunch => vec![
+            Note::new(square, 300.0, 0.0, 0.2)
+                .with_slide(900.0)
+                .with_volume(0.3),
+            Note::new(Waveform::Noise, 4000.0, 0.0, 0.25)
+                .with_volume(0.2)
+                .with_envelope(Envelope::BLAST),
+        ],
+        Sound::Explosion => vec![
+            Note::new(Waveform::Noise, 1500.0, 0.0, 0.3)
+                .with_slide(150.0)
+                .with_volume(0.7)
+                .with_envelope(Envelope::BLAST),
+        ],
+        Sound::ShieldHit => vec![
+            Note::new(square, 120.0, 0.0, 0.15)
+                .with_slide(80.0)
+                .with_volume(0.5),
+            Note::new(Waveform::Noise, 600.0, 0.0, 0.1).with_volume(0.4),
+        ],
+        Sound::MenuMove => vec![Note::new(pulse, 660.0, 0.0, 0.03).with_volume(0.3)],
+        Sound::GameOver => ["A4", "F4", "D4", "A3"]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| {
+                let note = Note::new(Waveform::Triangle, pitch(name)?, i as f32 * 0.3, 0.25);
+                Some(note.with_volume(0.6).with_envelope(Envelope::ORGAN))
+            })
+            .collect(),
+    }
+}
+
+/// Pitch in Hz of a note name like `A4` or `C#3`
+pub fn pitch(name: &str) -> Option<f32> {
+    let mut chars = name.chars();
+    let semitone = match chars.next()? {
+        'C' => -9,
+        'D' => -7,
+        'E' => -5,
+        'F' => -4,
+        'G' => -2,
+        'A' => 0,
+        'B' => 2,
+        _ => return None,
+    };
+    let rest = chars.as_str();
+    let (sharp, octave) = match rest.strip_prefix('#') {
+        Some(octave) => (1, octave),
+        None => (0, rest),
+    };
+    let octave: i32 = octave.parse().ok()?;
+    let from_a4 = semitone + sharp + (octave - 4) * 12;
+    Some(440.0 * 2f32.powf(from_a4 as f32 / 12.0))
+}
+
+/// One instrument's part in a track
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Part {
+    pub wave: Waveform,
+    pub volume: f32,
+    /// Note names, one per step, with `-` for a rest
+    pub notes: &'static str,
+}
+
+/// A looping piece of music
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Track {
+    /// Steps per minute
+    pub tempo: f32,
+    pub parts: &'static [Part],
+}
+
+/// Theme played during the game
+pub const THEME: Track = Track {
+    tempo: 280.0,
+    parts: &[
+        Part {
+            wave: Waveform::Square { duty: 0.25 },
+            volume: 0.15,
+            notes: "A4 - C5 - E5 - D5 C5 B4 - G4 - A4 - - - \
+                    A4 - C5 - E5 - G5 E5 F5 - E5 - D5 - - -",
+        },
+        Part {
+            wave: Waveform::Triangle,
+            volume: 0.3,
+            notes: "A2 A2 A3 A2 A2 A2 A3 A2 G2 G2 G3 G2 G2 G2 G3 G2 \
+                    F2 F2 F3 F2 F2 F2 F3 F2 E2 E2 E3 E2 E2 E2 E3 E2",
+        },
+        Part {
+            wave: Waveform::Noise,
+            volume: 0.1,
+            notes: "C2 - C7 - C2 - C7 C7 C2 - C7 - C2 - C7 C7 \
+                    C2 - C7 - C2 - C7 C7 C2 - C7 - C2 C2 C7 C7",
+        },
+    ],
+};
+
+impl Track {
+    /// Seconds per step
+    pub fn step(&self) -> f32 {
+        60.0 / self.tempo
+    }
+
+    /// Steps in one loop: the longest part
+    pub fn steps(&self) -> usize {
+        self.parts
+            .iter()
+            .map(|part| part.notes.split_whitespace().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Every note in one loop; unknown note names are rests
+    pub fn notes(&self) -> Vec<Note> {
+        let step = self.step();
+        self.parts
+            .iter()
+            .flat_map(|part| {
+                part.notes
+                    .split_whitespace()
+                    .enumerate()
+                    .filter_map(move |(i, name)| {
+                        let note =
+                            Note::new(part.wave, pitch(name)?, i as f32 * step, step * NOTE_HOLD);
+                        Some(note.with_volume(part.volume).with_envelope(Envelope::ORGAN))
+                    })
+            })
+            .collect()
+    }
+
+    /// One loop, exactly `steps` long so it repeats without a gap
+    pub fn render(&self, sample_rate: u32) -> Pcm {
+        let mut pcm = Pcm::render(&self.notes(), sample_rate);
+        let length = (self.steps() as f32 * self.step() * sample_rate as f32) as usize;
+        pcm.samples.resize(length, 0.0);
+        pcm
+    }
+}
+
+/// Plays sound effects over a looping soundtrack
+pub struct Mixer {
+    sample_rate: u32,
+    /// Every effect, rendered up front
+    effects: HashMap<Sound, Pcm>,
+    /// Effects playing and how far into each
+    voices: Vec<(Sound, usize)>,
+    /// Soundtrack loop and how far into it
+    music: Option<(Pcm, usize)>,
+}
+
+impl Mixer {
+    /// Mixer with no music, rendering at `sample_rate`
+    pub fn new(sample_rate: u32) -> Self {
+        let effects = Sound::all()
+            .iter()
+            .map(|&sound| (sound, Pcm::render(&effect(sound), sample_rate)))
+            .collect();
+        Self {
+            sample_rate,
+            effects,
+            voices: Vec::new(),
+            music: None,
+        }
+    }
+
+    /// Loop `track` under the effects
+    pub fn with_music(mut self, track: &Track) -> Self {
+        let music = track.render(self.sample_rate);
+        self.music = (!music.samples.is_empty()).then_some((music, 0));
+        self
+    }
+
+    /// Fill `out` with the next samples
+    pub fn fill(&mut self, out: &mut [f32]) {
+        out.fill(0.0);
+        if let Some((music, position)) = &mut self.music {
+            for sample in out.iter_mut() {
+                *sample = music.samples[*position] * MUSIC_VOLUME;
+                *position = (*position + 1) % music.samples.len();
+            }
+        }
+        for (sound, position) in &mut self.voices {
+            let samples = &self.effects[sound].samples;
+            for (sample, effect) in out
+                .iter_mut()
+                .zip(&samples[(*position).min(samples.len())..])
+            {
+                *sample += effect;
+            }
+            *position += out.len();
+        }
+        self.voices
+            .retain(|(sound, position)| *position < self.effects[sound].samples.len());
+        for sample in out.iter_mut() {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+    }
+
+    /// The next `seconds` of output
+    pub fn render(&mut self, seconds: f32) -> Pcm {
+        let mut samples = vec![0.0; (seconds * self.sample_rate as f32) as usize];
+        self.fill(&mut samples);
+        Pcm {
+            sample_rate: self.sample_rate,
+            samples,
+        }
+    }
+}
+
+impl AudioSink for Mixer {
+    fn play(&mut self, sound: Sound) {
+        if self.voices.len() >= MAX_VOICES {
+            self.voices.remove(0);
+        }
+        self.voices.push((sound, 0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+    use crate::tui::game::{GameState, LASER_COOLDOWN};
+
+    const RATE: u32 = 8000;
+
+    #[test]
+    fn test_note_names() {
+        assert_eq!(pitch("A4"), Some(440.0));
+        assert_eq!(pitch("A5"), Some(880.0));
+        assert!((pitch("C4").unwrap() - 261.63).abs() < 0.01);
+        assert!((pitch("C#4").unwrap() - 277.18).abs() < 0.01);
+        assert_eq!(pitch("-"), None);
+        assert_eq!(pitch("H2"), None);
+    }
+
+    #[test]
+    fn test_envelope_stages() {
+        let env = Envelope {
+            attack: 0.1,
+            decay: 0.1,
+            sustain: 0.5,
+            release: 0.2,
+        };
+        assert_eq!(env.level(0.05, 1.0), 0.5);
+        assert_eq!(env.level(0.1, 1.0), 1.0);
+        assert_eq!(env.level(0.5, 1.0), 0.5);
+        assert!((env.level(1.1, 1.0) - 0.25).abs() < 1e-6);
+        assert_eq!(env.level(1.3, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_waveforms_reach_their_volume() {
+        for wave in [
+            Waveform::Square { duty: 0.5 },
+            Waveform::Triangle,
+            Waveform::Noise,
+        ] {
+            let note = Note::new(wave, 440.0, 0.0, 0.5)
+                .with_volume(0.5)
+                .with_envelope(Envelope::ORGAN);
+            let pcm = Pcm::render(&[note], RATE);
+            assert!((pcm.duration() - note.end()).abs() < 0.001, "{wave:?}");
+            assert!(
+                (pcm.peak() - 0.5).abs() < 0.01,
+                "{wave:?} peaked at {}",
+                pcm.peak()
+            );
+        }
+    }
+
+    #[test]
+    fn test_effects_are_short_and_audible() {
+        for &sound in Sound::all() {
+            let pcm = Pcm::render(&effect(sound), RATE);
+            assert!(pcm.duration() > 0.0 && pcm.duration() <= 1.5, "{sound:?}");
+            assert!(pcm.peak() > 0.2 && pcm.peak() <= 1.0, "{sound:?}");
+        }
+    }
+
+    #[test]
+    fn test_theme_loops_whole_steps() {
+        assert_eq!(THEME.steps(), 32);
+        // Every token is a note or a rest
+        for part in THEME.parts {
+            for name in part.notes.split_whitespace() {
+                assert!(name == "-" || pitch(name).is_some(), "bad note {name}");
+            }
+        }
+        let pcm = THEME.render(RATE);
+        let expected = THEME.steps() as f32 * THEME.step();
+        assert!((pcm.duration() - expected).abs() < 0.001);
+        assert!(pcm.peak() > 0.1 && pcm.peak() <= 1.0);
+    }
+
+    #[test]
+    fn test_mixer_plays_effects_over_music() {
+        let mut quiet = Mixer::new(RATE);
+        assert_eq!(quiet.render(0.1).peak(), 0.0);
+
+        quiet.play(Sound::Explosion);
+        let bang = quiet.render(0.1);
+        assert!(bang.peak() > 0.2);
+        // The effect finishes and is dropped
+        quiet.render(2.0);
+        assert!(quiet.voices.is_empty());
+
+        // The music comes round again after one loop
+        let mut mixer = Mixer::new(RATE).with_music(&THEME);
+        let loop_length = THEME.render(RATE).samples.len();
+        let mut first = vec![0.0; 1000];
+        let mut rest = vec![0.0; loop_length - 1000];
+        let mut again = vec![0.0; 1000];
+        mixer.fill(&mut first);
+        mixer.fill(&mut rest);
+        mixer.fill(&mut again);
+        assert_eq!(again, first);
+    }
+
+    #[test]
+    fn test_game_events_drive_the_mixer() {
+        let mut game = GameState::with_seed(1);
+        game.frame = LASER_COOLDOWN + 1;
+        game.fire_laser();
+
+        let mut mixer = Mixer::new(RATE);
+        game.drain_sounds(&mut mixer);
+        assert_eq!(mixer.voices.len(), 1);
+        assert!(mixer.render(0.05).peak() > 0.2);
+    }
+
+    #[test]
+    fn test_wav_file_matches_pcm() {
+        let pcm = Pcm::render(&effect(Sound::LaserFire), RATE);
+        let path = env::temp_dir().join(format!("spacey-laser-{}.wav", std::process::id()));
+        pcm.write_wav(fs::File::create(&path).unwrap()).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(bytes.len(), 44 + pcm.samples.len() * 2);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[8..12], b"WAVE");
+        let rate = u32::from_le_bytes(bytes[24..28].try_into().unwrap());
+        assert_eq!(rate, RATE);
+        let peak = bytes[44..]
+            .chunks(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]).unsigned_abs())
+            .max()
+            .unwrap();
+        assert!((peak as f32 / i16::MAX as f32 - pcm.peak()).abs() < 0.001);
+    }
+}
@@ -0,0 +1,160 @@
+//! Target lock-on and homing missiles
+//!
+//! Aim is measured as an enemy's offset from the crosshair in fractions of
+//! the view's width and height (see `GameState::view_offset`), so locking
+//! works the same at any terminal size.
+
+/// Frames an enemy must stay in the crosshair to lock
+pub const LOCK_FRAMES: u8 = 10;
+/// Half-size of the crosshair, in view fractions
+const LOCK_BOX: f32 = 0.05;
+/// A lock breaks once its target strays this far from the crosshair
+const BREAK_BOX: f32 = 0.3;
+/// Depth a missile travels per frame
+pub const MISSILE_SPEED: f32 = 2.5;
+/// Furthest a missile turns sideways per frame, in view units
+const MISSILE_TURN: f32 = 0.08;
+
+/// Which enemy the crosshair is locking or locked on to
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LockOn {
+    /// Enemy in the crosshair and frames it has stayed there
+    pub(super) tracking: Option<(u32, u8)>,
+    /// Enemy locked on to
+    pub(super) locked: Option<u32>,
+}
+
+impl LockOn {
+    /// Advance one frame given each enemy's id and offset from the crosshair
+    pub fn update(&mut self, in_view: impl IntoIterator<Item = (u32, (f32, f32))>) {
+        let distance = |(x, y): (f32, f32)| x.abs().max(y.abs());
+        let in_view: Vec<(u32, f32)> = in_view
+            .into_iter()
+            .map(|(id, offset)| (id, distance(offset)))
+            .collect();
+
+        if let Some(target) = self.locked {
+            let held = in_view.iter().any(|&(id, d)| id == target && d < BREAK_BOX);
+            if !held {
+                self.locked = None;
+            }
+            return;
+        }
+
+        let nearest = in_view
+            .iter()
+            .filter(|&&(_, d)| d < LOCK_BOX)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|&(id, _)| id);
+        self.tracking = match (nearest, self.tracking) {
+            (Some(id), Some((tracked, frames))) if id == tracked => Some((id, frames + 1)),
+            (Some(id), _) => Some((id, 1)),
+            (None, _) => None,
+        };
+        if let Some((id, frames)) = self.tracking
+            && frames >= LOCK_FRAMES
+        {
+            self.locked = Some(id);
+            self.tracking = None;
+        }
+    }
+
+    /// The locked enemy, if any
+    pub fn target(&self) -> Option<u32> {
+        self.locked
+    }
+
+    /// Frames the enemy in the crosshair has been held there while locking
+    pub fn progress(&self) -> Option<u8> {
+        self.tracking.map(|(_, frames)| frames)
+    }
+}
+
+/// A homing missile
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Missile {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    /// Enemy it homes on
+    pub target: u32,
+}
+
+impl Missile {
+    /// Launch from the ship at `target`
+    pub fn launch(x: f32, y: f32, target: u32) -> Self {
+        Self {
+            x,
+            y,
+            z: 0.0,
+            target,
+        }
+    }
+
+    /// Fly one frame, turning toward the target's position if it's still
+    /// around
+    pub fn update(&mut self, target: Option<(f32, f32)>) {
+        if let Some((x, y)) = target {
+            self.x += (x - self.x).clamp(-MISSILE_TURN, MISSILE_TURN);
+            self.y += (y - self.y).clamp(-MISSILE_TURN, MISSILE_TURN);
+        }
+        self.z += MISSILE_SPEED;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_needs_a_steady_aim() {
+        let mut lock = LockOn::default();
+        for _ in 0..LOCK_FRAMES - 1 {
+            lock.update([(3, (0.01, 0.02)), (4, (0.2, 0.0))]);
+        }
+        assert_eq!(lock.progress(), Some(LOCK_FRAMES - 1));
+        assert_eq!(lock.target(), None);
+
+        // Losing the enemy for a frame starts over
+        lock.update([(3, (0.1, 0.0))]);
+        assert_eq!(lock.progress(), None);
+        for _ in 0..LOCK_FRAMES {
+            lock.update([(3, (0.0, 0.0))]);
+        }
+        assert_eq!(lock.target(), Some(3));
+        assert_eq!(lock.progress(), None);
+    }
+
+    #[test]
+    fn test_lock_breaks_when_target_strays_or_dies() {
+        let mut lock = LockOn::default();
+        for _ in 0..LOCK_FRAMES {
+            lock.update([(3, (0.0, 0.0))]);
+        }
+        // Drifting a little out of the crosshair keeps the lock
+        lock.update([(3, (0.2, 0.0))]);
+        assert_eq!(lock.target(), Some(3));
+
+        lock.update([(3, (0.5, 0.0))]);
+        assert_eq!(lock.target(), None);
+
+        for _ in 0..LOCK_FRAMES {
+            lock.update([(3, (0.0, 0.0))]);
+        }
+        lock.update([]);
+        assert_eq!(lock.target(), None);
+    }
+
+    #[test]
+    fn test_missile_homes_in() {
+        let mut missile = Missile::launch(0.0, 0.0, 1);
+        missile.update(Some((0.5, -0.02)));
+        assert_eq!((missile.x, missile.y), (MISSILE_TURN, -0.02));
+        assert_eq!(missile.z, MISSILE_SPEED);
+
+        // With the target gone it flies straight on
+        missile.update(None);
+        assert_eq!(missile.x, MISSILE_TURN);
+        assert_eq!(missile.z, MISSILE_SPEED * 2.0);
+    }
+}
@@ -0,0 +1,405 @@
+//! Color themes
+//!
+//! Renderers never pick colors directly; they ask the [`Theme`] for the color
+//! of a role. A theme is built from a [`Palette`] and the terminal's
+//! [`ColorDepth`], so palettes degrade to the nearest 256 or 16 color
+//! equivalents on terminals without truecolor.
+//!
+//! Color is never the only cue. With shape cues on (forced by `NO_COLOR` and
+//! the monochrome palette) each role also gets its own text modifier, so
+//! enemies, lasers and the trench stay distinct without any hue at all.
+
+use std::env;
+
+use ratatui::style::{Color, Modifier, Style};
+
+use super::{cycle::Cycle, sprite::ColorHint};
+
+/// Built-in color schemes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Palette {
+    #[default]
+    Phosphor,
+    Amber,
+    Monochrome,
+    HighContrast,
+    FullColor,
+    /// Blue and orange, safe for protanopia and deuteranopia
+    RedGreenSafe,
+    /// Cyan and red, safe for tritanopia
+    BlueYellowSafe,
+}
+
+impl Cycle for Palette {
+    const ALL: &'static [Self] = &[
+        Palette::Phosphor,
+        Palette::Amber,
+        Palette::Monochrome,
+        Palette::HighContrast,
+        Palette::FullColor,
+        Palette::RedGreenSafe,
+        Palette::BlueYellowSafe,
+    ];
+}
+
+impl Palette {
+    /// Get the display text for this palette
+    pub fn label(&self) -> &'static str {
+        match self {
+            Palette::Phosphor => "PHOSPHOR",
+            Palette::Amber => "AMBER",
+            Palette::Monochrome => "MONOCHROME",
+            Palette::HighContrast => "HIGH CONTRAST",
+            Palette::FullColor => "FULL COLOR",
+            Palette::RedGreenSafe => "RED-GREEN SAFE",
+            Palette::BlueYellowSafe => "BLUE-YELLOW SAFE",
+        }
+    }
+
+    /// Parse a palette name such as `amber` or `high-contrast`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase().replace(['_', ' '], "-");
+        match name.as_str() {
+            "phosphor" | "green" => Some(Palette::Phosphor),
+            "amber" => Some(Palette::Amber),
+            "monochrome" | "mono" => Some(Palette::Monochrome),
+            "high-contrast" => Some(Palette::HighContrast),
+            "full-color" | "color" => Some(Palette::FullColor),
+            "red-green-safe" | "deuteranopia" | "protanopia" => Some(Palette::RedGreenSafe),
+            "blue-yellow-safe" | "tritanopia" => Some(Palette::BlueYellowSafe),
+            _ => None,
+        }
+    }
+
+    /// Truecolor values for (normal, dim, bright, alert)
+    fn colors(&self) -> [(u8, u8, u8); 4] {
+        match self {
+            Palette::Phosphor => [(0, 200, 0), (0, 100, 0), (50, 255, 50), (230, 30, 30)],
+            Palette::Amber => [(255, 176, 0), (140, 90, 0), (255, 220, 80), (255, 80, 40)],
+            Palette::Monochrome => [
+                (190, 190, 190),
+                (100, 100, 100),
+                (255, 255, 255),
+                (255, 255, 255),
+            ],
+            Palette::HighContrast => [
+                (255, 255, 255),
+                (200, 200, 200),
+                (255, 255, 0),
+                (255, 80, 80),
+            ],
+            Palette::FullColor => [(0, 200, 255), (60, 80, 160), (255, 230, 0), (255, 40, 40)],
+            // Okabe-Ito sky blue, blue, yellow and vermilion, which also
+            // differ in brightness
+            Palette::RedGreenSafe => [(86, 180, 233), (0, 114, 178), (240, 228, 66), (213, 94, 0)],
+            Palette::BlueYellowSafe => [
+                (210, 210, 210),
+                (110, 110, 110),
+                (0, 220, 220),
+                (255, 40, 90),
+            ],
+        }
+    }
+}
+
+/// How many colors the terminal can show
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorDepth {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    /// Color disabled with `NO_COLOR`
+    NoColor,
+}
+
+impl ColorDepth {
+    /// Guess the color depth from the `NO_COLOR`, `COLORTERM` and `TERM`
+    /// variables
+    pub fn detect() -> Self {
+        let no_color = env::var("NO_COLOR").ok();
+        let colorterm = env::var("COLORTERM").ok();
+        let term = env::var("TERM").ok();
+        Self::from_env(no_color.as_deref(), colorterm.as_deref(), term.as_deref())
+    }
+
+    /// Color depth implied by the given `NO_COLOR`, `COLORTERM` and `TERM`
+    /// values
+    ///
+    /// Per <https://no-color.org>, an empty `NO_COLOR` does not count.
+    pub fn from_env(no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if no_color.is_some_and(|v| !v.is_empty()) {
+            ColorDepth::NoColor
+        } else if matches!(colorterm, Some("truecolor" | "24bit")) {
+            ColorDepth::TrueColor
+        } else if term.is_some_and(|t| t.contains("256color")) {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// Colors for every role in the UI, resolved for the terminal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub palette: Palette,
+    /// Screen background
+    pub background: Color,
+    /// Cockpit, title and selected text
+    pub normal: Color,
+    /// Trench, stars and secondary text
+    pub dim: Color,
+    /// Enemies, crosshair and HUD
+    pub bright: Color,
+    /// Lasers and warnings
+    pub alert: Color,
+    /// Add a distinct modifier to every role so nothing relies on hue
+    pub shape_cues: bool,
+    /// Terminal color depth the colors were resolved for
+    pub depth: ColorDepth,
+}
+
+impl Theme {
+    /// Resolve a palette for a terminal's color depth
+    ///
+    /// Shape cues are always on without color, and with the monochrome
+    /// palette, where lasers and enemies share a color.
+    pub fn new(palette: Palette, depth: ColorDepth) -> Self {
+        let [normal, dim, bright, alert] = palette.colors().map(|c| downsample(c, depth));
+        let background = if depth == ColorDepth::NoColor {
+            Color::Reset
+        } else {
+            Color::Black
+        };
+        let theme = Self {
+            palette,
+            background,
+            normal,
+            dim,
+            bright,
+            alert,
+            shape_cues: false,
+            depth,
+        };
+        theme.with_shape_cues(theme.is_monochrome())
+    }
+
+    /// Whether the screen is drawn without hue, by choice or by `NO_COLOR`
+    pub fn is_monochrome(&self) -> bool {
+        self.depth == ColorDepth::NoColor || self.palette == Palette::Monochrome
+    }
+
+    /// Turn shape cues on even when the palette does not need them
+    pub fn with_shape_cues(mut self, enabled: bool) -> Self {
+        self.shape_cues |= enabled;
+        self
+    }
+
+    /// Color for an asset's color hint
+    pub fn color(&self, hint: ColorHint) -> Color {
+        match hint {
+            ColorHint::Normal => self.normal,
+            ColorHint::Dim => self.dim,
+            ColorHint::Bright => self.bright,
+            ColorHint::Alert => self.alert,
+        }
+    }
+
+    /// Text style for a color hint, including its shape cue when enabled
+    pub fn style(&self, hint: ColorHint) -> Style {
+        let style = Style::default().fg(self.color(hint));
+        if !self.shape_cues {
+            return style;
+        }
+        style.add_modifier(match hint {
+            ColorHint::Normal => Modifier::empty(),
+            ColorHint::Dim => Modifier::DIM,
+            ColorHint::Bright => Modifier::BOLD,
+            ColorHint::Alert => Modifier::BOLD | Modifier::REVERSED,
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(Palette::default(), ColorDepth::default())
+    }
+}
+
+/// Nearest color the terminal can show
+fn downsample((r, g, b): (u8, u8, u8), depth: ColorDepth) -> Color {
+    match depth {
+        ColorDepth::TrueColor => Color::Rgb(r, g, b),
+        ColorDepth::Ansi256 => Color::Indexed(nearest_256(r, g, b)),
+        ColorDepth::Ansi16 => nearest_16(r, g, b),
+        ColorDepth::NoColor => Color::Reset,
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Closest entry in the xterm 6x6x6 color cube or grayscale ramp
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |v: u8| {
+        (0..LEVELS.len())
+            .min_by_key(|&i| (i32::from(LEVELS[i]) - i32::from(v)).abs())
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    // Grays 232..=255 run from 8 to 238 in steps of 10
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + gray_step * 10;
+    let gray = (gray_level, gray_level, gray_level);
+
+    if distance((r, g, b), gray) < distance((r, g, b), cube) {
+        232 + gray_step
+    } else {
+        cube_index as u8
+    }
+}
+
+/// Closest of the 16 standard ANSI colors
+fn nearest_16(r: u8, g: u8, b: u8) -> Color {
+    const ANSI: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (128, 0, 0)),
+        (Color::Green, (0, 128, 0)),
+        (Color::Yellow, (128, 128, 0)),
+        (Color::Blue, (0, 0, 128)),
+        (Color::Magenta, (128, 0, 128)),
+        (Color::Cyan, (0, 128, 128)),
+        (Color::Gray, (192, 192, 192)),
+        (Color::DarkGray, (128, 128, 128)),
+        (Color::LightRed, (255, 0, 0)),
+        (Color::LightGreen, (0, 255, 0)),
+        (Color::LightYellow, (255, 255, 0)),
+        (Color::LightBlue, (0, 0, 255)),
+        (Color::LightMagenta, (255, 0, 255)),
+        (Color::LightCyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+    ANSI.iter()
+        .min_by_key(|(_, rgb)| distance((r, g, b), *rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::White)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_depth() {
+        assert_eq!(
+            ColorDepth::from_env(None, Some("truecolor"), Some("xterm")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, None, Some("linux")),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(ColorDepth::from_env(None, None, None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn test_no_color_wins() {
+        assert_eq!(
+            ColorDepth::from_env(Some("1"), Some("truecolor"), Some("xterm-256color")),
+            ColorDepth::NoColor
+        );
+        // An empty NO_COLOR is ignored
+        assert_eq!(
+            ColorDepth::from_env(Some(""), Some("truecolor"), None),
+            ColorDepth::TrueColor
+        );
+
+        let theme = Theme::new(Palette::Phosphor, ColorDepth::NoColor);
+        assert!(theme.shape_cues);
+        assert_eq!(theme.background, Color::Reset);
+        assert_eq!(theme.alert, Color::Reset);
+    }
+
+    #[test]
+    fn test_shape_cues_are_distinct() {
+        let theme = Theme::default();
+        assert!(!theme.shape_cues);
+        assert_eq!(
+            theme.style(ColorHint::Alert).add_modifier,
+            Modifier::empty()
+        );
+        assert!(Theme::new(Palette::Monochrome, ColorDepth::TrueColor).shape_cues);
+
+        let theme = theme.with_shape_cues(true);
+        let hints = [
+            ColorHint::Normal,
+            ColorHint::Dim,
+            ColorHint::Bright,
+            ColorHint::Alert,
+        ];
+        for (i, a) in hints.iter().enumerate() {
+            for b in &hints[i + 1..] {
+                assert_ne!(
+                    theme.style(*a).add_modifier,
+                    theme.style(*b).add_modifier,
+                    "{a:?} and {b:?} share a modifier"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_truecolor_keeps_rgb() {
+        let theme = Theme::new(Palette::Phosphor, ColorDepth::TrueColor);
+        assert_eq!(theme.normal, Color::Rgb(0, 200, 0));
+        assert_eq!(theme.dim, Color::Rgb(0, 100, 0));
+    }
+
+    #[test]
+    fn test_256_color_fallback() {
+        let theme = Theme::new(Palette::Phosphor, ColorDepth::Ansi256);
+        // Pure green at the 215 cube level
+        assert_eq!(theme.normal, Color::Indexed(40));
+        let theme = Theme::new(Palette::Monochrome, ColorDepth::Ansi256);
+        assert_eq!(theme.bright, Color::Indexed(231));
+        assert!(matches!(theme.dim, Color::Indexed(232..=255)));
+    }
+
+    #[test]
+    fn test_16_color_fallback() {
+        let theme = Theme::new(Palette::Phosphor, ColorDepth::Ansi16);
+        assert_eq!(theme.dim, Color::Green);
+        assert_eq!(theme.bright, Color::LightGreen);
+        assert_eq!(theme.alert, Color::LightRed);
+
+        let theme = Theme::new(Palette::Amber, ColorDepth::Ansi16);
+        assert!(matches!(theme.normal, Color::Yellow | Color::LightYellow));
+    }
+
+    #[test]
+    fn test_palette_cycle_and_names() {
+        let mut palette = Palette::Phosphor;
+        for _ in Palette::ALL {
+            palette = palette.next();
+        }
+        assert_eq!(palette, Palette::Phosphor);
+        assert_eq!(Palette::Phosphor.previous(), Palette::BlueYellowSafe);
+
+        for palette in Palette::ALL {
+            let name = palette.label().to_lowercase();
+            assert_eq!(Palette::from_name(&name), Some(*palette));
+        }
+    }
+}
@@ -1,59 +1,92 @@
-//! UI rendering for the start menu
+//! UI rendering for the start menu and options screen
 
 use ratatui::{
     prelude::*,
     widgets::{Block, Paragraph},
 };
 
-use super::{app::App, menu::MenuItem};
+use super::{
+    app::App,
+    assets::Assets,
+    menu::MenuItem,
+    options::OptionItem,
+    settings::Settings,
+    sprite::{ColorHint, Sprite},
+    theme::Theme,
+};
 
-/// Retro phosphor green color
-const PHOSPHOR_GREEN: Color = Color::Rgb(0, 200, 0);
-const PHOSPHOR_GREEN_DIM: Color = Color::Rgb(0, 120, 0);
+/// Render the entire UI
+pub fn render(frame: &mut Frame, app: &App, assets: &Assets, theme: &Theme) {
+    let layout = screen_layout(frame, theme);
 
-/// ASCII art title banner
-const TITLE_ART: &str = r#"
-  ███████╗██████╗  █████╗  ██████╗███████╗██╗   ██╗
-  ██╔════╝██╔══██╗██╔══██╗██╔════╝██╔════╝╚██╗ ██╔╝
-  ███████╗██████╔╝███████║██║     █████╗   ╚████╔╝ 
-  ╚════██║██╔═══╝ ██╔══██║██║     ██╔══╝    ╚██╔╝  
-  ███████║██║     ██║  ██║╚██████╗███████╗   ██║   
-  ╚══════╝╚═╝     ╚═╝  ╚═╝ ╚═════╝╚══════╝   ╚═╝   
-"#;
+    render_title(frame, layout[0], assets.title.frame(0.0), theme);
+    render_menu(frame, layout[1], app, theme);
+    let prompt = app.notice().unwrap_or("PRESS ENTER.");
+    render_footer(frame, layout[2], prompt, theme);
+}
 
-/// Render the entire UI
-pub fn render(frame: &mut Frame, app: &App) {
+/// Render the options screen
+pub fn render_options(
+    frame: &mut Frame,
+    options: &App<OptionItem>,
+    settings: &Settings,
+    assets: &Assets,
+    theme: &Theme,
+) {
+    let layout = screen_layout(frame, theme);
+
+    render_title(frame, layout[0], assets.title.frame(0.0), theme);
+    render_option_items(frame, layout[1], options, settings, theme);
+    render_footer(
+        frame,
+        layout[2],
+        "LEFT/RIGHT: CHANGE   ENTER: SELECT   ESC: BACK",
+        theme,
+    );
+}
+
+/// Clear the screen and split it into title, body and footer
+fn screen_layout(frame: &mut Frame, theme: &Theme) -> std::rc::Rc<[Rect]> {
     let area = frame.area();
 
     // Create dark background
-    let block = Block::default().style(Style::default().bg(Color::Black));
+    let block = Block::default().style(Style::default().bg(theme.background));
     frame.render_widget(block, area);
 
     // Layout: Title at top, menu in center, footer at bottom
-    let layout = Layout::default()
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(9), // Title
             Constraint::Min(8),    // Menu
             Constraint::Length(3), // Footer
         ])
-        .split(area);
-
-    render_title(frame, layout[0]);
-    render_menu(frame, layout[1], app);
-    render_footer(frame, layout[2]);
+        .split(area)
 }
 
-/// Render the ASCII art title
-fn render_title(frame: &mut Frame, area: Rect) {
-    let title = Paragraph::new(TITLE_ART)
-        .style(Style::default().fg(PHOSPHOR_GREEN))
-        .alignment(Alignment::Center);
-    frame.render_widget(title, area);
+/// Render the ASCII art title, centered below a blank line
+fn render_title(frame: &mut Frame, area: Rect, art: &Sprite, theme: &Theme) {
+    let left = area.x + area.width.saturating_sub(art.width()) / 2;
+    let style = theme.style(ColorHint::Normal);
+    let buf = frame.buffer_mut();
+
+    for (row, line) in art.rows().iter().enumerate() {
+        let y = area.y + 1 + row as u16;
+        if y >= area.bottom() {
+            break;
+        }
+        buf.set_stringn(
+            left,
+            y,
+            line,
+            area.right().saturating_sub(left) as usize,
+            style,
+        );
+    }
 }
 
 /// Render the menu items
-fn render_menu(frame: &mut Frame, area: Rect, app: &App) {
+fn render_menu(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
     let items = MenuItem::all();
 
     // Calculate vertical centering
@@ -76,13 +109,7 @@ fn render_menu(frame: &mut Frame, area: Rect, app: &App) {
             let prefix = if is_selected { "> " } else { "  " };
             let text = format!("{}{}", prefix, item.label());
 
-            let style = if is_selected {
-                Style::default().fg(PHOSPHOR_GREEN).bold()
-            } else {
-                Style::default().fg(PHOSPHOR_GREEN_DIM)
-            };
-
-            Line::from(Span::styled(text, style))
+            Line::from(Span::styled(text, item_style(is_selected, theme)))
         })
         .collect();
 
@@ -91,10 +118,60 @@ fn render_menu(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(menu, menu_area);
 }
 
+/// Render the option rows with their current values
+fn render_option_items(
+    frame: &mut Frame,
+    area: Rect,
+    options: &App<OptionItem>,
+    settings: &Settings,
+    theme: &Theme,
+) {
+    let items = options.items();
+
+    // Calculate vertical centering
+    let menu_height = items.len() as u16;
+    let vertical_padding = (area.height.saturating_sub(menu_height)) / 2;
+
+    let menu_area = Rect {
+        x: area.x,
+        y: area.y + vertical_padding,
+        width: area.width,
+        height: menu_height,
+    };
+
+    let lines: Vec<Line> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let is_selected = i == options.selected_index();
+            let prefix = if is_selected { "> " } else { "  " };
+            let text = match item.value(settings) {
+                Some(value) => format!("{}{:<12}< {} >", prefix, item.label(), value),
+                None => format!("{}{}", prefix, item.label()),
+            };
+            Line::from(Span::styled(text, item_style(is_selected, theme)))
+        })
+        .collect();
+
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        menu_area,
+    );
+}
+
+/// Style for a menu row
+fn item_style(is_selected: bool, theme: &Theme) -> Style {
+    if is_selected {
+        theme.style(ColorHint::Normal).bold()
+    } else {
+        theme.style(ColorHint::Dim)
+    }
+}
+
 /// Render the footer prompt
-fn render_footer(frame: &mut Frame, area: Rect) {
-    let footer = Paragraph::new("PRESS ENTER.")
-        .style(Style::default().fg(PHOSPHOR_GREEN_DIM))
+fn render_footer(frame: &mut Frame, area: Rect, prompt: &str, theme: &Theme) {
+    let footer = Paragraph::new(prompt)
+        .style(theme.style(ColorHint::Dim))
         .alignment(Alignment::Center);
     frame.render_widget(footer, area);
 }